@@ -5,7 +5,11 @@ module cyrus_protocol::settlement {
     use std::vector;
     use aptos_framework::coin::{Self, Coin};
     use aptos_framework::account;
+    use aptos_framework::dispatchable_fungible_asset;
     use aptos_framework::event::{Self, EventHandle};
+    use aptos_framework::fungible_asset::{Self, FungibleStore, Metadata};
+    use aptos_framework::object::{Self, ExtendRef, Object};
+    use aptos_framework::primary_fungible_store;
     use aptos_framework::timestamp;
     use aptos_std::table::{Self, Table};
 
@@ -20,6 +24,7 @@ module cyrus_protocol::settlement {
     const E_UNAUTHORIZED_RELAYER: u64 = 6;
     const E_INVALID_AMOUNT: u64 = 7;
     const E_VAULT_ALREADY_EXISTS: u64 = 8;
+    const E_ASSET_NOT_REGISTERED: u64 = 9;
 
     struct SettlementInstruction has copy, drop, store {
         source_chain: String,           // "solana"
@@ -40,6 +45,13 @@ module cyrus_protocol::settlement {
         nonce: u64,
     }
 
+    // Emitted by settle_batch for an item it could not settle; `reason` is the E_* code
+    #[event]
+    struct SettlementSkipped has drop, store {
+        vault_owner: address,
+        source_tx_hash: String,
+        reason: u64,
+    }
 
     struct DepositEvent has drop, store {
         depositor: address,
@@ -53,13 +65,31 @@ module cyrus_protocol::settlement {
         usdc_balance: Coin<USDC>,
         owner: address,
         authorized_relayers: vector<address>,
-        processed_instructions: Table<String, bool>, // tx_hash -> settled
+        processed_instructions: Table<String, bool>, // settlement key -> settled
         total_settled: u64,
         settlement_events: EventHandle<SettlementEvent>,
         deposit_events: EventHandle<DepositEvent>,
         vault_created_at: u64,
     }
 
+    // Balance of a coin type other than the vault's built-in USDC, held alongside the Vault
+    struct CoinReserve<phantom CoinType> has key {
+        coins: Coin<CoinType>,
+        total_settled: u64,
+    }
+
+    // Fungible Asset balances held alongside the Vault, keyed by metadata object address
+    struct FungibleReserves has key {
+        reserves: Table<address, FungibleReserve>,
+    }
+
+    // A store owned by its own object, so settlements can withdraw without the owner's signer
+    struct FungibleReserve has store {
+        store: Object<FungibleStore>,
+        extend_ref: ExtendRef,
+        total_settled: u64,
+    }
+
     
     public entry fun initialize_vault(owner: &signer) {
         let owner_addr = signer::address_of(owner);
@@ -104,6 +134,71 @@ module cyrus_protocol::settlement {
         event::emit_event(&mut vault.deposit_events, deposit_event);
     }
 
+    // Deposit CoinType into the vault, registering a reserve for it on first use
+    public entry fun deposit_coin<CoinType>(owner: &signer, amount: u64) acquires Vault, CoinReserve {
+        let owner_addr = signer::address_of(owner);
+        assert!(exists<Vault>(owner_addr), error::not_found(E_VAULT_NOT_INITIALIZED));
+        assert!(amount > 0, error::invalid_argument(E_INVALID_AMOUNT));
+
+        if (!exists<CoinReserve<CoinType>>(owner_addr)) {
+            move_to(owner, CoinReserve<CoinType> { coins: coin::zero<CoinType>(), total_settled: 0 });
+        };
+
+        let reserve = borrow_global_mut<CoinReserve<CoinType>>(owner_addr);
+        coin::merge(&mut reserve.coins, coin::withdraw<CoinType>(owner, amount));
+        let new_vault_balance = coin::value(&reserve.coins);
+
+        let vault = borrow_global_mut<Vault>(owner_addr);
+        let deposit_event = DepositEvent {
+            depositor: owner_addr,
+            amount,
+            new_vault_balance,
+            timestamp: timestamp::now_microseconds(),
+        };
+        event::emit_event(&mut vault.deposit_events, deposit_event);
+    }
+
+    // Deposit a Fungible Asset into the vault, creating a store for it on first use
+    public entry fun deposit_fa(
+        owner: &signer,
+        metadata: Object<Metadata>,
+        amount: u64
+    ) acquires Vault, FungibleReserves {
+        let owner_addr = signer::address_of(owner);
+        assert!(exists<Vault>(owner_addr), error::not_found(E_VAULT_NOT_INITIALIZED));
+        assert!(amount > 0, error::invalid_argument(E_INVALID_AMOUNT));
+
+        if (!exists<FungibleReserves>(owner_addr)) {
+            move_to(owner, FungibleReserves { reserves: table::new<address, FungibleReserve>() });
+        };
+
+        let reserves = &mut borrow_global_mut<FungibleReserves>(owner_addr).reserves;
+        let metadata_addr = object::object_address(&metadata);
+        if (!table::contains(reserves, metadata_addr)) {
+            let constructor_ref = object::create_object(owner_addr);
+            let reserve = FungibleReserve {
+                store: fungible_asset::create_store(&constructor_ref, metadata),
+                extend_ref: object::generate_extend_ref(&constructor_ref),
+                total_settled: 0,
+            };
+            table::add(reserves, metadata_addr, reserve);
+        };
+
+        let reserve = table::borrow(reserves, metadata_addr);
+        let deposit = primary_fungible_store::withdraw(owner, metadata, amount);
+        dispatchable_fungible_asset::deposit(reserve.store, deposit);
+        let new_vault_balance = fungible_asset::balance(reserve.store);
+
+        let vault = borrow_global_mut<Vault>(owner_addr);
+        let deposit_event = DepositEvent {
+            depositor: owner_addr,
+            amount,
+            new_vault_balance,
+            timestamp: timestamp::now_microseconds(),
+        };
+        event::emit_event(&mut vault.deposit_events, deposit_event);
+    }
+
     public entry fun add_relayer(owner: &signer, relayer: address) acquires Vault {
         let owner_addr = signer::address_of(owner);
         assert!(exists<Vault>(owner_addr), error::not_found(E_VAULT_NOT_INITIALIZED));
@@ -129,6 +224,8 @@ module cyrus_protocol::settlement {
         };
     }
 
+    // `source_tx_hash` is the replay-protection key: the Solana transaction hash for the
+    // first settlement event in a transaction and "<hash>:<event index>" for later ones
     public entry fun settle(
         relayer: &signer,
         vault_owner: address,
@@ -142,27 +239,292 @@ module cyrus_protocol::settlement {
         assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
         
         let vault = borrow_global_mut<Vault>(vault_owner);
+        assert_authorized(vault, relayer_addr);
+
+        let available = coin::value(&vault.usdc_balance);
+        assert_settleable(vault, &source_tx_hash, amount, available);
+
+        pay_settlement(vault, source_tx_hash, receiver, amount, nonce, source_timestamp);
+    }
+
+    // Settle from the vault's reserve of CoinType
+    public entry fun settle_coin<CoinType>(
+        relayer: &signer,
+        vault_owner: address,
+        source_tx_hash: String,
+        receiver: address,
+        amount: u64,
+        nonce: u64,
+        source_timestamp: u64
+    ) acquires Vault, CoinReserve {
+        let relayer_addr = signer::address_of(relayer);
+        assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
+        assert!(exists<CoinReserve<CoinType>>(vault_owner), error::not_found(E_ASSET_NOT_REGISTERED));
+
+        let vault = borrow_global_mut<Vault>(vault_owner);
+        assert_authorized(vault, relayer_addr);
+
+        let reserve = borrow_global_mut<CoinReserve<CoinType>>(vault_owner);
+        assert_settleable(vault, &source_tx_hash, amount, coin::value(&reserve.coins));
+
+        pay_coin(reserve, receiver, amount);
+        record_settlement(vault, source_tx_hash, receiver, amount, nonce, source_timestamp);
+    }
+
+    // Settle from the vault's reserve of the Fungible Asset described by `metadata`
+    public entry fun settle_fa(
+        relayer: &signer,
+        vault_owner: address,
+        metadata: Object<Metadata>,
+        source_tx_hash: String,
+        receiver: address,
+        amount: u64,
+        nonce: u64,
+        source_timestamp: u64
+    ) acquires Vault, FungibleReserves {
+        let relayer_addr = signer::address_of(relayer);
+        assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
+
+        let vault = borrow_global_mut<Vault>(vault_owner);
+        assert_authorized(vault, relayer_addr);
+
+        let reserve = borrow_fungible_reserve_mut(vault_owner, metadata);
+        assert_settleable(vault, &source_tx_hash, amount, fungible_asset::balance(reserve.store));
+
+        pay_fungible(reserve, receiver, amount);
+        record_settlement(vault, source_tx_hash, receiver, amount, nonce, source_timestamp);
+    }
+
+    // Settle several instructions in one transaction. Items that cannot be settled
+    // (zero amount, insufficient balance, already settled) are skipped with a
+    // SettlementSkipped event so the rest of the batch still goes through.
+    public entry fun settle_batch(
+        relayer: &signer,
+        vault_owner: address,
+        source_tx_hashes: vector<String>,
+        receivers: vector<address>,
+        amounts: vector<u64>,
+        nonces: vector<u64>,
+        source_timestamps: vector<u64>
+    ) acquires Vault {
+        let relayer_addr = signer::address_of(relayer);
+        assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
+        let count = batch_length(&source_tx_hashes, &receivers, &amounts, &nonces, &source_timestamps);
+
+        let vault = borrow_global_mut<Vault>(vault_owner);
+        assert_authorized(vault, relayer_addr);
+
+        let i = 0;
+        while (i < count) {
+            let source_tx_hash = *vector::borrow(&source_tx_hashes, i);
+            let amount = *vector::borrow(&amounts, i);
+
+            let available = coin::value(&vault.usdc_balance);
+            let reason = settlement_check(vault, &source_tx_hash, amount, available);
+
+            if (reason == 0) {
+                pay_settlement(
+                    vault,
+                    source_tx_hash,
+                    *vector::borrow(&receivers, i),
+                    amount,
+                    *vector::borrow(&nonces, i),
+                    *vector::borrow(&source_timestamps, i),
+                );
+            } else {
+                event::emit(SettlementSkipped { vault_owner, source_tx_hash, reason });
+            };
+
+            i = i + 1;
+        };
+    }
+
+    // settle_batch for the vault's reserve of CoinType
+    public entry fun settle_batch_coin<CoinType>(
+        relayer: &signer,
+        vault_owner: address,
+        source_tx_hashes: vector<String>,
+        receivers: vector<address>,
+        amounts: vector<u64>,
+        nonces: vector<u64>,
+        source_timestamps: vector<u64>
+    ) acquires Vault, CoinReserve {
+        let relayer_addr = signer::address_of(relayer);
+        assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
+        assert!(exists<CoinReserve<CoinType>>(vault_owner), error::not_found(E_ASSET_NOT_REGISTERED));
+        let count = batch_length(&source_tx_hashes, &receivers, &amounts, &nonces, &source_timestamps);
+
+        let vault = borrow_global_mut<Vault>(vault_owner);
+        assert_authorized(vault, relayer_addr);
+        let reserve = borrow_global_mut<CoinReserve<CoinType>>(vault_owner);
+
+        let i = 0;
+        while (i < count) {
+            let source_tx_hash = *vector::borrow(&source_tx_hashes, i);
+            let amount = *vector::borrow(&amounts, i);
+            let reason = settlement_check(vault, &source_tx_hash, amount, coin::value(&reserve.coins));
+
+            if (reason == 0) {
+                let receiver = *vector::borrow(&receivers, i);
+                pay_coin(reserve, receiver, amount);
+                record_settlement(
+                    vault,
+                    source_tx_hash,
+                    receiver,
+                    amount,
+                    *vector::borrow(&nonces, i),
+                    *vector::borrow(&source_timestamps, i),
+                );
+            } else {
+                event::emit(SettlementSkipped { vault_owner, source_tx_hash, reason });
+            };
+
+            i = i + 1;
+        };
+    }
+
+    // settle_batch for the vault's reserve of the Fungible Asset described by `metadata`
+    public entry fun settle_batch_fa(
+        relayer: &signer,
+        vault_owner: address,
+        metadata: Object<Metadata>,
+        source_tx_hashes: vector<String>,
+        receivers: vector<address>,
+        amounts: vector<u64>,
+        nonces: vector<u64>,
+        source_timestamps: vector<u64>
+    ) acquires Vault, FungibleReserves {
+        let relayer_addr = signer::address_of(relayer);
+        assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
+        let count = batch_length(&source_tx_hashes, &receivers, &amounts, &nonces, &source_timestamps);
+
+        let vault = borrow_global_mut<Vault>(vault_owner);
+        assert_authorized(vault, relayer_addr);
+        let reserve = borrow_fungible_reserve_mut(vault_owner, metadata);
+
+        let i = 0;
+        while (i < count) {
+            let source_tx_hash = *vector::borrow(&source_tx_hashes, i);
+            let amount = *vector::borrow(&amounts, i);
+            let available = fungible_asset::balance(reserve.store);
+            let reason = settlement_check(vault, &source_tx_hash, amount, available);
+
+            if (reason == 0) {
+                let receiver = *vector::borrow(&receivers, i);
+                pay_fungible(reserve, receiver, amount);
+                record_settlement(
+                    vault,
+                    source_tx_hash,
+                    receiver,
+                    amount,
+                    *vector::borrow(&nonces, i),
+                    *vector::borrow(&source_timestamps, i),
+                );
+            } else {
+                event::emit(SettlementSkipped { vault_owner, source_tx_hash, reason });
+            };
+
+            i = i + 1;
+        };
+    }
 
+    fun assert_authorized(vault: &Vault, relayer_addr: address) {
         let is_authorized = vault.owner == relayer_addr || 
                            vector::contains(&vault.authorized_relayers, &relayer_addr);
         assert!(is_authorized, error::permission_denied(E_UNAUTHORIZED_RELAYER));
+    }
 
-        assert!(amount > 0, error::invalid_argument(E_INVALID_AMOUNT));
-        assert!(coin::value(&vault.usdc_balance) >= amount, 
-                error::invalid_state(E_INSUFFICIENT_BALANCE));
+    // Number of items in a batch, aborting if the argument vectors differ in length
+    fun batch_length(
+        source_tx_hashes: &vector<String>,
+        receivers: &vector<address>,
+        amounts: &vector<u64>,
+        nonces: &vector<u64>,
+        source_timestamps: &vector<u64>
+    ): u64 {
+        let count = vector::length(source_tx_hashes);
+        assert!(
+            vector::length(receivers) == count &&
+            vector::length(amounts) == count &&
+            vector::length(nonces) == count &&
+            vector::length(source_timestamps) == count,
+            error::invalid_argument(E_INVALID_INSTRUCTION)
+        );
+        count
+    }
 
-        assert!(!table::contains(&vault.processed_instructions, source_tx_hash), 
-                error::already_exists(E_ALREADY_SETTLED));
+    // 0 if the settlement can be paid from `available`, otherwise the E_* reason it cannot
+    fun settlement_check(vault: &Vault, source_tx_hash: &String, amount: u64, available: u64): u64 {
+        if (amount == 0) {
+            E_INVALID_AMOUNT
+        } else if (available < amount) {
+            E_INSUFFICIENT_BALANCE
+        } else if (table::contains(&vault.processed_instructions, *source_tx_hash)) {
+            E_ALREADY_SETTLED
+        } else {
+            0
+        }
+    }
 
-        table::add(&mut vault.processed_instructions, source_tx_hash, true);
+    fun assert_settleable(vault: &Vault, source_tx_hash: &String, amount: u64, available: u64) {
+        let reason = settlement_check(vault, source_tx_hash, amount, available);
+        assert!(reason != E_INVALID_AMOUNT, error::invalid_argument(E_INVALID_AMOUNT));
+        assert!(reason != E_INSUFFICIENT_BALANCE, error::invalid_state(E_INSUFFICIENT_BALANCE));
+        assert!(reason != E_ALREADY_SETTLED, error::already_exists(E_ALREADY_SETTLED));
+    }
 
-        
+    fun borrow_fungible_reserve_mut(
+        vault_owner: address,
+        metadata: Object<Metadata>
+    ): &mut FungibleReserve acquires FungibleReserves {
+        let metadata_addr = object::object_address(&metadata);
+        assert!(exists<FungibleReserves>(vault_owner), error::not_found(E_ASSET_NOT_REGISTERED));
+        let reserves = &mut borrow_global_mut<FungibleReserves>(vault_owner).reserves;
+        assert!(table::contains(reserves, metadata_addr), error::not_found(E_ASSET_NOT_REGISTERED));
+        table::borrow_mut(reserves, metadata_addr)
+    }
+
+    fun pay_coin<CoinType>(reserve: &mut CoinReserve<CoinType>, receiver: address, amount: u64) {
+        coin::deposit(receiver, coin::extract(&mut reserve.coins, amount));
+        reserve.total_settled = reserve.total_settled + amount;
+    }
+
+    fun pay_fungible(reserve: &mut FungibleReserve, receiver: address, amount: u64) {
+        let store_signer = object::generate_signer_for_extending(&reserve.extend_ref);
+        let payment = dispatchable_fungible_asset::withdraw(&store_signer, reserve.store, amount);
+        primary_fungible_store::deposit(receiver, payment);
+        reserve.total_settled = reserve.total_settled + amount;
+    }
+
+    // Pay out a settlement from the built-in USDC balance and record it as processed
+    fun pay_settlement(
+        vault: &mut Vault,
+        source_tx_hash: String,
+        receiver: address,
+        amount: u64,
+        nonce: u64,
+        source_timestamp: u64
+    ) {
         let settlement_coin = coin::extract(&mut vault.usdc_balance, amount);
         coin::deposit(receiver, settlement_coin);
 
         // Update statistics
         vault.total_settled = vault.total_settled + amount;
 
+        record_settlement(vault, source_tx_hash, receiver, amount, nonce, source_timestamp);
+    }
+
+    // Mark a paid settlement as processed and emit its event
+    fun record_settlement(
+        vault: &mut Vault,
+        source_tx_hash: String,
+        receiver: address,
+        amount: u64,
+        nonce: u64,
+        source_timestamp: u64
+    ) {
+        table::add(&mut vault.processed_instructions, source_tx_hash, true);
+
         // Emit settlement event
         let settlement_event = SettlementEvent {
             source_tx_hash,
@@ -204,7 +566,39 @@ module cyrus_protocol::settlement {
         };
     }
 
+    public entry fun emergency_withdraw_coin<CoinType>(owner: &signer, amount: u64) acquires Vault, CoinReserve {
+        let owner_addr = signer::address_of(owner);
+        assert!(exists<Vault>(owner_addr), error::not_found(E_VAULT_NOT_INITIALIZED));
+        assert!(borrow_global<Vault>(owner_addr).owner == owner_addr, error::permission_denied(E_NOT_OWNER));
+        assert!(exists<CoinReserve<CoinType>>(owner_addr), error::not_found(E_ASSET_NOT_REGISTERED));
+        assert!(amount > 0, error::invalid_argument(E_INVALID_AMOUNT));
+
+        let reserve = borrow_global_mut<CoinReserve<CoinType>>(owner_addr);
+        assert!(coin::value(&reserve.coins) >= amount, 
+                error::invalid_state(E_INSUFFICIENT_BALANCE));
+        coin::deposit(owner_addr, coin::extract(&mut reserve.coins, amount));
+    }
+
+    public entry fun emergency_withdraw_fa(
+        owner: &signer,
+        metadata: Object<Metadata>,
+        amount: u64
+    ) acquires Vault, FungibleReserves {
+        let owner_addr = signer::address_of(owner);
+        assert!(exists<Vault>(owner_addr), error::not_found(E_VAULT_NOT_INITIALIZED));
+        assert!(borrow_global<Vault>(owner_addr).owner == owner_addr, error::permission_denied(E_NOT_OWNER));
+        assert!(amount > 0, error::invalid_argument(E_INVALID_AMOUNT));
+
+        let reserve = borrow_fungible_reserve_mut(owner_addr, metadata);
+        assert!(fungible_asset::balance(reserve.store) >= amount, 
+                error::invalid_state(E_INSUFFICIENT_BALANCE));
+        let store_signer = object::generate_signer_for_extending(&reserve.extend_ref);
+        let withdrawal = dispatchable_fungible_asset::withdraw(&store_signer, reserve.store, amount);
+        primary_fungible_store::deposit(owner_addr, withdrawal);
+    }
+
     // View functions for monitoring and queries
+    #[view]
     public fun is_settled(vault_owner: address, source_tx_hash: String): bool acquires Vault {
         if (!exists<Vault>(vault_owner)) {
             return false
@@ -214,18 +608,65 @@ module cyrus_protocol::settlement {
         table::contains(&vault.processed_instructions, source_tx_hash)
     }
 
+    #[view]
     public fun get_vault_balance(vault_owner: address): u64 acquires Vault {
         assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
         let vault = borrow_global<Vault>(vault_owner);
         coin::value(&vault.usdc_balance)
     }
 
+    #[view]
     public fun get_total_settled(vault_owner: address): u64 acquires Vault {
         assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
         let vault = borrow_global<Vault>(vault_owner);
         vault.total_settled
     }
 
+    // Balance of the vault's CoinType reserve; 0 until the first deposit
+    #[view]
+    public fun get_coin_balance<CoinType>(vault_owner: address): u64 acquires CoinReserve {
+        assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
+        if (!exists<CoinReserve<CoinType>>(vault_owner)) {
+            return 0
+        };
+        coin::value(&borrow_global<CoinReserve<CoinType>>(vault_owner).coins)
+    }
+
+    #[view]
+    public fun get_coin_total_settled<CoinType>(vault_owner: address): u64 acquires CoinReserve {
+        assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
+        if (!exists<CoinReserve<CoinType>>(vault_owner)) {
+            return 0
+        };
+        borrow_global<CoinReserve<CoinType>>(vault_owner).total_settled
+    }
+
+    // Balance of the vault's reserve of the Fungible Asset at `metadata`; 0 until the first deposit
+    #[view]
+    public fun get_fa_balance(vault_owner: address, metadata: address): u64 acquires FungibleReserves {
+        assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
+        if (!fungible_reserve_exists(vault_owner, metadata)) {
+            return 0
+        };
+        let reserves = &borrow_global<FungibleReserves>(vault_owner).reserves;
+        fungible_asset::balance(table::borrow(reserves, metadata).store)
+    }
+
+    #[view]
+    public fun get_fa_total_settled(vault_owner: address, metadata: address): u64 acquires FungibleReserves {
+        assert!(exists<Vault>(vault_owner), error::not_found(E_VAULT_NOT_INITIALIZED));
+        if (!fungible_reserve_exists(vault_owner, metadata)) {
+            return 0
+        };
+        let reserves = &borrow_global<FungibleReserves>(vault_owner).reserves;
+        table::borrow(reserves, metadata).total_settled
+    }
+
+    fun fungible_reserve_exists(vault_owner: address, metadata: address): bool acquires FungibleReserves {
+        exists<FungibleReserves>(vault_owner) &&
+            table::contains(&borrow_global<FungibleReserves>(vault_owner).reserves, metadata)
+    }
+
     public fun is_authorized_relayer(vault_owner: address, relayer: address): bool acquires Vault {
         if (!exists<Vault>(vault_owner)) {
             return false
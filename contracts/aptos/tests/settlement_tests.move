@@ -1,11 +1,34 @@
 #[test_only]
 module cyrus_protocol::settlement_integration_tests {
+    use std::option;
     use std::string;
     use std::signer;
     use std::vector;
     use aptos_framework::account;
+    use aptos_framework::fungible_asset::{Self, Metadata, MintRef};
+    use aptos_framework::object::{Self, Object};
+    use aptos_framework::primary_fungible_store;
+    use aptos_framework::timestamp;
     use cyrus_protocol::settlement;
 
+    struct UnregisteredCoin {}
+
+    // A primary-store-enabled Fungible Asset standing in for native USDC
+    fun create_test_asset(creator: &signer): (Object<Metadata>, MintRef) {
+        let constructor_ref = object::create_named_object(creator, b"TEST_USDC");
+        primary_fungible_store::create_primary_store_enabled_fungible_asset(
+            &constructor_ref,
+            option::none(),
+            string::utf8(b"Test USDC"),
+            string::utf8(b"USDC"),
+            6,
+            string::utf8(b""),
+            string::utf8(b""),
+        );
+        let mint_ref = fungible_asset::generate_mint_ref(&constructor_ref);
+        (object::object_from_constructor_ref<Metadata>(&constructor_ref), mint_ref)
+    }
+
     #[test(admin = @cyrus_protocol)]
     public fun test_complete_vault_lifecycle(admin: &signer) {
         // Setup
@@ -131,6 +154,49 @@ module cyrus_protocol::settlement_integration_tests {
         settlement::add_relayer(other, other_addr);
     }
 
+    #[test(admin = @cyrus_protocol)]
+    public fun test_settle_batch_skips_unpayable_items(admin: &signer) {
+        settlement::setup_test_account(admin);
+        settlement::initialize_vault(admin);
+
+        let admin_addr = signer::address_of(admin);
+        let zero_amount = string::utf8(b"batch_tx_1");
+        let over_balance = string::utf8(b"batch_tx_2");
+
+        // Neither item can be paid from an empty vault, but the batch itself succeeds
+        settlement::settle_batch(
+            admin,
+            admin_addr,
+            vector[zero_amount, over_balance],
+            vector[admin_addr, admin_addr],
+            vector[0, 1_000_000],
+            vector[1, 2],
+            vector[0, 0],
+        );
+
+        assert!(!settlement::is_settled(admin_addr, zero_amount), 1);
+        assert!(!settlement::is_settled(admin_addr, over_balance), 2);
+        assert!(settlement::get_total_settled(admin_addr) == 0, 3);
+    }
+
+    #[test(admin = @cyrus_protocol)]
+    #[expected_failure(abort_code = 3, location = cyrus_protocol::settlement)]
+    public fun test_settle_batch_length_mismatch(admin: &signer) {
+        settlement::setup_test_account(admin);
+        settlement::initialize_vault(admin);
+
+        let admin_addr = signer::address_of(admin);
+        settlement::settle_batch(
+            admin,
+            admin_addr,
+            vector[string::utf8(b"batch_tx_1")],
+            vector[],
+            vector[1],
+            vector[1],
+            vector[0],
+        );
+    }
+
     #[test(admin = @cyrus_protocol)]
     #[expected_failure(abort_code = 5, location = cyrus_protocol::settlement)]
     public fun test_operations_without_vault(admin: &signer) {
@@ -163,4 +229,81 @@ module cyrus_protocol::settlement_integration_tests {
         assert!(created_at > 0, 5);
         assert!(vector::length(&relayers) == 0, 6);
     }
-}
\ No newline at end of file
+
+    #[test(aptos_framework = @aptos_framework, admin = @cyrus_protocol)]
+    public fun test_fungible_asset_settlement(aptos_framework: &signer, admin: &signer) {
+        timestamp::set_time_has_started_for_testing(aptos_framework);
+        settlement::setup_test_account(admin);
+        settlement::initialize_vault(admin);
+
+        let admin_addr = signer::address_of(admin);
+        let receiver = @0x789;
+        let (metadata, mint_ref) = create_test_asset(admin);
+        let metadata_addr = object::object_address(&metadata);
+
+        // Nothing is held for the asset until the first deposit
+        assert!(settlement::get_fa_balance(admin_addr, metadata_addr) == 0, 1);
+
+        primary_fungible_store::mint(&mint_ref, admin_addr, 5_000_000);
+        settlement::deposit_fa(admin, metadata, 3_000_000);
+        assert!(settlement::get_fa_balance(admin_addr, metadata_addr) == 3_000_000, 2);
+        assert!(primary_fungible_store::balance(admin_addr, metadata) == 2_000_000, 3);
+
+        let tx_hash = string::utf8(b"fa_tx_1");
+        settlement::settle_fa(admin, admin_addr, metadata, tx_hash, receiver, 1_000_000, 1, 0);
+        assert!(settlement::is_settled(admin_addr, tx_hash), 4);
+        assert!(primary_fungible_store::balance(receiver, metadata) == 1_000_000, 5);
+        assert!(settlement::get_fa_balance(admin_addr, metadata_addr) == 2_000_000, 6);
+        assert!(settlement::get_fa_total_settled(admin_addr, metadata_addr) == 1_000_000, 7);
+
+        // The legacy USDC totals are unaffected
+        assert!(settlement::get_total_settled(admin_addr) == 0, 8);
+
+        // A batch skips the replayed hash and pays the new one
+        let new_hash = string::utf8(b"fa_tx_2");
+        settlement::settle_batch_fa(
+            admin,
+            admin_addr,
+            metadata,
+            vector[tx_hash, new_hash],
+            vector[receiver, receiver],
+            vector[1_000_000, 500_000],
+            vector[1, 2],
+            vector[0, 0],
+        );
+        assert!(settlement::is_settled(admin_addr, new_hash), 9);
+        assert!(primary_fungible_store::balance(receiver, metadata) == 1_500_000, 10);
+        assert!(settlement::get_fa_total_settled(admin_addr, metadata_addr) == 1_500_000, 11);
+
+        // A second event in the same Solana transaction has its own key
+        let second_event = string::utf8(b"fa_tx_1:1");
+        assert!(!settlement::is_settled(admin_addr, second_event), 12);
+        settlement::settle_fa(admin, admin_addr, metadata, second_event, receiver, 250_000, 3, 0);
+        assert!(settlement::is_settled(admin_addr, second_event), 13);
+        assert!(primary_fungible_store::balance(receiver, metadata) == 1_750_000, 14);
+    }
+
+    #[test(aptos_framework = @aptos_framework, admin = @cyrus_protocol)]
+    #[expected_failure(abort_code = 0x60009, location = cyrus_protocol::settlement)]
+    public fun test_settle_fa_without_reserve(aptos_framework: &signer, admin: &signer) {
+        timestamp::set_time_has_started_for_testing(aptos_framework);
+        settlement::setup_test_account(admin);
+        settlement::initialize_vault(admin);
+
+        let admin_addr = signer::address_of(admin);
+        let (metadata, _) = create_test_asset(admin);
+        settlement::settle_fa(admin, admin_addr, metadata, string::utf8(b"fa_tx"), @0x789, 1, 1, 0);
+    }
+
+    #[test(aptos_framework = @aptos_framework, admin = @cyrus_protocol)]
+    #[expected_failure(abort_code = 0x60009, location = cyrus_protocol::settlement)]
+    public fun test_settle_coin_without_reserve(aptos_framework: &signer, admin: &signer) {
+        timestamp::set_time_has_started_for_testing(aptos_framework);
+        settlement::setup_test_account(admin);
+        settlement::initialize_vault(admin);
+
+        let admin_addr = signer::address_of(admin);
+        assert!(settlement::get_coin_balance<UnregisteredCoin>(admin_addr) == 0, 1);
+        settlement::settle_coin<UnregisteredCoin>(admin, admin_addr, string::utf8(b"coin_tx"), @0x789, 1, 1, 0);
+    }
+}
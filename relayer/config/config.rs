@@ -1,10 +1,16 @@
-use crate::types::{RelayerConfig, SettlementError};
+use crate::{
+    keys,
+    types::{AptosAsset, RelayerConfig, SettlementError},
+};
 use clap::{Arg, Command};
-use config::{Config, ConfigError, Environment, File};
-use serde::Deserialize;
+use config::{Config, Environment, File};
 use std::{env, path::Path};
 use tracing::{info, warn};
 
+/// Seconds a lease must allow beyond the batch wait and the settlement
+/// timeout for the database round-trips that start and finish it
+const LEASE_BOOKKEEPING_SECS: u64 = 10;
+
 /// Configuration builder for the relayer
 pub struct ConfigBuilder {
     config: Config,
@@ -21,6 +27,12 @@ pub struct CliArgs {
     pub metrics_port: Option<u16>,
 }
 
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ConfigBuilder {
     /// Create new config builder
     pub fn new() -> Self {
@@ -55,7 +67,7 @@ impl ConfigBuilder {
         builder.apply_cli_overrides(&cli_args)?;
         
         // Build final config
-        let config: RelayerConfig = builder.config.try_deserialize()
+        let config: RelayerConfig = builder.config.clone().try_deserialize()
             .map_err(|e| SettlementError::ConfigError(format!("Configuration parsing error: {}", e)))?;
         
         // Validate configuration
@@ -132,10 +144,17 @@ impl ConfigBuilder {
         let defaults = r#"
 [solana]
 rpc_url = "https://api.devnet.solana.com"
+rpc_timeout_ms = 10000
+rpc_requests_per_second = 10
+max_concurrent_fetches = 8
 program_id = ""
 commitment = "confirmed"
 poll_interval_ms = 1000
 max_retries = 3
+subscribe_logs = true
+
+[solana.finality]
+mode = "finalized"
 
 [aptos]
 rpc_url = "https://fullnode.testnet.aptoslabs.com/v1"
@@ -144,14 +163,44 @@ vault_owner = ""
 private_key = ""
 max_gas_amount = 200000
 gas_unit_price = 100
+gas_margin_percent = 20
+min_gas_unit_price = 100
+max_gas_unit_price = 10000
 transaction_timeout_secs = 30
+transaction_expiry_secs = 60
+max_resubmissions = 2
+gas_price_bump_percent = 20
+
+[aptos.assets.USDC]
+standard = "vault_coin"
 
 [processing]
 max_concurrent_settlements = 10
 batch_size = 5
-retry_attempts = 3
-retry_delay_seconds = 5
+batch_max_wait_ms = 500
 settlement_timeout_seconds = 300
+queue_capacity = 1000
+lease_timeout_seconds = 600
+queue_pause_seconds = 300
+
+[retry]
+sweep_interval_ms = 5000
+
+[retry.default]
+max_attempts = 3
+base_delay_ms = 5000
+max_delay_ms = 300000
+jitter = 0.2
+
+[retry.source_poll]
+max_attempts = 1
+base_delay_ms = 5000
+max_delay_ms = 60000
+jitter = 0.2
+
+[retry.circuit_breaker]
+failure_threshold = 5
+reset_timeout_ms = 30000
 
 [monitoring]
 metrics_port = 9090
@@ -231,22 +280,22 @@ connection_timeout_secs = 30
 
         // Override with CLI arguments if provided
         if let Some(ref url) = cli_args.solana_rpc_url {
-            builder = builder.set_override("solana.rpc_url", url)
+            builder = builder.set_override("solana.rpc_url", url.as_str())
                 .map_err(|e| SettlementError::ConfigError(format!("CLI override error: {}", e)))?;
         }
 
         if let Some(ref url) = cli_args.aptos_rpc_url {
-            builder = builder.set_override("aptos.rpc_url", url)
+            builder = builder.set_override("aptos.rpc_url", url.as_str())
                 .map_err(|e| SettlementError::ConfigError(format!("CLI override error: {}", e)))?;
         }
 
         if let Some(ref level) = cli_args.log_level {
-            builder = builder.set_override("monitoring.log_level", level)
+            builder = builder.set_override("monitoring.log_level", level.as_str())
                 .map_err(|e| SettlementError::ConfigError(format!("CLI override error: {}", e)))?;
         }
 
         if let Some(ref url) = cli_args.database_url {
-            builder = builder.set_override("database.url", url)
+            builder = builder.set_override("database.url", url.as_str())
                 .map_err(|e| SettlementError::ConfigError(format!("CLI override error: {}", e)))?;
         }
 
@@ -276,6 +325,22 @@ connection_timeout_secs = 30
             ));
         }
 
+        if let Some(quorum) = config.solana.rpc_quorum {
+            let endpoints = config.solana.rpc_urls().len();
+            if quorum == 0 || quorum > endpoints {
+                return Err(SettlementError::ConfigError(format!(
+                    "Solana RPC quorum must be between 1 and the {} configured endpoints",
+                    endpoints
+                )));
+            }
+        }
+
+        if config.solana.max_concurrent_fetches == 0 {
+            return Err(SettlementError::ConfigError(
+                "Solana max concurrent fetches must be at least 1".to_string()
+            ));
+        }
+
         // Validate Aptos configuration
         if config.aptos.contract_address.is_empty() {
             return Err(SettlementError::ConfigError(
@@ -289,9 +354,9 @@ connection_timeout_secs = 30
             ));
         }
 
-        if config.aptos.private_key.is_empty() {
+        if config.aptos.private_key.is_empty() && config.aptos.key_file.is_none() {
             return Err(SettlementError::ConfigError(
-                "Aptos private key is required".to_string()
+                "Aptos private key or key file is required".to_string()
             ));
         }
 
@@ -301,6 +366,28 @@ connection_timeout_secs = 30
             ));
         }
 
+        if config.aptos.min_gas_unit_price == 0
+            || config.aptos.min_gas_unit_price > config.aptos.max_gas_unit_price
+        {
+            return Err(SettlementError::ConfigError(
+                "Aptos gas price bounds must satisfy 0 < min_gas_unit_price <= max_gas_unit_price"
+                    .to_string()
+            ));
+        }
+
+        if config.aptos.transaction_expiry_secs == 0 {
+            return Err(SettlementError::ConfigError(
+                "Aptos transaction expiry must be greater than 0 seconds".to_string()
+            ));
+        }
+
+        // A replacement only displaces a pending transaction if it pays more
+        if config.aptos.max_resubmissions > 0 && config.aptos.gas_price_bump_percent == 0 {
+            return Err(SettlementError::ConfigError(
+                "Gas price bump must be greater than 0 when resubmissions are enabled".to_string()
+            ));
+        }
+
         // Validate processing configuration
         if config.processing.max_concurrent_settlements == 0 {
             return Err(SettlementError::ConfigError(
@@ -308,12 +395,49 @@ connection_timeout_secs = 30
             ));
         }
 
-        if config.processing.retry_attempts == 0 {
+        if config.processing.batch_size == 0 {
             return Err(SettlementError::ConfigError(
-                "Retry attempts must be greater than 0".to_string()
+                "Batch size must be greater than 0".to_string()
             ));
         }
 
+        if config.processing.queue_capacity == 0 {
+            return Err(SettlementError::ConfigError(
+                "Queue capacity must be greater than 0".to_string()
+            ));
+        }
+
+        if config.processing.queue_pause_seconds == 0 {
+            return Err(SettlementError::ConfigError(
+                "Queue pause must be greater than 0 seconds".to_string()
+            ));
+        }
+
+        // The first lease is taken before the batch fills, so it must last through that wait too
+        let lease_needed_ms = config.processing.batch_max_wait_ms.saturating_add(
+            config.processing.settlement_timeout_seconds
+                .saturating_add(LEASE_BOOKKEEPING_SECS)
+                .saturating_mul(1000),
+        );
+        if config.processing.lease_timeout_seconds.saturating_mul(1000) <= lease_needed_ms {
+            return Err(SettlementError::ConfigError(format!(
+                "Lease timeout must be longer than the batch wait, the settlement timeout and {}s for bookkeeping",
+                LEASE_BOOKKEEPING_SECS
+            )));
+        }
+
+        let confirmation_window = config.aptos.transaction_timeout_secs
+            * (u64::from(config.aptos.max_resubmissions) + 1);
+        if config.processing.settlement_timeout_seconds <= confirmation_window {
+            return Err(SettlementError::ConfigError(
+                "Settlement timeout must cover the Aptos confirmation timeout for every resubmission"
+                    .to_string()
+            ));
+        }
+
+        // Validate retry policies and the circuit breaker
+        config.retry.validate()?;
+
         // Validate database configuration
         if config.database.url.is_empty() {
             return Err(SettlementError::ConfigError(
@@ -321,13 +445,40 @@ connection_timeout_secs = 30
             ));
         }
 
-        // Validate hex keys
-        if !config.aptos.private_key.starts_with("0x") {
+        // Validate keys; a key file is only read when the relayer starts
+        if config.aptos.key_file.is_none() {
+            keys::parse_private_key(&config.aptos.private_key)?;
+        }
+
+        if let Some(address) = &config.aptos.account_address {
+            keys::parse_address(address)?;
+        }
+
+        // Validate the asset registry
+        if config.aptos.assets.is_empty() {
             return Err(SettlementError::ConfigError(
-                "Aptos private key must be in hex format (0x...)".to_string()
+                "At least one Aptos asset must be registered".to_string()
             ));
         }
 
+        for (symbol, asset) in &config.aptos.assets {
+            match asset {
+                AptosAsset::VaultCoin => {}
+                AptosAsset::Coin { coin_type } => {
+                    if coin_type.split("::").count() != 3 {
+                        return Err(SettlementError::ConfigError(format!(
+                            "Coin type for {} must look like 0x...::module::Name", symbol
+                        )));
+                    }
+                }
+                AptosAsset::FungibleAsset { metadata_address } => {
+                    keys::parse_address(metadata_address).map_err(|_| SettlementError::ConfigError(format!(
+                        "Metadata address for {} must be in hex format (0x...)", symbol
+                    )))?;
+                }
+            }
+        }
+
         // Validate addresses
         if !config.aptos.contract_address.starts_with("0x") {
             return Err(SettlementError::ConfigError(
@@ -359,14 +510,34 @@ pub fn create_sample_config() -> String {
 [solana]
 # Solana RPC endpoint
 rpc_url = "https://api.devnet.solana.com"
+# More endpoints to fail over to when rpc_url stalls, errors or falls behind
+# fallback_rpc_urls = ["https://solana-devnet.example.com"]
+# Require this many endpoints to agree on a transaction before settling it
+# rpc_quorum = 2
+# How long to wait on one endpoint before failing over
+rpc_timeout_ms = 10000
+# Requests sent to each endpoint per second (0 = unthrottled)
+rpc_requests_per_second = 10
+# Transactions fetched at once while catching up
+max_concurrent_fetches = 8
 # Cyrus settlement program ID
 program_id = "YOUR_PROGRAM_ID_HERE"
 # Transaction commitment level
 commitment = "confirmed"
-# Polling interval in milliseconds
+# Polling interval in milliseconds; while the log subscription is live,
+# polling only reconciles gaps and runs less often
 poll_interval_ms = 1000
 # Maximum RPC retries
 max_retries = 3
+# Stream program logs over the PubSub WebSocket
+subscribe_logs = true
+# PubSub endpoint, derived from rpc_url when unset
+# ws_url = "wss://api.devnet.solana.com"
+
+[solana.finality]
+# Settle events once their transaction is finalized, or use
+# mode = "confirmed" with slots = N to settle N slots past confirmation
+mode = "finalized"
 
 [aptos]
 # Aptos RPC endpoint
@@ -377,24 +548,88 @@ contract_address = "0xYOUR_CONTRACT_ADDRESS_HERE"
 vault_owner = "0xYOUR_VAULT_OWNER_ADDRESS_HERE"
 # Private key for transaction signing (keep secure!)
 private_key = "0xYOUR_PRIVATE_KEY_HERE"
-# Maximum gas for transactions
+# Or load the key from an Aptos CLI config.yaml profile or an encrypted keyfile
+# written by `cyrus-relayer key encrypt` (passphrase in CYRUS_KEY_PASSPHRASE)
+# key_file = "~/.aptos/config.yaml"
+# key_profile = "default"
+# Account address, only needed if the account's key has been rotated
+# account_address = "0xYOUR_ACCOUNT_ADDRESS_HERE"
+# Upper bound on gas for a settlement; the actual limit comes from simulating it
 max_gas_amount = 200000
-# Gas price in units
+# Gas price used when the node cannot provide an estimate
 gas_unit_price = 100
-# Transaction timeout in seconds
+# Headroom added to simulated gas usage, in percent
+gas_margin_percent = 20
+# Bounds applied to the node's gas price estimate
+min_gas_unit_price = 100
+max_gas_unit_price = 10000
+# Seconds to wait for a confirmation before a transaction is treated as stuck
 transaction_timeout_secs = 30
+# Seconds a submitted transaction stays valid before the chain drops it
+transaction_expiry_secs = 60
+# Replacements (same sequence number, higher gas price) sent for a stuck or expired transaction
+max_resubmissions = 2
+# Gas price increase for each replacement, in percent
+gas_price_bump_percent = 20
+
+# How the vault pays out each token symbol:
+#   standard = "vault_coin"                                  the vault's built-in Coin<USDC> balance
+#   standard = "coin", coin_type = "0x...::module::Coin"     a coin type, via settle_coin
+#   standard = "fungible_asset", metadata_address = "0x..."  a Fungible Asset, via settle_fa
+[aptos.assets.USDC]
+standard = "fungible_asset"
+metadata_address = "0xYOUR_USDC_METADATA_ADDRESS_HERE"
 
 [processing]
 # Maximum concurrent settlement processing
 max_concurrent_settlements = 10
-# Batch size for processing
+# Most instructions settled together in one Aptos transaction
 batch_size = 5
-# Number of retry attempts for failed settlements
-retry_attempts = 3
-# Delay between retries in seconds
-retry_delay_seconds = 5
+# Milliseconds to wait for a batch to fill before submitting what is ready
+batch_max_wait_ms = 500
 # Overall settlement timeout in seconds
 settlement_timeout_seconds = 300
+# Most instructions the durable queue holds before source ingest waits for room
+queue_capacity = 1000
+# Seconds a worker holds a queued instruction before it is handed to another worker;
+# must exceed batch_max_wait_ms plus settlement_timeout_seconds plus 10s
+lease_timeout_seconds = 600
+# Seconds to stop settling after an error that blocks every settlement (e.g. empty vault)
+queue_pause_seconds = 300
+
+[retry]
+# Milliseconds between checks of failed settlements for a retry that has come due
+sweep_interval_ms = 5000
+
+[retry.default]
+# Settlement attempts before a failure is dead-lettered
+max_attempts = 3
+# Delay before the first retry; doubles on every further attempt
+base_delay_ms = 5000
+# Upper bound on the delay between attempts
+max_delay_ms = 300000
+# Fraction of each delay randomly taken off so retries spread out (0.0 - 1.0)
+jitter = 0.2
+
+# Per error class overrides, keyed by the error code (timeout, network, database, ...)
+[retry.classes.timeout]
+max_attempts = 5
+base_delay_ms = 10000
+max_delay_ms = 600000
+jitter = 0.2
+
+[retry.source_poll]
+# Backoff while polling Solana keeps failing (the listener never gives up)
+max_attempts = 1
+base_delay_ms = 5000
+max_delay_ms = 60000
+jitter = 0.2
+
+[retry.circuit_breaker]
+# Consecutive Aptos failures before submissions are refused
+failure_threshold = 5
+# Milliseconds to refuse submissions before letting a probe through
+reset_timeout_ms = 30000
 
 [monitoring]
 # Port for metrics server
@@ -405,9 +640,12 @@ health_check_port = 8080
 log_level = "info"
 # Enable Prometheus metrics
 enable_metrics = true
+# Bearer token for the dead-letter requeue and discard endpoints; they are
+# refused while unset (or set CYRUS_MONITORING__ADMIN_TOKEN)
+# admin_token = "change-me"
 
 [database]
-# SQLite database file path
+# Settlement store URL: sqlite:<path>, memory:, or postgres://... (requires the `postgres` feature)
 url = "sqlite:./cyrus-relayer.db"
 # Maximum database connections
 max_connections = 10
@@ -435,32 +673,54 @@ mod tests {
         let mut config = RelayerConfig {
             solana: crate::types::SolanaConfig {
                 rpc_url: "https://api.devnet.solana.com".to_string(),
+                fallback_rpc_urls: Vec::new(),
+                rpc_quorum: None,
+                rpc_timeout_ms: 10_000,
+                rpc_requests_per_second: 10,
+                max_concurrent_fetches: 8,
+                ws_url: None,
                 program_id: "".to_string(), // Invalid: empty
                 commitment: "confirmed".to_string(),
                 poll_interval_ms: 1000,
                 max_retries: 3,
+                subscribe_logs: true,
+                finality: crate::types::FinalityPolicy::Finalized,
             },
             aptos: crate::types::AptosConfig {
                 rpc_url: "https://fullnode.testnet.aptoslabs.com/v1".to_string(),
                 contract_address: "0x1".to_string(),
                 vault_owner: "0x1".to_string(),
-                private_key: "0x1".to_string(),
+                private_key: "0x0000000000000000000000000000000000000000000000000000000000000001".to_string(),
                 max_gas_amount: 200000,
                 gas_unit_price: 100,
+                gas_margin_percent: 20,
+                min_gas_unit_price: 100,
+                max_gas_unit_price: 10000,
                 transaction_timeout_secs: 30,
+                transaction_expiry_secs: 60,
+                max_resubmissions: 2,
+                gas_price_bump_percent: 20,
+                account_address: None,
+                key_file: None,
+                key_profile: None,
+                assets: [("USDC".to_string(), AptosAsset::VaultCoin)].into(),
             },
             processing: crate::types::ProcessingConfig {
                 max_concurrent_settlements: 10,
                 batch_size: 5,
-                retry_attempts: 3,
-                retry_delay_seconds: 5,
+                batch_max_wait_ms: 500,
                 settlement_timeout_seconds: 300,
+                queue_capacity: 1000,
+                lease_timeout_seconds: 600,
+                queue_pause_seconds: 300,
             },
+            retry: crate::retry::RetryConfig::default(),
             monitoring: crate::types::MonitoringConfig {
                 metrics_port: 9090,
                 health_check_port: 8080,
                 log_level: "info".to_string(),
                 enable_metrics: true,
+                admin_token: None,
             },
             database: crate::types::DatabaseConfig {
                 url: "sqlite:test.db".to_string(),
@@ -479,6 +739,69 @@ mod tests {
         
         // Should pass now
         assert!(builder.validate_config(&config).is_ok());
+
+        // Leases must outlive a settlement attempt
+        config.processing.lease_timeout_seconds = config.processing.settlement_timeout_seconds;
+        assert!(builder.validate_config(&config).is_err());
+        // ...and the batch wait before it, plus the bookkeeping around it
+        config.processing.lease_timeout_seconds = config.processing.settlement_timeout_seconds + 15;
+        assert!(builder.validate_config(&config).is_ok());
+        config.processing.batch_max_wait_ms = 5_000;
+        assert!(builder.validate_config(&config).is_err());
+        config.processing.batch_max_wait_ms = 500;
+        config.processing.lease_timeout_seconds = 600;
+
+        // Jitter is a fraction of the delay
+        config.retry.default.jitter = 1.5;
+        assert!(builder.validate_config(&config).is_err());
+        config.retry.default.jitter = 0.2;
+
+        // Batches hold at least one settlement
+        config.processing.batch_size = 0;
+        assert!(builder.validate_config(&config).is_err());
+        config.processing.batch_size = 5;
+
+        // Resubmissions must fit inside a settlement attempt
+        config.aptos.max_resubmissions = 10;
+        assert!(builder.validate_config(&config).is_err());
+        config.aptos.max_resubmissions = 2;
+
+        // Gas price bounds must not be inverted
+        config.aptos.min_gas_unit_price = config.aptos.max_gas_unit_price + 1;
+        assert!(builder.validate_config(&config).is_err());
+        config.aptos.min_gas_unit_price = 100;
+
+        // Private keys are 32 bytes, with or without the CLI prefix
+        config.aptos.private_key = "0x1".to_string();
+        assert!(builder.validate_config(&config).is_err());
+        config.aptos.private_key =
+            "ed25519-priv-0x0000000000000000000000000000000000000000000000000000000000000001".to_string();
+        assert!(builder.validate_config(&config).is_ok());
+
+        // A key file replaces the inline key
+        config.aptos.private_key = String::new();
+        assert!(builder.validate_config(&config).is_err());
+        config.aptos.key_file = Some("/etc/cyrus/relayer.key".to_string());
+        assert!(builder.validate_config(&config).is_ok());
+
+        config.aptos.account_address = Some("not-an-address".to_string());
+        assert!(builder.validate_config(&config).is_err());
+        config.aptos.account_address = None;
+
+        // Every registered asset must name a coin type or metadata object
+        config.aptos.assets.insert(
+            "USDT".to_string(),
+            AptosAsset::Coin { coin_type: "USDT".to_string() },
+        );
+        assert!(builder.validate_config(&config).is_err());
+        config.aptos.assets.insert(
+            "USDT".to_string(),
+            AptosAsset::FungibleAsset { metadata_address: "0xa".to_string() },
+        );
+        assert!(builder.validate_config(&config).is_ok());
+
+        config.aptos.assets.clear();
+        assert!(builder.validate_config(&config).is_err());
     }
 
     #[tokio::test]
@@ -495,7 +818,7 @@ vault_owner = "0x456"
 private_key = "0x789"
 "#;
 
-        let mut temp_file = NamedTempFile::new().unwrap();
+        let temp_file = NamedTempFile::new().unwrap();
         fs::write(temp_file.path(), config_content).unwrap();
 
         let mut builder = ConfigBuilder::new();
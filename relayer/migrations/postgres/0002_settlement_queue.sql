@@ -0,0 +1,20 @@
+-- Durable work queue of instructions waiting to be processed.
+-- Times are unix milliseconds so lease expiry is a plain integer comparison.
+CREATE TABLE IF NOT EXISTS settlement_queue (
+    instruction_id TEXT PRIMARY KEY NOT NULL
+        REFERENCES settlement_instructions (id) ON DELETE CASCADE,
+    enqueued_at BIGINT NOT NULL,
+    available_at BIGINT NOT NULL,
+    lease_owner TEXT,
+    lease_expires_at BIGINT,
+    attempts BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_queue_available
+    ON settlement_queue (available_at, enqueued_at);
+
+-- Queue everything that was still in flight before the queue existed
+INSERT INTO settlement_queue (instruction_id, enqueued_at, available_at)
+SELECT r.instruction_id, 0, 0
+FROM settlement_results r
+WHERE r.status IN ('pending', 'processing', 'retrying');
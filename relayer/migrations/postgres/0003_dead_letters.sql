@@ -0,0 +1,27 @@
+-- Gas settings an operator attached when requeueing a dead-lettered settlement
+ALTER TABLE settlement_queue ADD COLUMN IF NOT EXISTS max_gas_amount BIGINT;
+ALTER TABLE settlement_queue ADD COLUMN IF NOT EXISTS gas_unit_price BIGINT;
+
+-- One row per processing round, kept for dead-letter inspection
+CREATE TABLE IF NOT EXISTS settlement_attempts (
+    instruction_id TEXT NOT NULL
+        REFERENCES settlement_instructions (id) ON DELETE CASCADE,
+    attempt BIGINT NOT NULL,
+    status TEXT NOT NULL,
+    error_class TEXT,
+    error_message TEXT,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (instruction_id, attempt)
+);
+
+-- Settlements parked for operator review
+CREATE TABLE IF NOT EXISTS settlement_dead_letters (
+    instruction_id TEXT PRIMARY KEY NOT NULL
+        REFERENCES settlement_instructions (id) ON DELETE CASCADE,
+    error_class TEXT NOT NULL,
+    last_error TEXT NOT NULL,
+    dead_lettered_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_dead_letters_time
+    ON settlement_dead_letters (dead_lettered_at);
@@ -0,0 +1,3 @@
+-- Position of the settlement event within its source transaction; part of the
+-- deterministic settlement ID. Existing rows predate multi-event support.
+ALTER TABLE settlement_instructions ADD COLUMN IF NOT EXISTS event_index BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,22 @@
+-- Every status change a settlement went through, oldest first by id
+CREATE TABLE IF NOT EXISTS settlement_transitions (
+    id BIGSERIAL PRIMARY KEY,
+    instruction_id TEXT NOT NULL
+        REFERENCES settlement_instructions (id) ON DELETE CASCADE,
+    from_status TEXT,
+    to_status TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    reason TEXT,
+    transitioned_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_transitions_instruction
+    ON settlement_transitions (instruction_id);
+
+-- Settlements stored before this migration start their timeline at the current status
+INSERT INTO settlement_transitions (
+    instruction_id, from_status, to_status, actor, reason, transitioned_at
+)
+SELECT instruction_id, NULL, status, 'migration', 'Recorded before transition history',
+    processed_at
+FROM settlement_results;
@@ -0,0 +1,13 @@
+-- Source events held until final, with the decision taken on each
+CREATE TABLE IF NOT EXISTS settlement_finality (
+    instruction_id TEXT PRIMARY KEY,
+    instruction TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    state TEXT NOT NULL,
+    reason TEXT,
+    held_at TIMESTAMPTZ NOT NULL,
+    decided_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_finality_state
+    ON settlement_finality (state, held_at);
@@ -0,0 +1,33 @@
+-- Settlement instructions received from source chains
+CREATE TABLE IF NOT EXISTS settlement_instructions (
+    id TEXT PRIMARY KEY NOT NULL,
+    source_chain TEXT NOT NULL,
+    source_tx_hash TEXT NOT NULL,
+    destination_chain TEXT NOT NULL,
+    sender TEXT NOT NULL,
+    receiver TEXT NOT NULL,
+    token_symbol TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    nonce INTEGER NOT NULL,
+    timestamp TEXT NOT NULL,
+    payload BLOB,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_instructions_source_tx
+    ON settlement_instructions (source_chain, source_tx_hash);
+
+-- Latest processing result for each instruction
+CREATE TABLE IF NOT EXISTS settlement_results (
+    instruction_id TEXT PRIMARY KEY NOT NULL
+        REFERENCES settlement_instructions (id) ON DELETE CASCADE,
+    status TEXT NOT NULL,
+    destination_tx_hash TEXT,
+    gas_used INTEGER,
+    error_message TEXT,
+    processed_at TEXT NOT NULL,
+    retry_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_results_status
+    ON settlement_results (status);
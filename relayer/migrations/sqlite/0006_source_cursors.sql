@@ -0,0 +1,7 @@
+-- Newest source transaction each listener has handed off, so restarts resume exactly
+CREATE TABLE IF NOT EXISTS source_cursors (
+    source TEXT PRIMARY KEY,
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,228 @@
+//! Operator commands for the dead-letter queue
+//!
+//! Exposed on the binary as `cyrus-relayer dlq <list|show|requeue|discard>`.
+//! Commands talk to the settlement store directly, so they work whether or
+//! not a relayer is running against the same database.
+
+use crate::{
+    database::SettlementStore,
+    types::{GasOverrides, SettlementError},
+};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use uuid::Uuid;
+
+/// A parsed `dlq` subcommand
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeadLetterCommand {
+    List { limit: Option<u32> },
+    Show { id: Uuid },
+    Requeue { id: Uuid, gas_overrides: GasOverrides },
+    Discard { id: Uuid },
+}
+
+/// Command line definition for `cyrus-relayer dlq`
+pub fn dead_letter_cli() -> Command {
+    let id_arg = || {
+        Arg::new("id")
+            .value_name("SETTLEMENT_ID")
+            .help("Settlement instruction ID")
+            .required(true)
+            .value_parser(value_parser!(Uuid))
+    };
+
+    Command::new("dlq")
+        .bin_name("cyrus-relayer dlq")
+        .about("Inspect and resolve dead-lettered settlements")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("database-url")
+                .long("database-url")
+                .value_name("URL")
+                .help("Database URL")
+                .env("DATABASE_URL")
+                .default_value("sqlite:./cyrus-relayer.db")
+                .global(true),
+        )
+        .subcommand(
+            Command::new("list").about("List dead-lettered settlements").arg(
+                Arg::new("limit")
+                    .long("limit")
+                    .value_name("N")
+                    .help("Maximum number of settlements to show")
+                    .value_parser(value_parser!(u32)),
+            ),
+        )
+        .subcommand(
+            Command::new("show")
+                .about("Show a dead-lettered settlement with its attempt history")
+                .arg(id_arg()),
+        )
+        .subcommand(
+            Command::new("requeue")
+                .about("Send a dead-lettered settlement back to the work queue")
+                .arg(id_arg())
+                .arg(
+                    Arg::new("max-gas-amount")
+                        .long("max-gas-amount")
+                        .value_name("GAS")
+                        .help("Override the configured max gas amount")
+                        .value_parser(value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("gas-unit-price")
+                        .long("gas-unit-price")
+                        .value_name("PRICE")
+                        .help("Override the configured gas unit price")
+                        .value_parser(value_parser!(u64)),
+                ),
+        )
+        .subcommand(
+            Command::new("discard")
+                .about("Permanently drop a dead-lettered settlement")
+                .arg(id_arg()),
+        )
+}
+
+impl DeadLetterCommand {
+    /// Build the command from matches produced by [`dead_letter_cli`]
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, SettlementError> {
+        let id = |matches: &ArgMatches| {
+            *matches
+                .get_one::<Uuid>("id")
+                .expect("id is a required argument")
+        };
+
+        match matches.subcommand() {
+            Some(("list", sub)) => Ok(Self::List {
+                limit: sub.get_one::<u32>("limit").copied(),
+            }),
+            Some(("show", sub)) => Ok(Self::Show { id: id(sub) }),
+            Some(("requeue", sub)) => Ok(Self::Requeue {
+                id: id(sub),
+                gas_overrides: GasOverrides {
+                    max_gas_amount: sub.get_one::<u64>("max-gas-amount").copied(),
+                    gas_unit_price: sub.get_one::<u64>("gas-unit-price").copied(),
+                },
+            }),
+            Some(("discard", sub)) => Ok(Self::Discard { id: id(sub) }),
+            Some((other, _)) => Err(SettlementError::ConfigError(format!(
+                "Unknown dlq command: {}",
+                other
+            ))),
+            None => Err(SettlementError::ConfigError("Missing dlq command".to_string())),
+        }
+    }
+
+    /// Run the command against `store`, returning the text to print
+    pub async fn run(&self, store: &dyn SettlementStore) -> Result<String, SettlementError> {
+        match self {
+            Self::List { limit } => {
+                let dead_letters = store.list_dead_letters(*limit).await?;
+                Ok(serde_json::to_string_pretty(&dead_letters)?)
+            }
+            Self::Show { id } => match store.get_dead_letter(*id).await? {
+                Some(dead_letter) => Ok(serde_json::to_string_pretty(&dead_letter)?),
+                None => Err(not_dead_lettered(*id)),
+            },
+            Self::Requeue { id, gas_overrides } => {
+                if store.requeue_dead_letter(*id, *gas_overrides, "cli").await? {
+                    Ok(format!("Requeued settlement {}", id))
+                } else {
+                    Err(not_dead_lettered(*id))
+                }
+            }
+            Self::Discard { id } => {
+                if store.discard_dead_letter(*id, "cli").await? {
+                    Ok(format!("Discarded settlement {}", id))
+                } else {
+                    Err(not_dead_lettered(*id))
+                }
+            }
+        }
+    }
+}
+
+fn not_dead_lettered(id: Uuid) -> SettlementError {
+    SettlementError::InvalidInstruction(format!("Settlement {} is not dead-lettered", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::MemoryStore,
+        types::{Address, ChainId, SettlementInstruction, SettlementStatus, TransactionHash},
+    };
+    use chrono::Utc;
+
+    fn parse(args: &[&str]) -> DeadLetterCommand {
+        let matches = dead_letter_cli().try_get_matches_from(args).unwrap();
+        DeadLetterCommand::from_matches(&matches).unwrap()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        let id = Uuid::new_v4();
+
+        assert_eq!(parse(&["dlq", "list"]), DeadLetterCommand::List { limit: None });
+        assert_eq!(
+            parse(&["dlq", "list", "--limit", "5"]),
+            DeadLetterCommand::List { limit: Some(5) }
+        );
+        assert_eq!(
+            parse(&["dlq", "requeue", &id.to_string(), "--gas-unit-price", "150"]),
+            DeadLetterCommand::Requeue {
+                id,
+                gas_overrides: GasOverrides {
+                    max_gas_amount: None,
+                    gas_unit_price: Some(150),
+                },
+            }
+        );
+        assert_eq!(
+            parse(&["dlq", "discard", &id.to_string()]),
+            DeadLetterCommand::Discard { id }
+        );
+
+        assert!(dead_letter_cli().try_get_matches_from(["dlq", "show", "not-a-uuid"]).is_err());
+        assert!(dead_letter_cli().try_get_matches_from(["dlq"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_requeue_and_discard() {
+        let store = MemoryStore::new();
+        let instruction = SettlementInstruction::new(
+            ChainId("solana".to_string()),
+            TransactionHash("tx_1".to_string()),
+            ChainId("aptos".to_string()),
+            Address("sender".to_string()),
+            Address("0x123".to_string()),
+            "USDC".to_string(),
+            1_000_000,
+            1,
+            Utc::now(),
+            None,
+        );
+        store.enqueue_instruction(&instruction).await.unwrap();
+        for status in [SettlementStatus::Processing, SettlementStatus::Failed] {
+            store.transition_status(instruction.id, status, "worker", None).await.unwrap();
+        }
+        store
+            .dead_letter_instruction(instruction.id, "timeout", "Confirmation timeout", "worker")
+            .await
+            .unwrap();
+
+        let listed = DeadLetterCommand::List { limit: None }.run(&store).await.unwrap();
+        assert!(listed.contains(&instruction.id.to_string()));
+
+        let requeue = DeadLetterCommand::Requeue {
+            id: instruction.id,
+            gas_overrides: GasOverrides::default(),
+        };
+        assert!(requeue.run(&store).await.is_ok());
+        // Already back on the queue, so there is nothing left to requeue or discard
+        assert!(requeue.run(&store).await.is_err());
+        let discard = DeadLetterCommand::Discard { id: instruction.id };
+        assert!(discard.run(&store).await.is_err());
+    }
+}
@@ -0,0 +1,359 @@
+//! Operator command to re-scan Solana program history
+//!
+//! Exposed on the binary as `cyrus-relayer backfill`. Events found in the
+//! range that the store has never seen are held for finality exactly like
+//! live ones, so a running relayer settles them through the normal queue.
+//! Events already held, queued or settled are skipped.
+
+use crate::{
+    chains::SolanaChain,
+    database::SettlementStore,
+    types::{
+        FinalityPolicy, FinalityState, HistoryRange, SettlementError, SettlementInstruction,
+        SolanaConfig,
+    },
+};
+use clap::{value_parser, Arg, ArgGroup, ArgMatches, Command};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// A parsed `backfill` command
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackfillCommand {
+    /// RPC endpoints, the first preferred and the rest failed over to
+    pub rpc_urls: Vec<String>,
+    pub program_id: String,
+    pub commitment: String,
+    pub range: HistoryRange,
+    /// Transactions fetched at once
+    pub max_concurrent_fetches: usize,
+    /// Requests each endpoint may be sent per second, 0 for no limit
+    pub requests_per_second: u32,
+    /// Only report what would be settled
+    pub dry_run: bool,
+}
+
+/// What a backfill found
+#[derive(Debug, Clone, Serialize)]
+pub struct BackfillReport {
+    pub dry_run: bool,
+    /// Settlement events in the range
+    pub events_found: usize,
+    /// Events the store already knew about
+    pub already_known: usize,
+    /// Events new to the store or retracted by it, held for finality unless
+    /// this was a dry run
+    pub new_events: Vec<BackfillEvent>,
+}
+
+/// A settlement event new to the store
+#[derive(Debug, Clone, Serialize)]
+pub struct BackfillEvent {
+    pub slot: u64,
+    pub instruction: SettlementInstruction,
+}
+
+/// Command line definition for `cyrus-relayer backfill`
+pub fn backfill_cli() -> Command {
+    Command::new("backfill")
+        .bin_name("cyrus-relayer backfill")
+        .about("Re-scan a slot or signature range of the Solana program's history")
+        .arg(
+            Arg::new("database-url")
+                .long("database-url")
+                .value_name("URL")
+                .help("Database URL")
+                .env("DATABASE_URL")
+                .default_value("sqlite:./cyrus-relayer.db"),
+        )
+        .arg(
+            Arg::new("rpc-url")
+                .long("rpc-url")
+                .value_name("URL")
+                .help("Solana RPC URL; repeat or separate with commas to fail over")
+                .env("SOLANA_RPC_URL")
+                .action(clap::ArgAction::Append)
+                .value_delimiter(',')
+                .default_value("https://api.devnet.solana.com"),
+        )
+        .arg(
+            Arg::new("program-id")
+                .long("program-id")
+                .value_name("PUBKEY")
+                .help("Cyrus program ID")
+                .env("SOLANA_PROGRAM_ID")
+                .required(true),
+        )
+        .arg(
+            Arg::new("commitment")
+                .long("commitment")
+                .value_name("LEVEL")
+                .help("Commitment to read history at")
+                .value_parser(["confirmed", "finalized"])
+                .default_value("confirmed"),
+        )
+        .arg(
+            Arg::new("from-slot")
+                .long("from-slot")
+                .value_name("SLOT")
+                .help("First slot to scan")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("to-slot")
+                .long("to-slot")
+                .value_name("SLOT")
+                .help("Last slot to scan, the newest when omitted")
+                .requires("from-slot")
+                .conflicts_with("after-signature")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("after-signature")
+                .long("after-signature")
+                .value_name("SIGNATURE")
+                .help("Scan transactions after this one"),
+        )
+        .arg(
+            Arg::new("before-signature")
+                .long("before-signature")
+                .value_name("SIGNATURE")
+                .help("Scan transactions before this one, up to the newest when omitted")
+                .requires("after-signature")
+                .conflicts_with("from-slot"),
+        )
+        .group(
+            ArgGroup::new("range")
+                .args(["from-slot", "after-signature"])
+                .required(true),
+        )
+        .arg(
+            Arg::new("concurrency")
+                .long("concurrency")
+                .value_name("N")
+                .help("Transactions to fetch at once")
+                .value_parser(value_parser!(u64).range(1..))
+                .default_value("8"),
+        )
+        .arg(
+            Arg::new("requests-per-second")
+                .long("requests-per-second")
+                .value_name("N")
+                .help("Requests to send each RPC endpoint per second, 0 for no limit")
+                .value_parser(value_parser!(u32))
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Only report what would be settled")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+impl BackfillCommand {
+    /// Build the command from matches produced by [`backfill_cli`]
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, SettlementError> {
+        let range = match matches.get_one::<u64>("from-slot") {
+            Some(&from) => {
+                let to = matches.get_one::<u64>("to-slot").copied();
+                if to.is_some_and(|to| to < from) {
+                    return Err(SettlementError::ConfigError(format!(
+                        "--to-slot must not be before --from-slot {}",
+                        from
+                    )));
+                }
+                HistoryRange::Slots { from, to }
+            }
+            None => HistoryRange::Signatures {
+                after: matches
+                    .get_one::<String>("after-signature")
+                    .cloned()
+                    .expect("range group is required"),
+                before: matches.get_one::<String>("before-signature").cloned(),
+            },
+        };
+
+        Ok(Self {
+            rpc_urls: matches
+                .get_many::<String>("rpc-url")
+                .map(|urls| urls.cloned().collect())
+                .unwrap_or_default(),
+            program_id: matches.get_one::<String>("program-id").cloned().unwrap_or_default(),
+            commitment: matches.get_one::<String>("commitment").cloned().unwrap_or_default(),
+            range,
+            max_concurrent_fetches: matches.get_one::<u64>("concurrency").copied().unwrap_or(1) as usize,
+            requests_per_second: matches.get_one::<u32>("requests-per-second").copied().unwrap_or_default(),
+            dry_run: matches.get_flag("dry-run"),
+        })
+    }
+
+    /// Solana settings for the scan; the relayer's own settings decide finality
+    fn solana_config(&self) -> SolanaConfig {
+        let (rpc_url, fallback_rpc_urls) = match self.rpc_urls.split_first() {
+            Some((first, rest)) => (first.clone(), rest.to_vec()),
+            None => (String::new(), Vec::new()),
+        };
+        SolanaConfig {
+            rpc_url,
+            fallback_rpc_urls,
+            rpc_quorum: None,
+            rpc_timeout_ms: 10_000,
+            rpc_requests_per_second: self.requests_per_second,
+            max_concurrent_fetches: self.max_concurrent_fetches,
+            ws_url: None,
+            program_id: self.program_id.clone(),
+            commitment: self.commitment.clone(),
+            poll_interval_ms: 1000,
+            max_retries: 3,
+            subscribe_logs: false,
+            finality: FinalityPolicy::Finalized,
+        }
+    }
+
+    /// Scan the range and hold new events in `store`, returning the text to print
+    pub async fn run(&self, store: Arc<dyn SettlementStore>) -> Result<String, SettlementError> {
+        let chain = SolanaChain::new(self.solana_config())?;
+        let events = chain.history_events(&self.range).await?;
+        let report = backfill(store.as_ref(), events, self.dry_run).await?;
+        Ok(serde_json::to_string_pretty(&report)?)
+    }
+}
+
+/// Hold every event in `events` the store has not seen or has retracted, or
+/// only count them when `dry_run` is set
+pub async fn backfill(
+    store: &dyn SettlementStore,
+    events: Vec<(u64, SettlementInstruction)>,
+    dry_run: bool,
+) -> Result<BackfillReport, SettlementError> {
+    let mut report = BackfillReport {
+        dry_run,
+        events_found: events.len(),
+        already_known: 0,
+        new_events: Vec::new(),
+    };
+
+    for (slot, instruction) in events {
+        // Settlement IDs derive from the source event, so a re-scan finds the same ones.
+        // A retracted event this scan just found on chain is held again.
+        let known = store.get_settlement(instruction.id).await?.is_some()
+            || store
+                .get_finality(instruction.id)
+                .await?
+                .is_some_and(|record| record.state != FinalityState::Retracted);
+        if known || (!dry_run && !store.hold_for_finality(&instruction, slot).await?) {
+            report.already_known += 1;
+            continue;
+        }
+        report.new_events.push(BackfillEvent { slot, instruction });
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::MemoryStore,
+        types::{Address, ChainId, TransactionHash},
+    };
+    use chrono::Utc;
+
+    fn parse(args: &[&str]) -> Result<BackfillCommand, String> {
+        let matches = backfill_cli()
+            .try_get_matches_from(args)
+            .map_err(|e| e.to_string())?;
+        BackfillCommand::from_matches(&matches).map_err(|e| e.to_string())
+    }
+
+    fn instruction(tx_hash: &str) -> SettlementInstruction {
+        SettlementInstruction::new(
+            ChainId("solana".to_string()),
+            TransactionHash(tx_hash.to_string()),
+            ChainId("aptos".to_string()),
+            Address("sender".to_string()),
+            Address("0x123".to_string()),
+            "USDC".to_string(),
+            1_000_000,
+            1,
+            Utc::now(),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_parse_ranges() {
+        let base = ["backfill", "--program-id", "11111111111111111111111111111112"];
+        let with = |extra: &[&str]| parse(&[&base[..], extra].concat());
+
+        let command = with(&["--from-slot", "100", "--to-slot", "200", "--dry-run"]).unwrap();
+        assert_eq!(command.range, HistoryRange::Slots { from: 100, to: Some(200) });
+        assert!(command.dry_run);
+        assert_eq!((command.max_concurrent_fetches, command.requests_per_second), (8, 10));
+
+        let command = with(&["--from-slot", "100", "--concurrency", "2", "--requests-per-second", "0"]).unwrap();
+        assert_eq!((command.max_concurrent_fetches, command.requests_per_second), (2, 0));
+        assert!(with(&["--from-slot", "100", "--concurrency", "0"]).is_err());
+
+        let command = with(&["--from-slot", "100", "--rpc-url", "http://a,http://b"]).unwrap();
+        assert_eq!(command.rpc_urls, ["http://a", "http://b"]);
+
+        let command = with(&["--after-signature", "sig_a"]).unwrap();
+        assert_eq!(
+            command.range,
+            HistoryRange::Signatures {
+                after: "sig_a".to_string(),
+                before: None,
+            }
+        );
+        assert!(!command.dry_run);
+
+        // Exactly one kind of range, in order
+        assert!(with(&[]).is_err());
+        assert!(with(&["--from-slot", "1", "--after-signature", "sig_a"]).is_err());
+        assert!(with(&["--to-slot", "200", "--after-signature", "sig_a"]).is_err());
+        assert!(with(&["--from-slot", "200", "--to-slot", "100"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_skips_known_events() {
+        let store = MemoryStore::new();
+        let settled = instruction("tx_settled");
+        let held = instruction("tx_held");
+        let missed = instruction("tx_missed");
+        let retracted = instruction("tx_retracted");
+        store.enqueue_instruction(&settled).await.unwrap();
+        store.hold_for_finality(&held, 10).await.unwrap();
+        // Wrongly retracted, e.g. while the endpoint lagged behind the cluster
+        store.hold_for_finality(&retracted, 11).await.unwrap();
+        store.decide_finality(retracted.id, FinalityState::Retracted, "Transaction dropped").await.unwrap();
+
+        let events = vec![
+            (5, settled.clone()),
+            (10, held.clone()),
+            (11, retracted.clone()),
+            (12, missed.clone()),
+        ];
+
+        // A dry run reports the missed and retracted events without touching the store
+        let report = backfill(&store, events.clone(), true).await.unwrap();
+        assert_eq!(report.events_found, 4);
+        assert_eq!(report.already_known, 2);
+        let ids: Vec<_> = report.new_events.iter().map(|event| event.instruction.id).collect();
+        assert_eq!(ids, vec![retracted.id, missed.id]);
+        assert!(store.get_finality(missed.id).await.unwrap().is_none());
+        assert_eq!(store.get_finality(retracted.id).await.unwrap().unwrap().state, FinalityState::Retracted);
+
+        let report = backfill(&store, events.clone(), false).await.unwrap();
+        assert_eq!(report.new_events.len(), 2);
+        assert_eq!(report.new_events[1].slot, 12);
+        assert_eq!(store.pending_finality().await.unwrap().len(), 3);
+        assert_eq!(store.get_finality(retracted.id).await.unwrap().unwrap().state, FinalityState::Pending);
+
+        // Running it again finds nothing new
+        let report = backfill(&store, events, false).await.unwrap();
+        assert_eq!(report.already_known, 4);
+        assert!(report.new_events.is_empty());
+    }
+}
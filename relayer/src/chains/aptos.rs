@@ -1,31 +1,38 @@
-use crate::types::{
-    Address, AptosConfig, SettlementError, SettlementInstruction, SettlementResult,
-    TransactionHash,
-};
-use aptos_sdk::{
-    coin_client::CoinClient,
-    crypto::{ed25519::Ed25519PrivateKey, PrivateKey},
-    move_types::{
-        account_address::AccountAddress,
-        identifier::Identifier,
-        language_storage::{ModuleId, StructTag},
-        value::{serialize_values, MoveValue},
-    },
-    rest_client::{Client, FaucetClient},
-    transaction_builder::TransactionBuilder,
+use crate::{
+    keys,
+    retry::CircuitBreaker,
     types::{
-        account_config::aptos_coin_type,
-        chain_id::ChainId,
-        transaction::{EntryFunction, TransactionPayload},
-        LocalAccount,
+        AptosAsset, AptosConfig, GasOverrides, MoveAbort, SettlementAbort, SettlementCheck,
+        SettlementError, SettlementInstruction, SettlementResult, SettlementStatus, TransactionHash,
     },
 };
+use aptos_sdk::{
+    account::Ed25519Account,
+    api::FullnodeClient,
+    aptos_bcs,
+    transaction::{EntryFunction, SignedTransaction, TransactionBuilder, TransactionPayload},
+    types::{AccountAddress, ChainId, HashValue, Identifier, MoveModuleId, TypeTag},
+    AptosConfig as NodeConfig, AptosError,
+};
 use async_trait::async_trait;
 use chrono::Utc;
-use std::{str::FromStr, sync::Arc, time::Duration};
-use tokio::time::timeout;
+use futures_util::{
+    future::{join_all, select_ok},
+    TryFutureExt,
+};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    str::FromStr,
+    sync::{Arc, Mutex as StdMutex, Weak},
+    time::Duration,
+};
+use tokio::{
+    sync::{oneshot, Mutex, OnceCell},
+    time::{sleep, timeout},
+};
 use tracing::{debug, error, info, warn};
-use url::Url;
+use uuid::Uuid;
 
 /// Trait for interacting with destination chains
 #[async_trait]
@@ -33,73 +40,724 @@ pub trait DestinationChain: Send + Sync {
     async fn submit_settlement(
         &self,
         instruction: &SettlementInstruction,
+    ) -> Result<SettlementResult, SettlementError> {
+        self.submit_settlement_with_gas(instruction, &GasOverrides::default()).await
+    }
+    /// Submit using per-settlement gas settings in place of the configured defaults
+    async fn submit_settlement_with_gas(
+        &self,
+        instruction: &SettlementInstruction,
+        gas_overrides: &GasOverrides,
     ) -> Result<SettlementResult, SettlementError>;
-    async fn is_settlement_processed(
+    /// Submit several settlements together, returning one outcome per instruction in order.
+    ///
+    /// An `Err` means the batch as a whole failed. The default submits them one at a time.
+    async fn submit_settlement_batch(
         &self,
-        tx_hash: &TransactionHash,
-    ) -> Result<bool, SettlementError>;
-    async fn get_vault_balance(&self) -> Result<u64, SettlementError>;
-    async fn get_total_settled(&self) -> Result<u64, SettlementError>;
+        instructions: &[SettlementInstruction],
+    ) -> Result<Vec<Result<SettlementResult, SettlementError>>, SettlementError> {
+        let mut outcomes = Vec::with_capacity(instructions.len());
+        for instruction in instructions {
+            outcomes.push(self.submit_settlement(instruction).await);
+        }
+        Ok(outcomes)
+    }
+    /// Whether the vault has recorded `settlement_key`; see [`SettlementInstruction::settlement_key`].
+    ///
+    /// Never guesses: a failed or unreadable query is [`SettlementCheck::Unknown`].
+    async fn check_settlement(&self, settlement_key: &str) -> SettlementCheck;
+    /// Vault balance of `token_symbol`, in its smallest unit
+    async fn get_vault_balance(&self, token_symbol: &str) -> Result<u64, SettlementError>;
+    /// Amount of `token_symbol` paid out by the vault so far
+    async fn get_total_settled(&self, token_symbol: &str) -> Result<u64, SettlementError>;
     async fn check_health(&self) -> Result<bool, SettlementError>;
 }
 
+/// BCS encoding of an entry function argument
+fn bcs<T: Serialize + ?Sized>(value: &T) -> Vec<u8> {
+    aptos_bcs::to_bytes(value).expect("plain Move values always serialize")
+}
+
+/// A transaction as rendered by the node's JSON API
+#[derive(Debug, Clone)]
+pub struct NodeTransaction(serde_json::Value);
+
+impl NodeTransaction {
+    fn is_pending(&self) -> bool {
+        self.0["type"] == "pending_transaction"
+    }
+
+    fn success(&self) -> bool {
+        self.0["success"].as_bool().unwrap_or(false)
+    }
+
+    fn vm_status(&self) -> String {
+        self.0["vm_status"].as_str().unwrap_or_default().to_string()
+    }
+
+    /// u64 fields are rendered as strings in JSON
+    fn gas_used(&self) -> Option<u64> {
+        let gas_used = &self.0["gas_used"];
+        gas_used
+            .as_str()
+            .and_then(|gas_used| gas_used.parse().ok())
+            .or_else(|| gas_used.as_u64())
+    }
+
+    fn events(&self) -> &[serde_json::Value] {
+        self.0["events"].as_array().map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+/// What the settlement module reported for one item of a `settle_batch` call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BatchItemOutcome {
+    Settled,
+    /// Skipped with the given `E_*` reason code
+    Skipped(u64),
+}
+
+/// A registry entry resolved to what the settlement module expects
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum PayoutAsset {
+    VaultCoin,
+    Coin(TypeTag),
+    /// Address of the asset's metadata object
+    FungibleAsset(AccountAddress),
+}
+
+/// A per-asset figure kept by the vault
+#[derive(Debug, Clone, Copy)]
+enum ReserveView {
+    Balance,
+    TotalSettled,
+}
+
+impl PayoutAsset {
+    fn resolve(asset: &AptosAsset) -> Result<Self, SettlementError> {
+        match asset {
+            AptosAsset::VaultCoin => Ok(Self::VaultCoin),
+            AptosAsset::Coin { coin_type } => TypeTag::from_str_strict(coin_type)
+                .map(Self::Coin)
+                .map_err(|e| SettlementError::ConfigError(format!("Invalid coin type {}: {}", coin_type, e))),
+            AptosAsset::FungibleAsset { metadata_address } => AccountAddress::from_hex(metadata_address)
+                .map(Self::FungibleAsset)
+                .map_err(|e| {
+                    SettlementError::ConfigError(format!("Invalid metadata address {}: {}", metadata_address, e))
+                }),
+        }
+    }
+
+    /// Entry function settling one instruction, or a batch of them
+    fn settle_function(&self, batch: bool) -> &'static str {
+        match (self, batch) {
+            (Self::VaultCoin, false) => "settle",
+            (Self::VaultCoin, true) => "settle_batch",
+            (Self::Coin(_), false) => "settle_coin",
+            (Self::Coin(_), true) => "settle_batch_coin",
+            (Self::FungibleAsset(_), false) => "settle_fa",
+            (Self::FungibleAsset(_), true) => "settle_batch_fa",
+        }
+    }
+
+    fn type_args(&self) -> Vec<TypeTag> {
+        match self {
+            Self::Coin(coin_type) => vec![coin_type.clone()],
+            _ => vec![],
+        }
+    }
+
+    /// BCS-encoded settle arguments identifying the asset, passed right after the vault owner
+    fn asset_args(&self) -> Vec<Vec<u8>> {
+        match self {
+            Self::FungibleAsset(metadata) => vec![bcs(metadata)],
+            _ => vec![],
+        }
+    }
+
+    /// View function, type arguments and arguments reading `view` for this asset
+    fn view_call(&self, vault_owner: AccountAddress, view: ReserveView) -> (&'static str, Vec<String>, Vec<String>) {
+        let owner = vault_owner.to_short_string();
+        match (self, view) {
+            (Self::VaultCoin, ReserveView::Balance) => ("get_vault_balance", vec![], vec![owner]),
+            (Self::VaultCoin, ReserveView::TotalSettled) => ("get_total_settled", vec![], vec![owner]),
+            (Self::Coin(coin_type), ReserveView::Balance) => {
+                ("get_coin_balance", vec![coin_type.to_string()], vec![owner])
+            }
+            (Self::Coin(coin_type), ReserveView::TotalSettled) => {
+                ("get_coin_total_settled", vec![coin_type.to_string()], vec![owner])
+            }
+            (Self::FungibleAsset(metadata), ReserveView::Balance) => {
+                ("get_fa_balance", vec![], vec![owner, metadata.to_short_string()])
+            }
+            (Self::FungibleAsset(metadata), ReserveView::TotalSettled) => {
+                ("get_fa_total_settled", vec![], vec![owner, metadata.to_short_string()])
+            }
+        }
+    }
+}
+
+/// How often in-flight transactions are checked for confirmation
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Hands out account sequence numbers locally so concurrent submissions do not
+/// collide on the same number.
+///
+/// The next number is fetched from the chain on first use and again after
+/// [`SequenceNumberManager::invalidate`], e.g. when the node reports the local
+/// count as too old or too new.
+pub struct SequenceNumberManager {
+    client: Arc<FullnodeClient>,
+    address: AccountAddress,
+    /// `None` until the next number has been fetched from the chain
+    next: Mutex<Option<u64>>,
+}
+
+impl SequenceNumberManager {
+    pub fn new(client: Arc<FullnodeClient>, address: AccountAddress) -> Self {
+        Self {
+            client,
+            address,
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Reserve the next sequence number
+    pub async fn allocate(&self) -> Result<u64, SettlementError> {
+        let mut next = self.next.lock().await;
+        let sequence_number = match *next {
+            Some(sequence_number) => sequence_number,
+            None => self.fetch().await?,
+        };
+        *next = Some(sequence_number + 1);
+        Ok(sequence_number)
+    }
+
+    /// Forget the local count; the next allocation resyncs with the chain
+    pub async fn invalidate(&self) {
+        *self.next.lock().await = None;
+    }
+
+    /// The account's committed sequence number, without reserving anything
+    pub async fn on_chain(&self) -> Result<u64, SettlementError> {
+        self.fetch().await
+    }
+
+    async fn fetch(&self) -> Result<u64, SettlementError> {
+        let sequence_number = self.client
+            .get_sequence_number(self.address)
+            .await
+            .map_err(|e| SettlementError::ChainError(format!("Failed to get account info: {}", e)))?;
+
+        debug!("Synced sequence number for {}: {}", self.address, sequence_number);
+        Ok(sequence_number)
+    }
+}
+
+/// Whether a submission was rejected because its sequence number is out of step with the chain
+fn is_sequence_number_error(message: &str) -> bool {
+    message.contains("SEQUENCE_NUMBER_TOO_OLD") || message.contains("SEQUENCE_NUMBER_TOO_NEW")
+}
+
+/// Whether the node refused a submission outright, so its sequence number
+/// was certainly not taken. A timeout or 5xx may come after the node already
+/// accepted the transaction, so those leave the number in use.
+fn is_rejection(error: &AptosError) -> bool {
+    matches!(error, AptosError::Api { status_code: 400..=499, .. })
+}
+
+/// A submitted transaction and what is needed to replace it
+#[derive(Debug, Clone)]
+struct Submission {
+    tx_hash: String,
+    sequence_number: u64,
+    gas_unit_price: u64,
+    /// Unix seconds after which the chain will no longer commit it
+    expiration_secs: u64,
+}
+
+/// Gas price for a replacement transaction, always strictly higher than `gas_unit_price`
+fn bumped_gas_price(gas_unit_price: u64, bump_percent: u64) -> u64 {
+    let bumped = u128::from(gas_unit_price) * u128::from(bump_percent.saturating_add(100)) / 100;
+    let bumped = u64::try_from(bumped).unwrap_or(u64::MAX);
+    bumped.max(gas_unit_price.saturating_add(1))
+}
+
+/// Gas limit for a transaction that used `gas_used` in simulation.
+///
+/// Adds `margin_percent` of headroom, trimmed to `gas_cap`; fails when the
+/// simulation alone already needs more than the cap allows.
+fn gas_limit(gas_used: u64, margin_percent: u64, gas_cap: u64) -> Result<u64, SettlementError> {
+    if gas_used > gas_cap {
+        return Err(SettlementError::TransactionFailed(format!(
+            "Simulated gas {} exceeds the limit of {}",
+            gas_used, gas_cap
+        )));
+    }
+    let with_margin = u128::from(gas_used) * u128::from(margin_percent.saturating_add(100)) / 100;
+    Ok(u64::try_from(with_margin).unwrap_or(u64::MAX).min(gas_cap))
+}
+
+/// In-flight transactions waiting for confirmation, keyed by hash
+type PendingConfirmations = StdMutex<HashMap<String, Vec<oneshot::Sender<NodeTransaction>>>>;
+
+/// Follows submitted transactions until they are committed.
+///
+/// A single background task polls every in-flight hash, so submitters do not
+/// each block in their own polling loop and can keep submitting while earlier
+/// transactions confirm. The task stops when the tracker is dropped.
+pub struct ConfirmationTracker {
+    pending: Arc<PendingConfirmations>,
+}
+
+impl ConfirmationTracker {
+    pub fn new(client: Arc<FullnodeClient>, poll_interval: Duration) -> Self {
+        let pending: Arc<PendingConfirmations> = Arc::default();
+        tokio::spawn(Self::poll(client, Arc::downgrade(&pending), poll_interval));
+        Self { pending }
+    }
+
+    /// Resolves with the transaction once it is committed, successfully or not
+    pub fn track(&self, tx_hash: &str) -> oneshot::Receiver<NodeTransaction> {
+        let (sender, receiver) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap()
+            .entry(tx_hash.to_string())
+            .or_default()
+            .push(sender);
+        receiver
+    }
+
+    /// Stop following a transaction nobody is waiting on any more
+    pub fn forget(&self, tx_hash: &str) {
+        self.pending.lock().unwrap().remove(tx_hash);
+    }
+
+    /// Number of transactions still awaiting confirmation
+    pub fn in_flight(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    async fn poll(client: Arc<FullnodeClient>, pending: Weak<PendingConfirmations>, poll_interval: Duration) {
+        loop {
+            sleep(poll_interval).await;
+
+            let Some(pending) = pending.upgrade() else {
+                return;
+            };
+            let hashes: Vec<String> = pending.lock().unwrap().keys().cloned().collect();
+            if hashes.is_empty() {
+                continue;
+            }
+
+            let client = &client;
+            let lookups = hashes.iter().map(|tx_hash| async move {
+                let hash = HashValue::from_str(tx_hash).ok()?;
+                client.get_transaction_by_hash(&hash).await.ok()
+            });
+            for (tx_hash, lookup) in hashes.iter().zip(join_all(lookups).await) {
+                // Not yet known to the node
+                let Some(txn) = lookup else {
+                    continue;
+                };
+                let txn = NodeTransaction(txn.into_inner());
+                if txn.is_pending() {
+                    continue;
+                }
+
+                let waiters = pending.lock().unwrap().remove(tx_hash).unwrap_or_default();
+                for waiter in waiters {
+                    let _ = waiter.send(txn.clone());
+                }
+            }
+        }
+    }
+}
+
 /// Aptos chain implementation
 pub struct AptosChain {
-    client: Arc<Client>,
+    client: Arc<FullnodeClient>,
     config: AptosConfig,
-    account: LocalAccount,
+    account: Ed25519Account,
     contract_address: AccountAddress,
     vault_owner: AccountAddress,
+    sequence_numbers: SequenceNumberManager,
+    confirmations: ConfirmationTracker,
+    chain_id: OnceCell<ChainId>,
 }
 
 impl AptosChain {
     pub async fn new(config: AptosConfig) -> Result<Self, SettlementError> {
+        let node_config = NodeConfig::custom(&config.rpc_url)
+            .map_err(|e| SettlementError::ConfigError(format!("Invalid RPC URL: {}", e)))?;
         let client = Arc::new(
-            Client::new(
-                Url::parse(&config.rpc_url)
-                    .map_err(|e| SettlementError::ConfigError(format!("Invalid RPC URL: {}", e)))?,
-            )
+            FullnodeClient::new(node_config)
+                .map_err(|e| SettlementError::ConfigError(format!("Failed to create Aptos client: {}", e)))?,
         );
 
-        // Parse private key
-        let private_key_bytes = hex::decode(&config.private_key)
-            .map_err(|e| SettlementError::ConfigError(format!("Invalid private key: {}", e)))?;
-        
-        let private_key = Ed25519PrivateKey::try_from(private_key_bytes.as_slice())
-            .map_err(|e| SettlementError::ConfigError(format!("Invalid private key format: {}", e)))?;
-
-        // Create local account
-        let account = LocalAccount::new(
-            AccountAddress::from_hex_literal(&format!("0x{}", hex::encode(private_key.public_key().to_bytes())))
-                .map_err(|e| SettlementError::ConfigError(format!("Invalid account address: {}", e)))?,
-            private_key,
-            0, // sequence number will be fetched
-        );
+        // Load the signing key and the account it signs for
+        let key = keys::load(&config)?;
+        let account = Ed25519Account::from_private_key_bytes(&key.private_key_bytes()[..])
+            .map_err(|e| SettlementError::ConfigError(format!("Invalid private key format: {}", e)))?
+            .with_address(AccountAddress::new(key.address()));
 
-        let contract_address = AccountAddress::from_hex_literal(&config.contract_address)
+        let contract_address = AccountAddress::from_hex(&config.contract_address)
             .map_err(|e| SettlementError::ConfigError(format!("Invalid contract address: {}", e)))?;
 
-        let vault_owner = AccountAddress::from_hex_literal(&config.vault_owner)
+        let vault_owner = AccountAddress::from_hex(&config.vault_owner)
             .map_err(|e| SettlementError::ConfigError(format!("Invalid vault owner: {}", e)))?;
 
+        // Reject a malformed asset registry before anything is submitted
+        for asset in config.assets.values() {
+            PayoutAsset::resolve(asset)?;
+        }
+
+        let sequence_numbers = SequenceNumberManager::new(Arc::clone(&client), account.address());
+        let confirmations = ConfirmationTracker::new(Arc::clone(&client), CONFIRMATION_POLL_INTERVAL);
+
         Ok(Self {
             client,
             config,
             account,
             contract_address,
             vault_owner,
+            sequence_numbers,
+            confirmations,
+            chain_id: OnceCell::new(),
         })
     }
 
-    /// Sync account sequence number
-    async fn sync_account(&mut self) -> Result<(), SettlementError> {
-        let account_info = self.client
-            .get_account(self.account.address())
+    /// Chain ID of the connected network, fetched once
+    async fn chain_id(&self) -> Result<ChainId, SettlementError> {
+        self.chain_id
+            .get_or_try_init(|| async {
+                let ledger = self.client
+                    .get_ledger_info()
+                    .await
+                    .map_err(|e| SettlementError::ChainError(format!("Failed to get chain info: {}", e)))?;
+                Ok(ChainId::new(ledger.into_inner().chain_id))
+            })
             .await
-            .map_err(|e| SettlementError::ChainError(format!("Failed to get account info: {}", e)))?;
+            .copied()
+    }
+
+    /// Sign `payload` with the relayer account
+    fn sign(
+        &self,
+        payload: &TransactionPayload,
+        sequence_number: u64,
+        max_gas_amount: u64,
+        gas_unit_price: u64,
+        expiration_secs: u64,
+        chain_id: ChainId,
+    ) -> Result<SignedTransaction, SettlementError> {
+        TransactionBuilder::new()
+            .sender(self.account.address())
+            .sequence_number(sequence_number)
+            .payload(payload.clone())
+            .max_gas_amount(max_gas_amount)
+            .gas_unit_price(gas_unit_price)
+            .expiration_timestamp_secs(expiration_secs)
+            .chain_id(chain_id)
+            .build_and_sign(&self.account)
+            .map_err(|e| SettlementError::Unknown(format!("Failed to sign transaction: {}", e)))
+    }
+
+    /// Sign and submit a transaction.
+    ///
+    /// Uses the `replacing` sequence number to replace an earlier transaction, otherwise
+    /// allocates one locally. Returns without waiting for confirmation. A new
+    /// submission rejected for an out-of-step sequence number is retried once
+    /// after resyncing with the chain; one lost to a timeout or server error
+    /// keeps its number, since the node may have accepted it.
+    async fn submit_payload(
+        &self,
+        payload: TransactionPayload,
+        max_gas_amount: u64,
+        gas_unit_price: u64,
+        replacing: Option<u64>,
+    ) -> Result<Submission, SettlementError> {
+        let chain_id = self.chain_id().await?;
+        let mut resynced = false;
+
+        loop {
+            let sequence_number = match replacing {
+                Some(sequence_number) => sequence_number,
+                None => self.sequence_numbers.allocate().await?,
+            };
+            let expiration_secs =
+                chrono::Utc::now().timestamp() as u64 + self.config.transaction_expiry_secs;
+
+            let signed_txn = self.sign(
+                &payload,
+                sequence_number,
+                max_gas_amount,
+                gas_unit_price,
+                expiration_secs,
+                chain_id,
+            )?;
+
+            match self.client.submit_transaction(&signed_txn).await {
+                Ok(response) => {
+                    let tx_hash = response.into_inner().hash.to_string();
+                    debug!("Transaction submitted: {} (sequence number {})", tx_hash, sequence_number);
+                    return Ok(Submission {
+                        tx_hash,
+                        sequence_number,
+                        gas_unit_price,
+                        expiration_secs,
+                    });
+                }
+                Err(e) => {
+                    let message = e.to_string();
+                    // A replaced number stays with its original transaction, so only allocated ones
+                    // resync, and only once the node refused them: resyncing while the transaction
+                    // may still land would hand its number, and those after it, out again
+                    if replacing.is_none() && (is_sequence_number_error(&message) || is_rejection(&e)) {
+                        // The allocated number was not used, so later ones would be stuck behind it
+                        self.sequence_numbers.invalidate().await;
+                        if is_sequence_number_error(&message) && !resynced {
+                            warn!("Sequence number {} rejected, resyncing: {}", sequence_number, message);
+                            resynced = true;
+                            continue;
+                        }
+                    }
+                    return Err(SettlementError::NetworkError(format!("Submission failed: {}", message)));
+                }
+            }
+        }
+    }
+
+    /// Submit settlements and wait for the transaction to commit, replacing it
+    /// if it gets stuck or expires.
+    ///
+    /// A transaction that is not confirmed within `transaction_timeout_secs` is
+    /// replaced by one with the same sequence number and a higher gas price, so
+    /// at most one of them can ever commit. Before each replacement the
+    /// settlements are checked on chain and those already settled are left out.
+    ///
+    /// `max_gas_amount` and `gas_unit_price` come from simulating the
+    /// settlements beforehand.
+    ///
+    /// Returns the hash of the committed transaction, the transaction itself and
+    /// the settlements it carried.
+    async fn submit_with_recovery<'a>(
+        &self,
+        instructions: Vec<&'a SettlementInstruction>,
+        build_payload: impl Fn(&[&'a SettlementInstruction]) -> Result<TransactionPayload, SettlementError>,
+        max_gas_amount: u64,
+        gas_unit_price: u64,
+    ) -> Result<(String, NodeTransaction, Vec<&'a SettlementInstruction>), SettlementError> {
+        let mut pending = instructions;
+        let payload = build_payload(&pending)?;
+
+        let mut submission = self
+            .submit_payload(payload, max_gas_amount, gas_unit_price, None)
+            .await?;
+        let mut tx_hashes = vec![submission.tx_hash.clone()];
+        let mut resubmissions = 0;
+
+        loop {
+            let wait = Duration::from_secs(self.config.transaction_timeout_secs);
+            if let Some((tx_hash, txn)) = self.wait_for_any(&tx_hashes, wait).await? {
+                return Ok((tx_hash, txn, pending));
+            }
+
+            let expired = (Utc::now().timestamp() as u64) >= submission.expiration_secs;
+            if resubmissions >= self.config.max_resubmissions {
+                return Err(SettlementError::Timeout(format!(
+                    "Transaction {} not confirmed after {} resubmission(s)",
+                    submission.tx_hash, resubmissions
+                )));
+            }
+
+            // Never resubmit a settlement that has landed in the meantime
+            let mut unsettled = Vec::with_capacity(pending.len());
+            for instruction in &pending {
+                if !self.check_settlement(&instruction.settlement_key()).await.settled()? {
+                    unsettled.push(*instruction);
+                }
+            }
+            if unsettled.is_empty() {
+                // One of our transactions may have committed between the wait and the check
+                let wait = CONFIRMATION_POLL_INTERVAL * 2;
+                if let Some((tx_hash, txn)) = self.wait_for_any(&tx_hashes, wait).await? {
+                    return Ok((tx_hash, txn, pending));
+                }
+                let settled: Vec<String> = pending.iter().map(|i| i.settlement_key()).collect();
+                return Err(SettlementError::AlreadySettled(settled.join(", ")));
+            }
+            pending = unsettled;
+
+            let gas_unit_price = bumped_gas_price(
+                submission.gas_unit_price,
+                self.config.gas_price_bump_percent,
+            );
+            warn!(
+                "Transaction {} {}, resubmitting with sequence number {} at gas price {}",
+                submission.tx_hash,
+                if expired { "expired" } else { "is stuck" },
+                submission.sequence_number,
+                gas_unit_price
+            );
+            resubmissions += 1;
+
+            match self
+                .submit_payload(
+                    build_payload(&pending)?,
+                    max_gas_amount,
+                    gas_unit_price,
+                    Some(submission.sequence_number),
+                )
+                .await
+            {
+                Ok(replacement) => {
+                    tx_hashes.push(replacement.tx_hash.clone());
+                    submission = replacement;
+                }
+                // The sequence number was used after all; keep waiting for whichever copy landed
+                Err(e) if is_sequence_number_error(&e.to_string()) => {
+                    debug!("Sequence number {} already used: {}", submission.sequence_number, e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// The node's gas price estimate, kept within the configured bounds
+    async fn estimate_gas_unit_price(&self) -> u64 {
+        let estimate = match self.client.estimate_gas_price().await {
+            Ok(estimate) => estimate.into_inner().gas_estimate,
+            Err(e) => {
+                warn!("Gas price estimate unavailable, using {}: {}", self.config.gas_unit_price, e);
+                self.config.gas_unit_price
+            }
+        };
+        estimate.clamp(self.config.min_gas_unit_price, self.config.max_gas_unit_price)
+    }
+
+    /// Dry-run `payload` with a gas limit of `gas_cap`
+    async fn simulate(
+        &self,
+        payload: &TransactionPayload,
+        gas_cap: u64,
+        gas_unit_price: u64,
+    ) -> Result<NodeTransaction, SettlementError> {
+        // Simulation runs the prologue, which only accepts the next committed number
+        let sequence_number = self.sequence_numbers.on_chain().await?;
+        let chain_id = self.chain_id().await?;
+
+        let expiration_secs = Utc::now().timestamp() as u64 + self.config.transaction_expiry_secs;
+        let signed_txn = self.sign(payload, sequence_number, gas_cap, gas_unit_price, expiration_secs, chain_id)?;
+
+        // The client strips the signature, since the node refuses to simulate valid ones
+        let simulated = self.client
+            .simulate_transaction(&signed_txn)
+            .await
+            .map_err(|e| SettlementError::NetworkError(format!("Simulation failed: {}", e)))?
+            .into_inner();
+        simulated
+            .into_iter()
+            .next()
+            .map(NodeTransaction)
+            .ok_or_else(|| SettlementError::ChainError("Simulation returned no transaction".to_string()))
+    }
+
+    /// Gas limit for a successful simulation, at most `gas_cap`
+    fn simulated_gas_limit(&self, simulated: &NodeTransaction, gas_cap: u64) -> Result<u64, SettlementError> {
+        let Some(gas_used) = simulated.gas_used() else {
+            return Err(SettlementError::ChainError("Simulation reported no gas usage".to_string()));
+        };
+        gas_limit(gas_used, self.config.gas_margin_percent, gas_cap)
+    }
+
+    /// Dry-run `instruction` and size its gas limit from the simulated usage.
+    ///
+    /// A simulated abort comes back as the matching typed error, so a doomed
+    /// transaction is never paid for.
+    async fn simulate_gas(
+        &self,
+        instruction: &SettlementInstruction,
+        gas_cap: u64,
+        gas_unit_price: u64,
+    ) -> Result<u64, SettlementError> {
+        let payload = self.create_settlement_payload(instruction)?;
+        let simulated = self.simulate(&payload, gas_cap, gas_unit_price).await?;
+
+        if !simulated.success() {
+            let error = self.vm_status_error(instruction, &simulated.vm_status()).await;
+            warn!("Simulation rejected settlement {}: {}", instruction.id, error);
+            return Err(error);
+        }
+
+        let max_gas_amount = self.simulated_gas_limit(&simulated, gas_cap)?;
+        debug!(
+            "Simulated settlement {}: limit {} at price {}",
+            instruction.id, max_gas_amount, gas_unit_price
+        );
+        Ok(max_gas_amount)
+    }
+
+    /// Dry-run a batch and size its gas limit from the simulated usage.
+    ///
+    /// When the batch aborts as a whole, each settlement is simulated on its
+    /// own and those that abort are dropped with their own error, so one bad
+    /// settlement is neither blamed on another nor allowed to sink the rest.
+    ///
+    /// Returns the gas limit, the settlements left to submit and the rejected ones.
+    async fn simulate_batch<'a>(
+        &self,
+        instructions: Vec<&'a SettlementInstruction>,
+        gas_unit_price: u64,
+    ) -> Result<(u64, Vec<&'a SettlementInstruction>, Vec<(&'a SettlementInstruction, SettlementError)>), SettlementError> {
+        let mut pending = instructions;
+        let mut rejected = Vec::new();
+
+        while !pending.is_empty() {
+            // The gas cap scales with the number of settlements in the transaction
+            let gas_cap = self.config.max_gas_amount.saturating_mul(pending.len() as u64);
+            let simulated = self
+                .simulate(&self.create_batch_payload(&pending)?, gas_cap, gas_unit_price)
+                .await?;
+            if simulated.success() {
+                let max_gas_amount = self.simulated_gas_limit(&simulated, gas_cap)?;
+                debug!(
+                    "Simulated batch of {}: limit {} at price {}",
+                    pending.len(), max_gas_amount, gas_unit_price
+                );
+                return Ok((max_gas_amount, pending, rejected));
+            }
+
+            let vm_status = simulated.vm_status();
+            warn!("Simulated batch of {} aborted, checking each settlement: {}", pending.len(), vm_status);
+            let mut accepted = Vec::with_capacity(pending.len());
+            for instruction in &pending {
+                let payload = self.create_settlement_payload(instruction)?;
+                let simulated = self.simulate(&payload, self.config.max_gas_amount, gas_unit_price).await?;
+                if simulated.success() {
+                    accepted.push(*instruction);
+                } else {
+                    let error = self.vm_status_error(instruction, &simulated.vm_status()).await;
+                    warn!("Simulation rejected settlement {}: {}", instruction.id, error);
+                    rejected.push((*instruction, error));
+                }
+            }
+            if accepted.len() == pending.len() {
+                return Err(SettlementError::TransactionFailed(format!(
+                    "Batch aborted although each settlement simulates on its own: {}",
+                    vm_status
+                )));
+            }
+            pending = accepted;
+        }
 
-        self.account.set_sequence_number(account_info.sequence_number);
-        Ok(())
+        Ok((0, pending, rejected))
+    }
+
+    /// How the vault pays out `token_symbol`, per the asset registry
+    fn payout_asset(&self, token_symbol: &str) -> Result<PayoutAsset, SettlementError> {
+        PayoutAsset::resolve(self.config.asset(token_symbol)?)
     }
 
     /// Create settlement transaction payload
@@ -107,58 +765,222 @@ impl AptosChain {
         &self,
         instruction: &SettlementInstruction,
     ) -> Result<TransactionPayload, SettlementError> {
-        let module_id = ModuleId::new(self.contract_address, Identifier::new("settlement").unwrap());
-        
-        let function = Identifier::new("settle").unwrap();
+        let asset = self.payout_asset(&instruction.token_symbol)?;
+        let module_id = MoveModuleId::new(self.contract_address, Identifier::new("settlement").unwrap());
         
         // Parse receiver address
-        let receiver_address = AccountAddress::from_hex_literal(&instruction.receiver.0)
+        let receiver_address = AccountAddress::from_hex(&instruction.receiver.0)
             .map_err(|e| SettlementError::InvalidInstruction(format!("Invalid receiver address: {}", e)))?;
 
-        let args = serialize_values(&vec![
-            MoveValue::Address(self.vault_owner),
-            MoveValue::vector_u8(instruction.source_tx_hash.0.as_bytes().to_vec()),
-            MoveValue::Address(receiver_address),
-            MoveValue::U64(instruction.amount),
-            MoveValue::U64(instruction.nonce),
-            MoveValue::U64(instruction.timestamp.timestamp() as u64),
+        let mut args = vec![bcs(&self.vault_owner)];
+        args.extend(asset.asset_args());
+        args.extend([
+            bcs(instruction.settlement_key().as_bytes()),
+            bcs(&receiver_address),
+            bcs(&instruction.amount),
+            bcs(&instruction.nonce),
+            bcs(&(instruction.timestamp.timestamp() as u64)),
         ]);
 
         Ok(TransactionPayload::EntryFunction(EntryFunction::new(
             module_id,
-            function,
-            vec![], // type arguments
+            asset.settle_function(false),
+            asset.type_args(),
             args,
         )))
     }
 
-    /// Wait for transaction confirmation
-    async fn wait_for_transaction(&self, tx_hash: &str) -> Result<bool, SettlementError> {
-        let timeout_duration = Duration::from_secs(self.config.transaction_timeout_secs);
-        
-        match timeout(timeout_duration, async {
-            loop {
-                match self.client.get_transaction_by_hash(tx_hash).await {
-                    Ok(txn) => {
-                        if txn.success() {
-                            return Ok(true);
-                        } else {
-                            return Err(SettlementError::TransactionFailed(
-                                format!("Transaction failed: {:?}", txn.vm_status())
-                            ));
-                        }
-                    }
-                    Err(_) => {
-                        // Transaction not yet confirmed
-                        tokio::time::sleep(Duration::from_secs(1)).await;
+    /// Create a batch payload covering every instruction, in order.
+    ///
+    /// All instructions must pay out the same asset.
+    fn create_batch_payload(
+        &self,
+        instructions: &[&SettlementInstruction],
+    ) -> Result<TransactionPayload, SettlementError> {
+        let asset = self.payout_asset(&instructions[0].token_symbol)?;
+        let module_id = MoveModuleId::new(self.contract_address, Identifier::new("settlement").unwrap());
+
+        let mut source_tx_hashes = Vec::with_capacity(instructions.len());
+        let mut receivers = Vec::with_capacity(instructions.len());
+        let mut amounts = Vec::with_capacity(instructions.len());
+        let mut nonces = Vec::with_capacity(instructions.len());
+        let mut timestamps = Vec::with_capacity(instructions.len());
+
+        for instruction in instructions {
+            let receiver_address = AccountAddress::from_hex(&instruction.receiver.0)
+                .map_err(|e| SettlementError::InvalidInstruction(format!("Invalid receiver address: {}", e)))?;
+
+            source_tx_hashes.push(instruction.settlement_key().into_bytes());
+            receivers.push(receiver_address);
+            amounts.push(instruction.amount);
+            nonces.push(instruction.nonce);
+            timestamps.push(instruction.timestamp.timestamp() as u64);
+        }
+
+        let mut args = vec![bcs(&self.vault_owner)];
+        args.extend(asset.asset_args());
+        args.extend([
+            bcs(&source_tx_hashes),
+            bcs(&receivers),
+            bcs(&amounts),
+            bcs(&nonces),
+            bcs(&timestamps),
+        ]);
+
+        Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+            module_id,
+            asset.settle_function(true),
+            asset.type_args(),
+            args,
+        )))
+    }
+
+    /// Wait up to `wait` for any of `tx_hashes` to commit, successfully or not.
+    ///
+    /// Returns the hash that committed with its transaction, or `None` if none
+    /// of them committed in time.
+    async fn wait_for_any(
+        &self,
+        tx_hashes: &[String],
+        wait: Duration,
+    ) -> Result<Option<(String, NodeTransaction)>, SettlementError> {
+        let confirmations = tx_hashes.iter().map(|tx_hash| {
+            let tx_hash = tx_hash.clone();
+            self.confirmations
+                .track(&tx_hash)
+                .map_ok(move |txn| (tx_hash, txn))
+        });
+        let outcome = timeout(wait, select_ok(confirmations)).await;
+
+        // Copies that did not commit are followed again on the next wait
+        for tx_hash in tx_hashes {
+            self.confirmations.forget(tx_hash);
+        }
+
+        match outcome {
+            Ok(Ok((committed, _))) => Ok(Some(committed)),
+            Ok(Err(_)) => Err(SettlementError::ChainError(
+                format!("Stopped tracking transactions: {}", tx_hashes.join(", "))
+            )),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Pair each item of a `settle_batch` transaction with the settlement module
+    /// event it produced, keyed by source transaction hash.
+    ///
+    /// The module emits exactly one `SettlementEvent` or `SettlementSkipped` per
+    /// item, in order, so repeated hashes are matched first come first served.
+    fn batch_item_outcomes(
+        contract_address: AccountAddress,
+        events: &[serde_json::Value],
+    ) -> HashMap<String, VecDeque<BatchItemOutcome>> {
+        let mut outcomes: HashMap<String, VecDeque<BatchItemOutcome>> = HashMap::new();
+
+        for event in events {
+            let event_type = event["type"].as_str().unwrap_or_default();
+            let mut parts = event_type.splitn(3, "::");
+            let (Some(address), Some(module), Some(name)) = (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let from_contract = AccountAddress::from_hex(address)
+                .is_ok_and(|address| address == contract_address);
+            if !from_contract || module != SettlementAbort::MODULE_NAME {
+                continue;
+            }
+
+            let outcome = match name {
+                "SettlementEvent" => BatchItemOutcome::Settled,
+                "SettlementSkipped" => {
+                    // u64 fields are rendered as strings in JSON
+                    let reason = &event["data"]["reason"];
+                    let reason = reason
+                        .as_str()
+                        .and_then(|reason| reason.parse().ok())
+                        .or_else(|| reason.as_u64())
+                        .unwrap_or_default();
+                    BatchItemOutcome::Skipped(reason)
+                }
+                _ => continue,
+            };
+
+            if let Some(source_tx_hash) = event["data"]["source_tx_hash"].as_str() {
+                outcomes.entry(source_tx_hash.to_string()).or_default().push_back(outcome);
+            }
+        }
+
+        outcomes
+    }
+
+    /// Turn a failed transaction's `vm_status` into a typed error.
+    ///
+    /// Aborts raised by our settlement module map to specific variants; anything
+    /// else is reported as a plain transaction failure.
+    async fn vm_status_error(
+        &self,
+        instruction: &SettlementInstruction,
+        vm_status: &str,
+    ) -> SettlementError {
+        let Some(abort) = MoveAbort::parse(vm_status) else {
+            return SettlementError::TransactionFailed(vm_status.to_string());
+        };
+
+        let from_contract = AccountAddress::from_hex(&abort.module_address)
+            .is_ok_and(|address| address == self.contract_address);
+        let Some(reason) = SettlementAbort::from_abort(&abort).filter(|_| from_contract) else {
+            return SettlementError::TransactionFailed(vm_status.to_string());
+        };
+
+        self.settlement_abort_error(instruction, reason, abort.code, vm_status).await
+    }
+
+    /// Map a settlement module error code to the error reported for `instruction`
+    async fn settlement_abort_error(
+        &self,
+        instruction: &SettlementInstruction,
+        reason: SettlementAbort,
+        code: u64,
+        message: &str,
+    ) -> SettlementError {
+        match reason {
+            SettlementAbort::AlreadySettled => {
+                SettlementError::AlreadySettled(instruction.settlement_key())
+            }
+            SettlementAbort::InsufficientBalance => {
+                // An unreadable balance is reported as unknown rather than as empty
+                let available = match self.get_vault_balance(&instruction.token_symbol).await {
+                    Ok(available) => Some(available),
+                    Err(e) => {
+                        warn!("Could not read the {} vault balance: {}", instruction.token_symbol, e);
+                        None
                     }
+                };
+                SettlementError::InsufficientBalance {
+                    required: instruction.amount,
+                    available,
+                }
+            }
+            // Nothing of this asset has been deposited yet
+            SettlementAbort::AssetNotRegistered => SettlementError::InsufficientBalance {
+                required: instruction.amount,
+                available: Some(0),
+            },
+            SettlementAbort::UnauthorizedRelayer => {
+                SettlementError::UnauthorizedRelayer(self.account.address().to_short_string())
+            }
+            SettlementAbort::VaultNotInitialized => {
+                SettlementError::VaultNotInitialized(self.vault_owner.to_short_string())
+            }
+            SettlementAbort::InvalidInstruction | SettlementAbort::InvalidAmount => {
+                SettlementError::InvalidInstruction(message.to_string())
+            }
+            SettlementAbort::NotOwner | SettlementAbort::VaultAlreadyExists => {
+                SettlementError::ContractAbort {
+                    code,
+                    message: message.to_string(),
                 }
             }
-        }).await {
-            Ok(result) => result,
-            Err(_) => Err(SettlementError::Timeout(
-                format!("Transaction confirmation timeout: {}", tx_hash)
-            )),
         }
     }
 
@@ -169,193 +991,249 @@ impl AptosChain {
         type_args: Vec<String>,
         args: Vec<String>,
     ) -> Result<Vec<serde_json::Value>, SettlementError> {
-        let payload = aptos_sdk::types::transaction::ViewRequest {
-            function: format!("{}::settlement::{}", self.contract_address, function_name).parse()
-                .map_err(|e| SettlementError::ChainError(format!("Invalid function: {}", e)))?,
-            type_arguments: type_args,
-            arguments: args,
-        };
+        let function = format!("{}::settlement::{}", self.contract_address, function_name);
+        let args = args.into_iter().map(serde_json::Value::String).collect();
 
         self.client
-            .view(&payload, None)
+            .view(&function, type_args, args)
             .await
+            .map(|response| response.into_inner())
             .map_err(|e| SettlementError::ChainError(format!("View function call failed: {}", e)))
     }
+
+    /// Read a per-asset figure from the vault; a failed or unreadable call is an error, not 0
+    async fn reserve_view(&self, token_symbol: &str, view: ReserveView) -> Result<u64, SettlementError> {
+        let (function_name, type_args, args) = self.payout_asset(token_symbol)?.view_call(self.vault_owner, view);
+
+        let result = self.call_view_function(function_name, type_args, args).await?;
+        // u64 results are rendered as strings in JSON
+        let value = result.first();
+        value
+            .and_then(serde_json::Value::as_str)
+            .and_then(|amount| amount.parse::<u64>().ok())
+            .or_else(|| value.and_then(serde_json::Value::as_u64))
+            .ok_or_else(|| {
+                SettlementError::ChainError(format!("Unexpected {} result: {:?}", function_name, result))
+            })
+    }
 }
 
 #[async_trait]
 impl DestinationChain for AptosChain {
-    async fn submit_settlement(
+    async fn submit_settlement_with_gas(
         &self,
         instruction: &SettlementInstruction,
+        gas_overrides: &GasOverrides,
     ) -> Result<SettlementResult, SettlementError> {
         info!("Submitting settlement to Aptos: {:?}", instruction.id);
 
         // Validate instruction
         instruction.validate()?;
 
-        // Check if already processed
-        if self.is_settlement_processed(&instruction.source_tx_hash).await? {
-            warn!("Settlement already processed: {}", instruction.source_tx_hash);
-            return Ok(SettlementResult::failure(
-                instruction.id,
-                "Already processed".to_string(),
-                0,
-            ));
+        // Check if already processed; an unanswered check holds the settlement back
+        let settlement_key = instruction.settlement_key();
+        if self.check_settlement(&settlement_key).await.settled()? {
+            warn!("Settlement already processed: {}", settlement_key);
+            return Err(SettlementError::AlreadySettled(settlement_key));
         }
 
-        // Sync account
-        let mut account = self.account.clone();
-        let account_info = self.client
-            .get_account(account.address())
-            .await
-            .map_err(|e| SettlementError::ChainError(format!("Failed to get account info: {}", e)))?;
+        // Confirmation is followed by the tracker, so other submissions are not held up
+        let committed = async {
+            let gas_unit_price = match gas_overrides.gas_unit_price {
+                Some(gas_unit_price) => gas_unit_price,
+                None => self.estimate_gas_unit_price().await,
+            };
+            let gas_cap = gas_overrides.max_gas_amount.unwrap_or(self.config.max_gas_amount);
+            let max_gas_amount = self.simulate_gas(instruction, gas_cap, gas_unit_price).await?;
 
-        account.set_sequence_number(account_info.sequence_number);
+            self.submit_with_recovery(
+                vec![instruction],
+                |pending| self.create_settlement_payload(pending[0]),
+                max_gas_amount,
+                gas_unit_price,
+            )
+            .await
+        };
+        let (tx_hash, txn, _) = match committed.await {
+            Ok(committed) => committed,
+            Err(e) => {
+                error!("Settlement processing error: {} ({})", e, e.class());
+                return Err(e);
+            }
+        };
+        if !txn.success() {
+            let e = self.vm_status_error(instruction, &txn.vm_status()).await;
+            error!("Settlement processing error: {} ({})", e, e.class());
+            return Err(e);
+        }
 
-        // Create transaction payload
-        let payload = self.create_settlement_payload(instruction)?;
+        info!("Settlement completed successfully: {}", tx_hash);
+        Ok(SettlementResult::success(
+            instruction.id,
+            TransactionHash(tx_hash),
+            txn.gas_used(),
+        ))
+    }
 
-        // Build transaction
-        let chain_id = self.client
-            .get_ledger_information()
-            .await
-            .map_err(|e| SettlementError::ChainError(format!("Failed to get chain info: {}", e)))?
-            .chain_id;
+    async fn submit_settlement_batch(
+        &self,
+        instructions: &[SettlementInstruction],
+    ) -> Result<Vec<Result<SettlementResult, SettlementError>>, SettlementError> {
+        info!("Submitting batch of {} settlements to Aptos", instructions.len());
 
-        let transaction_builder = TransactionBuilder::new(
-            payload,
-            chrono::Utc::now().timestamp() as u64 + 30, // 30 second expiry
-            ChainId::new(chain_id),
-        )
-        .sender(account.address())
-        .sequence_number(account.sequence_number())
-        .max_gas_amount(self.config.max_gas_amount)
-        .gas_unit_price(self.config.gas_unit_price);
-
-        // Sign and submit transaction
-        let signed_txn = account.sign_with_transaction_builder(transaction_builder);
-
-        match self.client.submit(&signed_txn).await {
-            Ok(response) => {
-                let tx_hash = response.hash.to_string();
-                debug!("Transaction submitted: {}", tx_hash);
-
-                // Wait for confirmation
-                match self.wait_for_transaction(&tx_hash).await {
-                    Ok(true) => {
-                        info!("Settlement completed successfully: {}", tx_hash);
-                        
-                        // Get gas used (optional)
-                        let gas_used = self.client
-                            .get_transaction_by_hash(&tx_hash)
-                            .await
-                            .ok()
-                            .and_then(|txn| txn.gas_used().map(|g| g as u64));
-
-                        Ok(SettlementResult::success(
-                            instruction.id,
-                            TransactionHash(tx_hash),
-                            gas_used,
-                        ))
+        // Invalid instructions are reported on their own and left out of the transaction
+        let mut outcomes: Vec<Option<Result<SettlementResult, SettlementError>>> = Vec::with_capacity(instructions.len());
+        let mut assets: Vec<(PayoutAsset, Vec<usize>)> = Vec::new();
+        for (index, instruction) in instructions.iter().enumerate() {
+            match instruction.validate().and_then(|_| self.payout_asset(&instruction.token_symbol)) {
+                Ok(asset) => {
+                    match assets.iter_mut().find(|(existing, _)| *existing == asset) {
+                        Some((_, indices)) => indices.push(index),
+                        None => assets.push((asset, vec![index])),
                     }
-                    Ok(false) => {
-                        error!("Transaction failed: {}", tx_hash);
-                        Ok(SettlementResult::failure(
-                            instruction.id,
-                            "Transaction failed".to_string(),
-                            0,
-                        ))
+                    outcomes.push(None);
+                }
+                Err(e) => outcomes.push(Some(Err(e))),
+            }
+        }
+
+        // A transaction pays out a single asset, so a mixed batch is split per asset
+        if assets.len() > 1 {
+            for (_, indices) in assets {
+                let group: Vec<SettlementInstruction> =
+                    indices.iter().map(|&index| instructions[index].clone()).collect();
+                match self.submit_settlement_batch(&group).await {
+                    Ok(items) => {
+                        for (index, item) in indices.into_iter().zip(items) {
+                            outcomes[index] = Some(item);
+                        }
                     }
                     Err(e) => {
-                        error!("Settlement processing error: {}", e);
-                        Ok(SettlementResult::failure(instruction.id, e.to_string(), 0))
+                        for index in indices {
+                            outcomes[index] = Some(Err(e.clone()));
+                        }
                     }
                 }
             }
-            Err(e) => {
-                error!("Failed to submit transaction: {}", e);
-                Ok(SettlementResult::failure(
-                    instruction.id,
-                    format!("Submission failed: {}", e),
-                    0,
-                ))
-            }
+            return Ok(outcomes.into_iter().flatten().collect());
         }
-    }
 
-    async fn is_settlement_processed(
-        &self,
-        tx_hash: &TransactionHash,
-    ) -> Result<bool, SettlementError> {
-        let args = vec![
-            self.vault_owner.to_hex_literal(),
-            format!("\"{}\"", tx_hash.0),
-        ];
+        let batch: Vec<&SettlementInstruction> = instructions
+            .iter()
+            .zip(&outcomes)
+            .filter(|(_, outcome)| outcome.is_none())
+            .map(|(instruction, _)| instruction)
+            .collect();
 
-        match self.call_view_function("is_settled", vec![], args).await {
-            Ok(result) => {
-                if let Some(value) = result.first() {
-                    Ok(value.as_bool().unwrap_or(false))
-                } else {
-                    Ok(false)
-                }
-            }
-            Err(_) => {
-                // If we can't check, assume not processed for safety
-                Ok(false)
+        if batch.is_empty() {
+            return Ok(outcomes.into_iter().flatten().collect());
+        }
+
+        // Settlements that abort on their own are reported against themselves
+        let gas_unit_price = self.estimate_gas_unit_price().await;
+        let (max_gas_amount, batch, rejected) = self.simulate_batch(batch, gas_unit_price).await?;
+        for (rejected, error) in rejected {
+            if let Some(index) = instructions.iter().position(|instruction| std::ptr::eq(instruction, rejected)) {
+                outcomes[index] = Some(Err(error));
             }
         }
-    }
+        if batch.is_empty() {
+            return Ok(outcomes.into_iter().flatten().collect());
+        }
 
-    async fn get_vault_balance(&self) -> Result<u64, SettlementError> {
-        let args = vec![self.vault_owner.to_hex_literal()];
+        let (tx_hash, txn, included) = self
+            .submit_with_recovery(
+                batch,
+                |pending| self.create_batch_payload(pending),
+                max_gas_amount,
+                gas_unit_price,
+            )
+            .await?;
 
-        match self.call_view_function("get_vault_balance", vec![], args).await {
-            Ok(result) => {
-                if let Some(value) = result.first() {
-                    if let Some(balance_str) = value.as_str() {
-                        balance_str.parse::<u64>()
-                            .map_err(|e| SettlementError::ChainError(format!("Invalid balance format: {}", e)))
-                    } else {
-                        Ok(value.as_u64().unwrap_or(0))
-                    }
-                } else {
-                    Ok(0)
-                }
+        if !txn.success() {
+            // Only checks that apply to the whole batch abort it
+            let error = self.vm_status_error(included[0], &txn.vm_status()).await;
+            error!("Batch settlement {} failed: {} ({})", tx_hash, error, error.class());
+            return Err(error);
+        }
+
+        let mut item_outcomes = Self::batch_item_outcomes(self.contract_address, txn.events());
+        let settled = item_outcomes
+            .values()
+            .flatten()
+            .filter(|outcome| **outcome == BatchItemOutcome::Settled)
+            .count() as u64;
+        let gas_used = txn.gas_used().map(|g| g / settled.max(1));
+
+        let included_ids: HashSet<Uuid> = included.iter().map(|instruction| instruction.id).collect();
+        for (instruction, outcome) in instructions.iter().zip(outcomes.iter_mut()) {
+            if outcome.is_some() {
+                continue;
             }
-            Err(e) => {
-                warn!("Failed to get vault balance: {}", e);
-                Ok(0)
+            // Left out of a replacement because it had already landed
+            if !included_ids.contains(&instruction.id) {
+                *outcome = Some(Err(SettlementError::AlreadySettled(instruction.settlement_key())));
+                continue;
             }
+
+            let item = item_outcomes
+                .get_mut(&instruction.settlement_key())
+                .and_then(VecDeque::pop_front);
+            *outcome = Some(match item {
+                Some(BatchItemOutcome::Settled) => Ok(SettlementResult::success(
+                    instruction.id,
+                    TransactionHash(tx_hash.clone()),
+                    gas_used,
+                )),
+                Some(BatchItemOutcome::Skipped(code)) => {
+                    let message = format!("Skipped in batch {} (reason {})", tx_hash, code);
+                    Err(match SettlementAbort::from_reason(code) {
+                        Some(reason) => {
+                            self.settlement_abort_error(instruction, reason, code, &message).await
+                        }
+                        None => SettlementError::ContractAbort { code, message },
+                    })
+                }
+                None => Err(SettlementError::TransactionFailed(format!(
+                    "No settlement event for {} in batch {}",
+                    instruction.settlement_key(), tx_hash
+                ))),
+            });
         }
+
+        info!("Batch settlement completed: {} ({} of {} settled)", tx_hash, settled, included.len());
+        Ok(outcomes.into_iter().flatten().collect())
     }
 
-    async fn get_total_settled(&self) -> Result<u64, SettlementError> {
-        let args = vec![self.vault_owner.to_hex_literal()];
+    async fn check_settlement(&self, settlement_key: &str) -> SettlementCheck {
+        let args = vec![self.vault_owner.to_short_string(), settlement_key.to_string()];
 
-        match self.call_view_function("get_total_settled", vec![], args).await {
-            Ok(result) => {
-                if let Some(value) = result.first() {
-                    if let Some(total_str) = value.as_str() {
-                        total_str.parse::<u64>()
-                            .map_err(|e| SettlementError::ChainError(format!("Invalid total format: {}", e)))
-                    } else {
-                        Ok(value.as_u64().unwrap_or(0))
-                    }
-                } else {
-                    Ok(0)
-                }
-            }
+        match self.call_view_function("is_settled", vec![], args).await {
+            Ok(result) => match result.first().and_then(serde_json::Value::as_bool) {
+                Some(true) => SettlementCheck::Settled,
+                Some(false) => SettlementCheck::NotSettled,
+                None => SettlementCheck::Unknown(format!(
+                    "Unexpected is_settled result for {}: {:?}",
+                    settlement_key, result
+                )),
+            },
             Err(e) => {
-                warn!("Failed to get total settled: {}", e);
-                Ok(0)
+                warn!("Could not check whether {} is settled: {}", settlement_key, e);
+                SettlementCheck::Unknown(e.to_string())
             }
         }
     }
 
+    async fn get_vault_balance(&self, token_symbol: &str) -> Result<u64, SettlementError> {
+        self.reserve_view(token_symbol, ReserveView::Balance).await
+    }
+
+    async fn get_total_settled(&self, token_symbol: &str) -> Result<u64, SettlementError> {
+        self.reserve_view(token_symbol, ReserveView::TotalSettled).await
+    }
+
     async fn check_health(&self) -> Result<bool, SettlementError> {
-        match self.client.get_ledger_information().await {
+        match self.client.get_ledger_info().await {
             Ok(_) => Ok(true),
             Err(e) => {
                 error!("Aptos health check failed: {}", e);
@@ -365,12 +1243,85 @@ impl DestinationChain for AptosChain {
     }
 }
 
+/// Destination chain guarded by a circuit breaker.
+///
+/// Submissions are refused with [`SettlementError::CircuitOpen`] while the
+/// breaker is open; read-only calls always go through.
+pub struct CircuitBreakerChain {
+    inner: Arc<dyn DestinationChain>,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl CircuitBreakerChain {
+    pub fn new(inner: Arc<dyn DestinationChain>, breaker: Arc<CircuitBreaker>) -> Self {
+        Self { inner, breaker }
+    }
+}
+
+#[async_trait]
+impl DestinationChain for CircuitBreakerChain {
+    async fn submit_settlement_with_gas(
+        &self,
+        instruction: &SettlementInstruction,
+        gas_overrides: &GasOverrides,
+    ) -> Result<SettlementResult, SettlementError> {
+        let attempt = self.breaker.attempt()?;
+
+        let outcome = self.inner.submit_settlement_with_gas(instruction, gas_overrides).await;
+        match &outcome {
+            // A failure reported as a result still means the submission did not land
+            Ok(result) if result.status != SettlementStatus::Completed => attempt.record_failure(),
+            _ => attempt.record(&outcome),
+        }
+        outcome
+    }
+
+    async fn submit_settlement_batch(
+        &self,
+        instructions: &[SettlementInstruction],
+    ) -> Result<Vec<Result<SettlementResult, SettlementError>>, SettlementError> {
+        let attempt = self.breaker.attempt()?;
+
+        let outcome = self.inner.submit_settlement_batch(instructions).await;
+        match &outcome {
+            // One transaction landed, so Aptos is reachable even if some items were skipped
+            Ok(items) if items.iter().any(|item| {
+                item.as_ref().is_ok_and(|result| result.status == SettlementStatus::Completed)
+            }) => attempt.record_success(),
+            Ok(items) if items.iter().any(|item| {
+                item.as_ref().map_or_else(SettlementError::is_chain_failure, |result| {
+                    result.status != SettlementStatus::Completed
+                })
+            }) => attempt.record_failure(),
+            Ok(_) => attempt.record_success(),
+            Err(_) => attempt.record(&outcome),
+        }
+        outcome
+    }
+
+    async fn check_settlement(&self, settlement_key: &str) -> SettlementCheck {
+        self.inner.check_settlement(settlement_key).await
+    }
+
+    async fn get_vault_balance(&self, token_symbol: &str) -> Result<u64, SettlementError> {
+        self.inner.get_vault_balance(token_symbol).await
+    }
+
+    async fn get_total_settled(&self, token_symbol: &str) -> Result<u64, SettlementError> {
+        self.inner.get_total_settled(token_symbol).await
+    }
+
+    async fn check_health(&self) -> Result<bool, SettlementError> {
+        self.inner.check_health().await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{AptosConfig, ChainId, SettlementInstruction, TransactionHash};
+    use crate::retry::{CircuitBreakerConfig, CircuitState};
+    use crate::types::{Address, AptosConfig, ChainId, SettlementInstruction, TransactionHash};
     use chrono::Utc;
-    use uuid::Uuid;
 
     fn create_test_config() -> AptosConfig {
         AptosConfig {
@@ -380,7 +1331,17 @@ mod tests {
             private_key: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
             max_gas_amount: 200000,
             gas_unit_price: 100,
+            gas_margin_percent: 20,
+            min_gas_unit_price: 100,
+            max_gas_unit_price: 10000,
             transaction_timeout_secs: 30,
+            transaction_expiry_secs: 60,
+            max_resubmissions: 2,
+            gas_price_bump_percent: 20,
+            account_address: None,
+            key_file: None,
+            key_profile: None,
+            assets: [("USDC".to_string(), AptosAsset::VaultCoin)].into(),
         }
     }
 
@@ -411,9 +1372,278 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_settlement_payload_creation() {
+        let chain = AptosChain::new(create_test_config()).await.unwrap();
+        let instruction = create_test_instruction();
+
+        let TransactionPayload::EntryFunction(entry) = chain.create_settlement_payload(&instruction).unwrap()
+        else {
+            panic!("expected an entry function payload");
+        };
+        assert_eq!(entry.function, "settle");
+        assert!(entry.type_args.is_empty());
+        assert_eq!(entry.args.len(), 6);
+        assert_eq!(entry.args[0], bcs(&AccountAddress::ONE));
+        assert_eq!(entry.args[1], bcs(instruction.source_tx_hash.0.as_bytes()));
+        assert_eq!(entry.args[3], bcs(&instruction.amount));
+
+        let TransactionPayload::EntryFunction(entry) = chain.create_batch_payload(&[&instruction, &instruction]).unwrap()
+        else {
+            panic!("expected an entry function payload");
+        };
+        assert_eq!(entry.function, "settle_batch");
+        assert_eq!(entry.args[3], bcs(&vec![instruction.amount; 2]));
+    }
+
+    #[tokio::test]
+    async fn test_check_settlement_view_arguments() {
+        use axum::{routing::post, Json, Router};
+        use std::sync::Mutex;
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let rpc_url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let app = Router::new().route("/v1/view", post({
+            let requests = Arc::clone(&requests);
+            move |Json(request): Json<serde_json::Value>| async move {
+                requests.lock().unwrap().push(request);
+                Json(serde_json::json!([true]))
+            }
+        }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let chain = AptosChain::new(AptosConfig { rpc_url, ..create_test_config() }).await.unwrap();
+        let key = create_test_instruction().settlement_key();
+        assert_eq!(chain.check_settlement(&key).await, SettlementCheck::Settled);
+
+        // The key is sent as a plain JSON string, exactly as the vault stores it
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0]["function"], "0x1::settlement::is_settled");
+        assert_eq!(requests[0]["arguments"], serde_json::json!(["0x1", key]));
+    }
+
+    #[tokio::test]
+    async fn test_only_refused_submissions_resync_the_sequence_number() {
+        use axum::{http::StatusCode, routing::{get, post}, Json, Router};
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+        let account_fetches = Arc::new(AtomicUsize::new(0));
+        let refuse = Arc::new(AtomicBool::new(false));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let rpc_url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let app = Router::new()
+            .route("/v1", get(|| async {
+                Json(serde_json::json!({
+                    "chain_id": 4,
+                    "epoch": "1",
+                    "ledger_version": "1",
+                    "oldest_ledger_version": "0",
+                    "ledger_timestamp": "1",
+                    "node_role": "full_node",
+                    "oldest_block_height": "0",
+                    "block_height": "1",
+                }))
+            }))
+            .route("/v1/accounts/:address", get({
+                let account_fetches = Arc::clone(&account_fetches);
+                move || async move {
+                    account_fetches.fetch_add(1, Ordering::SeqCst);
+                    Json(serde_json::json!({ "sequence_number": "5", "authentication_key": "0x1" }))
+                }
+            }))
+            .route("/v1/transactions", post({
+                let refuse = Arc::clone(&refuse);
+                move || async move {
+                    if refuse.load(Ordering::SeqCst) {
+                        let error = serde_json::json!({
+                            "message": "Invalid transaction: Type: Validation Code: INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE",
+                            "error_code": "vm_error",
+                        });
+                        (StatusCode::BAD_REQUEST, Json(error))
+                    } else {
+                        (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({ "message": "unavailable" })))
+                    }
+                }
+            }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let chain = AptosChain::new(AptosConfig { rpc_url, ..create_test_config() }).await.unwrap();
+        let payload = || chain.create_settlement_payload(&create_test_instruction()).unwrap();
+
+        // The node may have taken number 5 before failing, so the count moves on past it
+        assert!(chain.submit_payload(payload(), 1000, 100, None).await.is_err());
+        assert_eq!(chain.sequence_numbers.allocate().await.unwrap(), 6);
+        assert_eq!(account_fetches.load(Ordering::SeqCst), 1);
+
+        // A refused submission did not use its number, so the count resyncs with the chain
+        refuse.store(true, Ordering::SeqCst);
+        assert!(chain.submit_payload(payload(), 1000, 100, None).await.is_err());
+        assert_eq!(chain.sequence_numbers.allocate().await.unwrap(), 5);
+        assert_eq!(account_fetches.load(Ordering::SeqCst), 2);
+    }
+
+    /// Destination whose first submission never finishes
+    struct HangingChain {
+        submissions: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl DestinationChain for HangingChain {
+        async fn submit_settlement_with_gas(
+            &self,
+            instruction: &SettlementInstruction,
+            _gas_overrides: &GasOverrides,
+        ) -> Result<SettlementResult, SettlementError> {
+            if self.submissions.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                std::future::pending::<()>().await;
+            }
+            Ok(SettlementResult::success(instruction.id, TransactionHash("0xabc".to_string()), None))
+        }
+
+        async fn check_settlement(&self, _settlement_key: &str) -> SettlementCheck {
+            SettlementCheck::NotSettled
+        }
+
+        async fn get_vault_balance(&self, _token_symbol: &str) -> Result<u64, SettlementError> {
+            Ok(0)
+        }
+
+        async fn get_total_settled(&self, _token_symbol: &str) -> Result<u64, SettlementError> {
+            Ok(0)
+        }
+
+        async fn check_health(&self) -> Result<bool, SettlementError> {
+            Ok(true)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_probe_reopens_the_circuit() {
+        let breaker = Arc::new(CircuitBreaker::new(
+            "aptos",
+            CircuitBreakerConfig {
+                failure_threshold: 1,
+                reset_timeout_ms: 20,
+            },
+        ));
+        let inner = Arc::new(HangingChain { submissions: Default::default() });
+        let chain = CircuitBreakerChain::new(inner, Arc::clone(&breaker));
+        let instruction = create_test_instruction();
+
+        breaker.record_failure();
+        sleep(Duration::from_millis(25)).await;
+
+        // The probe hangs and the caller gives up on it
+        let probe = timeout(Duration::from_millis(50), chain.submit_settlement(&instruction)).await;
+        assert!(probe.is_err());
+        assert_eq!(breaker.status().state, CircuitState::Open);
+
+        // A later probe is let through and closes the circuit
+        sleep(Duration::from_millis(25)).await;
+        assert!(chain.submit_settlement(&instruction).await.is_ok());
+        assert_eq!(breaker.status().state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_sequence_number_errors() {
+        assert!(is_sequence_number_error(
+            "API error: Invalid transaction: Type: Validation Code: SEQUENCE_NUMBER_TOO_OLD"
+        ));
+        assert!(is_sequence_number_error("Code: SEQUENCE_NUMBER_TOO_NEW"));
+        assert!(!is_sequence_number_error("Code: INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE"));
+    }
+
+    #[test]
+    fn test_bumped_gas_price() {
+        assert_eq!(bumped_gas_price(100, 20), 120);
+        // Replacements must always pay strictly more
+        assert_eq!(bumped_gas_price(1, 20), 2);
+        assert_eq!(bumped_gas_price(u64::MAX, 20), u64::MAX);
+    }
+
     #[test]
-    fn test_settlement_payload_creation() {
-        // This test would require a properly initialized AptosChain
-        // Skipping for now as it requires network access
+    fn test_gas_limit_from_simulation() {
+        assert_eq!(gas_limit(1_000, 20, 200_000).unwrap(), 1_200);
+        // Margin is trimmed to the cap
+        assert_eq!(gas_limit(190_000, 20, 200_000).unwrap(), 200_000);
+        assert!(gas_limit(250_000, 20, 200_000).is_err());
+    }
+
+    #[test]
+    fn test_payout_asset_selection() {
+        let owner = AccountAddress::from_hex("0xcafe").unwrap();
+
+        let vault_coin = PayoutAsset::resolve(&AptosAsset::VaultCoin).unwrap();
+        assert_eq!(vault_coin.settle_function(false), "settle");
+        assert_eq!(vault_coin.settle_function(true), "settle_batch");
+        assert!(vault_coin.type_args().is_empty());
+
+        let coin = PayoutAsset::resolve(&AptosAsset::Coin {
+            coin_type: "0x1::aptos_coin::AptosCoin".to_string(),
+        })
+        .unwrap();
+        assert_eq!(coin.settle_function(true), "settle_batch_coin");
+        assert_eq!(coin.type_args().len(), 1);
+        assert!(coin.asset_args().is_empty());
+        let (function, type_args, _) = coin.view_call(owner, ReserveView::TotalSettled);
+        assert_eq!(function, "get_coin_total_settled");
+        assert!(type_args[0].ends_with("::aptos_coin::AptosCoin"));
+
+        let fa = PayoutAsset::resolve(&AptosAsset::FungibleAsset {
+            metadata_address: "0xa".to_string(),
+        })
+        .unwrap();
+        assert_eq!(fa.settle_function(false), "settle_fa");
+        assert!(fa.type_args().is_empty());
+        assert_eq!(fa.asset_args(), vec![bcs(&AccountAddress::from_hex("0xa").unwrap())]);
+        let (function, _, args) = fa.view_call(owner, ReserveView::Balance);
+        assert_eq!(function, "get_fa_balance");
+        assert_eq!(args, vec!["0xcafe".to_string(), "0xa".to_string()]);
+
+        assert!(PayoutAsset::resolve(&AptosAsset::Coin { coin_type: "not a type".to_string() }).is_err());
+        assert!(PayoutAsset::resolve(&AptosAsset::FungibleAsset { metadata_address: "xyz".to_string() }).is_err());
+    }
+
+    fn module_event(event_type: &str, data: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "guid": { "creation_number": "0", "account_address": "0x0" },
+            "sequence_number": "0",
+            "type": event_type,
+            "data": data,
+        })
+    }
+
+    #[test]
+    fn test_batch_item_outcomes() {
+        let events = vec![
+            module_event("0x1::coin::DepositEvent", serde_json::json!({ "amount": "5" })),
+            module_event(
+                "0x1::settlement::SettlementEvent",
+                serde_json::json!({ "source_tx_hash": "tx_a", "amount": "5" }),
+            ),
+            module_event(
+                "0x1::settlement::SettlementSkipped",
+                serde_json::json!({ "source_tx_hash": "tx_b", "reason": "2" }),
+            ),
+            module_event(
+                "0x1::settlement::SettlementSkipped",
+                serde_json::json!({ "source_tx_hash": "tx_a", "reason": "4" }),
+            ),
+            // Same module name at another address is not ours
+            module_event(
+                "0x2::settlement::SettlementEvent",
+                serde_json::json!({ "source_tx_hash": "tx_c" }),
+            ),
+        ];
+
+        let mut outcomes = AptosChain::batch_item_outcomes(AccountAddress::ONE, &events);
+
+        let tx_a = outcomes.get_mut("tx_a").unwrap();
+        assert_eq!(tx_a.pop_front(), Some(BatchItemOutcome::Settled));
+        assert_eq!(tx_a.pop_front(), Some(BatchItemOutcome::Skipped(4)));
+        assert_eq!(outcomes["tx_b"], VecDeque::from([BatchItemOutcome::Skipped(2)]));
+        assert!(!outcomes.contains_key("tx_c"));
     }
 }
\ No newline at end of file
@@ -0,0 +1,39 @@
+//! Source and destination chain clients
+
+pub mod aptos;
+pub mod rate_limit;
+pub mod rpc_pool;
+pub mod solana;
+
+pub use aptos::{AptosChain, DestinationChain};
+pub use solana::{SolanaChain, SourceAck, SourceChain, SourceEvent, SourceEventStream, SourceFetchStats};
+
+#[cfg(test)]
+pub(crate) mod mock_rpc {
+    use serde_json::json;
+
+    /// Serve JSON-RPC over HTTP on a local port, answering each request with
+    /// `respond(method, params)`; an `Err` is sent back as that HTTP status
+    pub async fn serve_rpc<F>(respond: F) -> String
+    where
+        F: Fn(&str, &serde_json::Value) -> Result<serde_json::Value, axum::http::StatusCode>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+    {
+        use axum::{response::IntoResponse, routing::post, Json, Router};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let app = Router::new().route("/", post(move |Json(request): Json<serde_json::Value>| async move {
+            let method = request["method"].as_str().unwrap_or_default();
+            match respond(method, &request["params"]) {
+                Ok(result) => Json(json!({ "jsonrpc": "2.0", "result": result, "id": request["id"] })).into_response(),
+                Err(status) => status.into_response(),
+            }
+        }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+}
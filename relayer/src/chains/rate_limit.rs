@@ -0,0 +1,272 @@
+//! Per-endpoint rate limiting for Solana JSON-RPC
+//!
+//! Every request to an endpoint first takes a token from that endpoint's
+//! bucket, so concurrent fetches never send more than the configured rate.
+//! When the endpoint still answers HTTP 429 the whole bucket pauses for the
+//! `Retry-After` it asked for, or an exponential backoff without one, and the
+//! request is sent again; only once the retries run out does the error reach
+//! the pool, which then fails over.
+
+use async_trait::async_trait;
+use reqwest::{
+    header::{CONTENT_TYPE, RETRY_AFTER},
+    StatusCode,
+};
+use serde_json::Value;
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_request::{RpcError, RpcRequest, RpcResponseErrorData},
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+use tracing::debug;
+
+use crate::{retry::RetryPolicy, sync::lock_unpoisoned};
+
+/// How often a rate limited request is sent before giving up, and the
+/// backoff between attempts when the endpoint sends no `Retry-After`
+const THROTTLE_RETRY: RetryPolicy = RetryPolicy {
+    max_attempts: 5,
+    base_delay_ms: 500,
+    max_delay_ms: 30_000,
+    jitter: 0.2,
+};
+
+/// Token bucket holding up to one second of requests
+pub struct TokenBucket {
+    /// Tokens added per second; `None` only honors throttling pauses
+    rate: Option<f64>,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    refilled_at: Instant,
+    /// Set when the endpoint rate limited a request
+    paused_until: Option<Instant>,
+}
+
+impl TokenBucket {
+    /// Bucket allowing `requests_per_second`, or any rate when it is 0
+    pub fn new(requests_per_second: u32) -> Self {
+        let rate = (requests_per_second > 0).then_some(requests_per_second as f64);
+        Self {
+            rate,
+            state: Mutex::new(BucketState {
+                tokens: rate.unwrap_or_default(),
+                refilled_at: Instant::now(),
+                paused_until: None,
+            }),
+        }
+    }
+
+    /// Wait for a token, returning how long that took
+    pub async fn acquire(&self) -> Duration {
+        let started = Instant::now();
+        while let Some(wait) = self.try_take(Instant::now()) {
+            sleep(wait).await;
+        }
+        started.elapsed()
+    }
+
+    /// Hold every request back for `delay`, then refill from empty
+    pub fn pause(&self, delay: Duration) {
+        let mut state = lock_unpoisoned(&self.state);
+        let until = Instant::now() + delay;
+        let until = state.paused_until.map_or(until, |paused| paused.max(until));
+        state.paused_until = Some(until);
+        state.tokens = 0.0;
+        state.refilled_at = until;
+    }
+
+    /// Take a token, or say how long until one may be available
+    fn try_take(&self, now: Instant) -> Option<Duration> {
+        let mut state = lock_unpoisoned(&self.state);
+        if let Some(until) = state.paused_until {
+            if until > now {
+                return Some(until - now);
+            }
+            state.paused_until = None;
+        }
+
+        let rate = self.rate?;
+        let refill = now.saturating_duration_since(state.refilled_at).as_secs_f64() * rate;
+        state.tokens = (state.tokens + refill).min(rate);
+        state.refilled_at = state.refilled_at.max(now);
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - state.tokens) / rate))
+        }
+    }
+}
+
+/// Request and throttling counters of one endpoint
+#[derive(Debug, Default)]
+pub struct ThrottleStats {
+    requests: AtomicU64,
+    throttled: AtomicU64,
+    waited_us: AtomicU64,
+    elapsed_us: AtomicU64,
+}
+
+impl ThrottleStats {
+    /// Requests sent, including retries
+    pub fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// Requests the endpoint answered with HTTP 429
+    pub fn throttled(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+
+    /// Time requests spent waiting on the rate limit or a throttling pause
+    pub fn waited(&self) -> Duration {
+        Duration::from_micros(self.waited_us.load(Ordering::Relaxed))
+    }
+
+    fn add(counter: &AtomicU64, duration: Duration) {
+        counter.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+/// JSON-RPC over HTTP that takes a token from `bucket` before every request
+/// and retries requests the endpoint rate limits
+pub struct ThrottledSender {
+    client: reqwest::Client,
+    url: String,
+    request_id: AtomicU64,
+    bucket: TokenBucket,
+    stats: Arc<ThrottleStats>,
+}
+
+impl ThrottledSender {
+    pub fn new(
+        url: String,
+        timeout: Duration,
+        bucket: TokenBucket,
+        stats: Arc<ThrottleStats>,
+    ) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .pool_idle_timeout(timeout)
+            .build()
+            .expect("reqwest client with a timeout always builds");
+        Self {
+            client,
+            url,
+            request_id: AtomicU64::new(0),
+            bucket,
+            stats,
+        }
+    }
+
+    async fn send_throttled(&self, body: String) -> ClientResult<Value> {
+        let mut throttled = 0;
+        loop {
+            ThrottleStats::add(&self.stats.waited_us, self.bucket.acquire().await);
+            self.stats.requests.fetch_add(1, Ordering::Relaxed);
+
+            let response = self
+                .client
+                .post(&self.url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(body.clone())
+                .send()
+                .await?;
+
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                throttled += 1;
+                self.stats.throttled.fetch_add(1, Ordering::Relaxed);
+                if !THROTTLE_RETRY.allows_retry(throttled) {
+                    return Err(response.error_for_status().unwrap_err().into());
+                }
+                let delay = retry_after(&response).unwrap_or_else(|| THROTTLE_RETRY.delay(throttled));
+                debug!("{} rate limited a request, pausing it for {:?}", self.url, delay);
+                self.bucket.pause(delay);
+                continue;
+            }
+
+            let mut json: Value = response.error_for_status()?.json().await?;
+            if json["error"].is_object() {
+                return Err(rpc_error(&json["error"]).into());
+            }
+            return Ok(json["result"].take());
+        }
+    }
+}
+
+#[async_trait]
+impl RpcSender for ThrottledSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let started = Instant::now();
+        let id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        let result = self.send_throttled(request.build_request_json(id, params).to_string()).await;
+        ThrottleStats::add(&self.stats.elapsed_us, started.elapsed());
+        result
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats {
+            request_count: self.stats.requests() as usize,
+            elapsed_time: Duration::from_micros(self.stats.elapsed_us.load(Ordering::Relaxed)),
+            rate_limited_time: self.stats.waited(),
+        }
+    }
+
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+}
+
+/// Delay a 429 response asks for, capped at the longest throttling backoff
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let seconds = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds).min(Duration::from_millis(THROTTLE_RETRY.max_delay_ms)))
+}
+
+/// Client error for a JSON-RPC error object
+fn rpc_error(error: &Value) -> RpcError {
+    match (error["code"].as_i64(), error["message"].as_str()) {
+        (Some(code), Some(message)) => RpcError::RpcResponseError {
+            code,
+            message: message.to_string(),
+            data: RpcResponseErrorData::Empty,
+        },
+        _ => RpcError::RpcRequestError(format!("Malformed RPC error response: {}", error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bucket_limits_the_rate_and_pauses() {
+        let bucket = TokenBucket::new(20);
+
+        // A full second's burst goes through at once, the rest at the rate
+        let started = Instant::now();
+        for _ in 0..30 {
+            bucket.acquire().await;
+        }
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(400), "took {:?}", elapsed);
+        assert!(elapsed < Duration::from_secs(2), "took {:?}", elapsed);
+
+        // A pause holds back even an unlimited bucket
+        let bucket = TokenBucket::new(0);
+        assert_eq!(bucket.try_take(Instant::now()), None);
+        bucket.pause(Duration::from_millis(200));
+        assert!(bucket.acquire().await >= Duration::from_millis(150));
+    }
+}
@@ -0,0 +1,428 @@
+//! Solana JSON-RPC endpoint pool
+//!
+//! Calls go to the healthiest endpoint first and fail over to the next one
+//! when it errors or stalls. Endpoints are ordered by recent failures, by how
+//! far their slot lags the others and then by latency. In quorum mode
+//! [`RpcPool::quorum`] asks every endpoint and only trusts an answer enough of
+//! them agree on. Each endpoint is rate limited on its own; see
+//! [`rate_limit`](super::rate_limit).
+
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use solana_client::{
+    client_error::ClientError, nonblocking::rpc_client::RpcClient, rpc_client::RpcClientConfig,
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+use super::rate_limit::{ThrottleStats, ThrottledSender, TokenBucket};
+use crate::{sync::lock_unpoisoned, types::SettlementError};
+
+/// Slots an endpoint may trail the most advanced one before it counts as stale
+const MAX_SLOT_LAG: u64 = 150;
+
+/// Weight of the newest sample in an endpoint's latency average
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+/// Longest an endpoint is passed over after repeated failures
+const MAX_FAILURE_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Point-in-time view of one endpoint for health checks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointStatus {
+    pub url: String,
+    /// Smoothed latency of successful calls
+    pub latency_ms: Option<f64>,
+    pub consecutive_failures: u32,
+    /// Latest slot the endpoint reported
+    pub slot: Option<u64>,
+    /// Passed over until its failure backoff ends
+    pub backing_off: bool,
+    /// Trailing the most advanced endpoint by more than the allowed lag
+    pub stale: bool,
+    /// Requests sent since startup, including retries
+    pub requests: u64,
+    /// Requests the endpoint answered with HTTP 429
+    pub throttled: u64,
+    /// Time requests spent held back by the rate limit or a 429
+    pub throttle_wait_ms: u64,
+}
+
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    latency_ms: Option<f64>,
+    consecutive_failures: u32,
+    last_failure: Option<Instant>,
+    slot: Option<u64>,
+}
+
+impl EndpointHealth {
+    fn backing_off(&self) -> bool {
+        match self.last_failure {
+            Some(at) if self.consecutive_failures > 0 => {
+                let backoff = Duration::from_secs(1)
+                    .saturating_mul(1 << self.consecutive_failures.min(6))
+                    .min(MAX_FAILURE_BACKOFF);
+                at.elapsed() < backoff
+            }
+            _ => false,
+        }
+    }
+}
+
+struct Endpoint {
+    url: String,
+    client: Arc<RpcClient>,
+    health: Mutex<EndpointHealth>,
+    throttle: Arc<ThrottleStats>,
+}
+
+impl Endpoint {
+    fn record<T>(&self, outcome: &Result<T, ClientError>, elapsed: Duration) {
+        let mut health = lock_unpoisoned(&self.health);
+        match outcome {
+            Ok(_) => {
+                let sample = elapsed.as_secs_f64() * 1000.0;
+                health.latency_ms = Some(match health.latency_ms {
+                    Some(average) => average + LATENCY_SMOOTHING * (sample - average),
+                    None => sample,
+                });
+                health.consecutive_failures = 0;
+            }
+            Err(_) => {
+                health.consecutive_failures += 1;
+                health.last_failure = Some(Instant::now());
+            }
+        }
+    }
+}
+
+/// Solana RPC endpoints with health scoring and failover
+pub struct RpcPool {
+    endpoints: Vec<Endpoint>,
+    quorum: Option<usize>,
+}
+
+impl RpcPool {
+    /// Pool over `urls`, the first of which is preferred until scores say otherwise.
+    ///
+    /// Each call gives up on an endpoint after `timeout`, and no endpoint is
+    /// sent more than `requests_per_second`, or any number when it is 0. With
+    /// `quorum` set, [`quorum`](Self::quorum) needs that many endpoints to agree.
+    pub fn new(
+        urls: &[String],
+        commitment: CommitmentConfig,
+        timeout: Duration,
+        quorum: Option<usize>,
+        requests_per_second: u32,
+    ) -> Result<Self, SettlementError> {
+        if urls.is_empty() {
+            return Err(SettlementError::ConfigError("No Solana RPC endpoints configured".to_string()));
+        }
+        if let Some(quorum) = quorum {
+            if quorum == 0 || quorum > urls.len() {
+                return Err(SettlementError::ConfigError(format!(
+                    "Solana RPC quorum must be between 1 and the {} configured endpoints, got {}",
+                    urls.len(),
+                    quorum
+                )));
+            }
+        }
+
+        let endpoints = urls
+            .iter()
+            .map(|url| {
+                let throttle = Arc::new(ThrottleStats::default());
+                let sender = ThrottledSender::new(
+                    url.clone(),
+                    timeout,
+                    TokenBucket::new(requests_per_second),
+                    Arc::clone(&throttle),
+                );
+                Endpoint {
+                    url: url.clone(),
+                    client: Arc::new(RpcClient::new_sender(
+                        sender,
+                        RpcClientConfig::with_commitment(commitment),
+                    )),
+                    health: Mutex::new(EndpointHealth::default()),
+                    throttle,
+                }
+            })
+            .collect();
+        Ok(Self { endpoints, quorum })
+    }
+
+    /// Endpoints that must agree in [`quorum`](Self::quorum), if quorum mode is on
+    pub fn quorum_size(&self) -> Option<usize> {
+        self.quorum
+    }
+
+    /// URL of the endpoint a call would go to first
+    pub fn primary_url(&self) -> &str {
+        &self.endpoints[self.ranked()[0]].url
+    }
+
+    /// Run `op` against the best endpoint, failing over through the rest in
+    /// order until one succeeds. Returns the last error if none does.
+    pub async fn call<T, F, Fut>(&self, op: F) -> Result<T, ClientError>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let mut last_error = None;
+        for index in self.ranked() {
+            let endpoint = &self.endpoints[index];
+            let started = Instant::now();
+            let outcome = op(Arc::clone(&endpoint.client)).await;
+            endpoint.record(&outcome, started.elapsed());
+
+            match outcome {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    warn!("Solana RPC call to {} failed, failing over: {}", endpoint.url, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.expect("the pool has at least one endpoint"))
+    }
+
+    /// Run `op` against every endpoint at once and return the answer at
+    /// least `quorum` of them agree on, or `None` if no answer has enough
+    /// support. Without quorum mode the first endpoint to answer decides.
+    pub async fn quorum<T, F, Fut>(&self, op: F) -> Result<Option<T>, SettlementError>
+    where
+        T: PartialEq,
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let Some(quorum) = self.quorum else {
+            return self
+                .call(op)
+                .await
+                .map(Some)
+                .map_err(|e| SettlementError::ChainError(e.to_string()));
+        };
+
+        let answers = join_all(self.endpoints.iter().map(|endpoint| {
+            let call = op(Arc::clone(&endpoint.client));
+            async move {
+                let started = Instant::now();
+                let outcome = call.await;
+                endpoint.record(&outcome, started.elapsed());
+                outcome
+            }
+        }))
+        .await;
+
+        let mut tallies: Vec<(T, usize)> = Vec::new();
+        for answer in answers.into_iter().flatten() {
+            match tallies.iter_mut().find(|(value, _)| *value == answer) {
+                Some((_, votes)) => *votes += 1,
+                None => tallies.push((answer, 1)),
+            }
+        }
+        debug!(
+            "Solana RPC quorum votes: {:?} (need {})",
+            tallies.iter().map(|(_, votes)| votes).collect::<Vec<_>>(),
+            quorum
+        );
+        Ok(tallies
+            .into_iter()
+            .find(|(_, votes)| *votes >= quorum)
+            .map(|(value, _)| value))
+    }
+
+    /// Ask every endpoint for its slot, refreshing latency and staleness scores
+    pub async fn probe(&self, commitment: CommitmentConfig) {
+        // A single endpoint has nothing to be compared with
+        if self.endpoints.len() < 2 {
+            return;
+        }
+        join_all(self.endpoints.iter().map(|endpoint| async move {
+            let started = Instant::now();
+            let outcome = endpoint.client.get_slot_with_commitment(commitment).await;
+            endpoint.record(&outcome, started.elapsed());
+            if let Ok(slot) = outcome {
+                lock_unpoisoned(&endpoint.health).slot = Some(slot);
+            }
+        }))
+        .await;
+    }
+
+    /// Health of every endpoint, in configured order
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let best_slot = self.best_slot();
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let health = lock_unpoisoned(&endpoint.health);
+                EndpointStatus {
+                    url: endpoint.url.clone(),
+                    latency_ms: health.latency_ms,
+                    consecutive_failures: health.consecutive_failures,
+                    slot: health.slot,
+                    backing_off: health.backing_off(),
+                    stale: Self::is_stale(&health, best_slot),
+                    requests: endpoint.throttle.requests(),
+                    throttled: endpoint.throttle.throttled(),
+                    throttle_wait_ms: endpoint.throttle.waited().as_millis() as u64,
+                }
+            })
+            .collect()
+    }
+
+    fn best_slot(&self) -> Option<u64> {
+        self.endpoints.iter().filter_map(|endpoint| lock_unpoisoned(&endpoint.health).slot).max()
+    }
+
+    fn is_stale(health: &EndpointHealth, best_slot: Option<u64>) -> bool {
+        match (health.slot, best_slot) {
+            (Some(slot), Some(best)) => best.saturating_sub(slot) > MAX_SLOT_LAG,
+            _ => false,
+        }
+    }
+
+    /// Endpoint indices, best first: not backing off, not stale, then fastest
+    fn ranked(&self) -> Vec<usize> {
+        let best_slot = self.best_slot();
+        let mut scored: Vec<_> = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| {
+                let health = lock_unpoisoned(&endpoint.health);
+                let latency = health.latency_ms.unwrap_or(0.0);
+                (
+                    (health.backing_off(), Self::is_stale(&health, best_slot)),
+                    latency,
+                    index,
+                )
+            })
+            .collect();
+        // Stable, so untried endpoints keep their configured order
+        scored.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        scored.into_iter().map(|(_, _, index)| index).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::mock_rpc::serve_rpc;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn new_pool(urls: &[String], quorum: Option<usize>) -> RpcPool {
+        RpcPool::new(urls, CommitmentConfig::confirmed(), Duration::from_millis(500), quorum, 0).unwrap()
+    }
+
+    async fn slot_server(slot: u64) -> String {
+        serve_rpc(move |method, _| match method {
+            "getSlot" => Ok(json!(slot)),
+            _ => Err(axum::http::StatusCode::NOT_FOUND),
+        })
+        .await
+    }
+
+    #[test]
+    fn test_quorum_must_fit_the_endpoints() {
+        let urls = vec!["http://a".to_string(), "http://b".to_string()];
+        let new = |quorum| RpcPool::new(&urls, CommitmentConfig::confirmed(), Duration::from_secs(1), quorum, 0);
+        assert!(new(Some(2)).is_ok());
+        assert!(new(Some(3)).is_err());
+        assert!(new(Some(0)).is_err());
+        assert!(RpcPool::new(&[], CommitmentConfig::confirmed(), Duration::from_secs(1), None, 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_and_prefers_the_healthy_endpoint() {
+        let failing_calls = Arc::new(AtomicUsize::new(0));
+        let failing = serve_rpc({
+            let failing_calls = Arc::clone(&failing_calls);
+            move |_, _| {
+                failing_calls.fetch_add(1, Ordering::SeqCst);
+                Err(axum::http::StatusCode::SERVICE_UNAVAILABLE)
+            }
+        })
+        .await;
+        let healthy = slot_server(500).await;
+        let pool = new_pool(&[failing.clone(), healthy.clone()], None);
+
+        let slot = pool.call(|client| async move { client.get_slot().await }).await.unwrap();
+        assert_eq!(slot, 500);
+        assert_eq!(failing_calls.load(Ordering::SeqCst), 1);
+
+        // The failed endpoint now backs off, so the next call skips it
+        assert_eq!(pool.primary_url(), healthy);
+        pool.call(|client| async move { client.get_slot().await }).await.unwrap();
+        assert_eq!(failing_calls.load(Ordering::SeqCst), 1);
+
+        let status = pool.status();
+        assert_eq!(status[0].consecutive_failures, 1);
+        assert!(status[0].backing_off);
+        assert!(status[1].latency_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_requests_are_retried_on_the_same_endpoint() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let throttling = serve_rpc({
+            let calls = Arc::clone(&calls);
+            move |_, _| match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(axum::http::StatusCode::TOO_MANY_REQUESTS),
+                _ => Ok(json!(42)),
+            }
+        })
+        .await;
+        let other = slot_server(7).await;
+        let pool = new_pool(&[throttling, other], None);
+
+        let slot = pool.call(|client| async move { client.get_slot().await }).await.unwrap();
+        assert_eq!(slot, 42);
+
+        // A 429 is waited out, not counted as a failure or failed over from
+        let status = pool.status();
+        assert_eq!((status[0].requests, status[0].throttled), (2, 1));
+        assert_eq!(status[0].consecutive_failures, 0);
+        assert!(status[0].throttle_wait_ms >= 300);
+        assert_eq!(status[1].requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stale_and_stalled_endpoints_are_passed_over() {
+        let stale = slot_server(1_000).await;
+        let fresh = slot_server(2_000).await;
+        let pool = new_pool(&[stale.clone(), fresh.clone()], None);
+
+        pool.probe(CommitmentConfig::confirmed()).await;
+        assert!(pool.status()[0].stale);
+        assert_eq!(pool.primary_url(), fresh);
+
+        // An endpoint that accepts connections but never answers times out
+        let stalled = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stalled_url = format!("http://{}", stalled.local_addr().unwrap());
+        let pool = new_pool(&[stalled_url, fresh], None);
+        let slot = pool.call(|client| async move { client.get_slot().await }).await.unwrap();
+        assert_eq!(slot, 2_000);
+        assert_eq!(pool.status()[0].consecutive_failures, 1);
+    }
+
+    #[tokio::test]
+    async fn test_quorum_needs_enough_agreeing_endpoints() {
+        let honest = slot_server(700).await;
+        let also_honest = slot_server(700).await;
+        let lying = slot_server(900).await;
+        let urls = vec![honest, lying, also_honest];
+
+        let get_slot = |client: Arc<RpcClient>| async move { client.get_slot().await };
+        assert_eq!(new_pool(&urls, Some(2)).quorum(get_slot).await.unwrap(), Some(700));
+        assert_eq!(new_pool(&urls, Some(3)).quorum(get_slot).await.unwrap(), None);
+    }
+}
@@ -1,56 +1,148 @@
-use crate::types::{
-    SettlementError, SettlementInstruction, SolanaConfig, SolanaSettlementEvent,
-    TransactionHash,
+use super::rpc_pool::{EndpointStatus, RpcPool};
+use crate::{
+    database::{MemoryStore, SettlementStore, SourceCursor},
+    retry::{RetryConfig, RetryPolicy},
+    types::{
+        FinalityState, FinalityVerdict, HistoryRange, SettlementError, SettlementInstruction,
+        SettlementRequested, SolanaConfig, SolanaSettlementEvent, SourceChainTip,
+        SourceTxStatus, TransactionHash,
+    },
 };
 use async_trait::async_trait;
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use solana_client::{
-    nonblocking::rpc_client::RpcClient,
+    client_error::ClientError,
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
     rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
-    rpc_response::{RpcLogsResponse, Response},
+    rpc_request::RpcRequest,
+    rpc_response::{RpcConfirmedTransactionStatusWithSignature, RpcLogsResponse},
 };
 use solana_sdk::{
     commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
 };
-use std::{str::FromStr, sync::Arc, time::Duration};
-use tokio::time::{interval, sleep};
+use std::{
+    pin::Pin,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, oneshot, Notify},
+    time::sleep,
+};
 use tracing::{debug, error, info, warn};
 
+/// Largest page `getSignaturesForAddress` returns
+const SIGNATURE_PAGE_LIMIT: usize = 1000;
+
+/// Slots read back on the very first run, before any checkpoint exists
+const FIRST_RUN_SLOTS: u64 = 10;
+
+/// While the log subscription is live, polling only reconciles and runs this many times less often
+const SUBSCRIBED_POLL_FACTOR: u32 = 30;
+
+/// Most signatures `getSignatureStatuses` accepts per call
+const SIGNATURE_STATUS_LIMIT: usize = 256;
+
+/// Events a listener may get ahead of the consumer of its stream
+const EVENT_STREAM_BUFFER: usize = 64;
+
+/// What a source chain's event stream yields
+#[derive(Debug)]
+pub enum SourceEvent {
+    /// A settlement event that meets the finality policy, to be acked once
+    /// it is stored durably
+    Settlement(SettlementInstruction, SourceAck),
+    /// Every transaction up to the cursor has been read and its events held
+    /// durably, and every settlement sent before it has been acked; persisting
+    /// it lets the next stream resume from here
+    Checkpoint(SourceCursor),
+}
+
+/// Sent back by the consumer of a [`SourceEvent::Settlement`] once the
+/// settlement is stored durably.
+///
+/// Until then the listener keeps the event held for finality, so an event
+/// dropped unacked, e.g. by a crash, is released again instead of lost.
+#[derive(Debug)]
+pub struct SourceAck(oneshot::Sender<()>);
+
+impl SourceAck {
+    fn new() -> (Self, oneshot::Receiver<()>) {
+        let (sender, receiver) = oneshot::channel();
+        (Self(sender), receiver)
+    }
+
+    /// Confirm the settlement is stored
+    pub fn ack(self) {
+        // The listener may have stopped since; it then re-releases the event on restart
+        let _ = self.0.send(());
+    }
+}
+
+/// Transaction fetching throughput and RPC throttling, for metrics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SourceFetchStats {
+    /// Transactions fetched since startup
+    pub transactions_fetched: u64,
+    /// Requests, throttling and health of each RPC endpoint
+    pub endpoints: Vec<EndpointStatus>,
+}
+
+/// Settlement events and checkpoints from a source chain
+pub type SourceEventStream = Pin<Box<dyn Stream<Item = SourceEvent> + Send>>;
+
 /// Trait for interacting with source chains
 #[async_trait]
 pub trait SourceChain: Send + Sync {
-    async fn start_event_listener(&self) -> Result<(), SettlementError>;
+    /// Key of this chain's checkpoint in the settlement store
+    fn source_id(&self) -> String;
+
+    /// Listen for settlement events, resuming after `checkpoint`.
+    ///
+    /// The consumer must store each settlement durably before acking it; the
+    /// listener waits for that ack before it moves on, so a checkpoint never
+    /// covers a settlement that could still be lost. Dropping the stream
+    /// stops every task behind it.
+    async fn event_stream(
+        &self,
+        checkpoint: Option<SourceCursor>,
+    ) -> Result<SourceEventStream, SettlementError>;
+
     async fn get_settlement_events(
         &self,
         from_slot: Option<u64>,
     ) -> Result<Vec<SettlementInstruction>, SettlementError>;
     async fn verify_transaction(&self, tx_hash: &TransactionHash) -> Result<bool, SettlementError>;
     async fn get_latest_slot(&self) -> Result<u64, SettlementError>;
+
+    /// Fetch throughput and throttling so far
+    fn fetch_stats(&self) -> SourceFetchStats;
 }
 
 /// Solana chain implementation
+#[derive(Clone)]
 pub struct SolanaChain {
-    client: Arc<RpcClient>,
+    rpc: Arc<RpcPool>,
     config: SolanaConfig,
     program_id: Pubkey,
     commitment: CommitmentConfig,
-    event_sender: Option<tokio::sync::mpsc::UnboundedSender<SettlementInstruction>>,
+    /// Backoff between failed slot polls
+    poll_retry: RetryPolicy,
+    /// Keeps the pending-finality buffer across restarts
+    store: Arc<dyn SettlementStore>,
+    /// Transactions fetched since startup
+    fetched: Arc<AtomicU64>,
 }
 
 impl SolanaChain {
-    pub fn new(
-        config: SolanaConfig,
-        event_sender: Option<tokio::sync::mpsc::UnboundedSender<SettlementInstruction>>,
-    ) -> Result<Self, SettlementError> {
-        let client = Arc::new(RpcClient::new_with_commitment(
-            config.rpc_url.clone(),
-            match config.commitment.as_str() {
-                "processed" => CommitmentConfig::processed(),
-                "confirmed" => CommitmentConfig::confirmed(),
-                "finalized" => CommitmentConfig::finalized(),
-                _ => CommitmentConfig::confirmed(),
-            },
-        ));
-
+    pub fn new(config: SolanaConfig) -> Result<Self, SettlementError> {
         let program_id = Pubkey::from_str(&config.program_id)
             .map_err(|e| SettlementError::ConfigError(format!("Invalid program ID: {}", e)))?;
 
@@ -61,222 +153,738 @@ impl SolanaChain {
             _ => CommitmentConfig::confirmed(),
         };
 
+        let rpc = Arc::new(RpcPool::new(
+            &config.rpc_urls(),
+            commitment,
+            Duration::from_millis(config.rpc_timeout_ms),
+            config.rpc_quorum,
+            config.rpc_requests_per_second,
+        )?);
+
         Ok(Self {
-            client,
+            rpc,
             config,
             program_id,
             commitment,
-            event_sender,
+            poll_retry: RetryConfig::default().source_poll,
+            store: Arc::new(MemoryStore::new()),
+            fetched: Arc::new(AtomicU64::new(0)),
         })
     }
 
-    /// Parse logs to extract settlement events
-    fn parse_settlement_event(&self, logs: &[String], signature: &str, slot: u64, block_time: Option<i64>) -> Option<SettlementInstruction> {
-        for log in logs {
-            if log.contains("SETTLEMENT_EVENT:") {
-                if let Some(json_start) = log.find('{') {
-                    let json_str = &log[json_start..];
-                    match serde_json::from_str::<serde_json::Value>(json_str) {
-                        Ok(event_json) => {
-                            let event = SolanaSettlementEvent {
-                                source_chain: "solana".to_string(),
-                                aptos_recipient: event_json["aptos_recipient"]
-                                    .as_str()
-                                    .unwrap_or_default()
-                                    .to_string(),
-                                amount: event_json["amount"].as_u64().unwrap_or(0),
-                                nonce: event_json["nonce"].as_u64().unwrap_or(0),
-                                slot,
-                                timestamp: event_json["timestamp"].as_u64().unwrap_or(0),
-                                signature: signature.to_string(),
-                                block_time,
-                            };
-
-                            debug!("Parsed settlement event: {:?}", event);
-                            return Some(event.into());
-                        }
-                        Err(e) => {
-                            warn!("Failed to parse settlement event JSON: {}", e);
-                        }
+    /// Use `policy` to back off while slot polling keeps failing
+    pub fn with_poll_retry(mut self, policy: RetryPolicy) -> Self {
+        self.poll_retry = policy;
+        self
+    }
+
+    /// Persist events waiting for finality in `store` instead of process memory
+    pub fn with_store(mut self, store: Arc<dyn SettlementStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Successful program transactions newer than `until`, oldest first.
+    ///
+    /// Pages back from the newest signature with `before` until the RPC reaches
+    /// `until`, so no transaction is skipped however many landed since the last
+    /// poll. Without a checkpoint only the newest page is read.
+    async fn signatures_since(
+        &self,
+        until: Option<&SourceCursor>,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, SettlementError> {
+        let until = until.map(|cursor| parse_signature(&cursor.signature)).transpose()?;
+
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self.signature_page(before, until).await?;
+
+            let page_len = page.len();
+            let oldest = page.last().map(|sig_info| sig_info.signature.clone());
+            // A failed transaction's events were rolled back with it
+            signatures.extend(page.into_iter().filter(|sig_info| sig_info.err.is_none()));
+
+            if until.is_none() || page_len < SIGNATURE_PAGE_LIMIT {
+                break;
+            }
+            match oldest {
+                Some(oldest) => {
+                    debug!("Paging signatures before {}", oldest);
+                    before = Some(parse_signature(&oldest)?);
+                }
+                None => break,
+            }
+        }
+
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    /// Successful program transactions in `range`, oldest first.
+    ///
+    /// Pages back through the program's full history rather than stopping at
+    /// the newest page, so a range far behind the tip is still covered.
+    async fn signatures_in_range(
+        &self,
+        range: &HistoryRange,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, SettlementError> {
+        let (mut before, until, slots) = match range {
+            HistoryRange::Slots { from, to } => (None, None, *from..=to.unwrap_or(u64::MAX)),
+            HistoryRange::Signatures { after, before } => (
+                before.as_deref().map(parse_signature).transpose()?,
+                Some(parse_signature(after)?),
+                0..=u64::MAX,
+            ),
+        };
+
+        let mut signatures = Vec::new();
+        loop {
+            let page = self.signature_page(before, until).await?;
+
+            let page_len = page.len();
+            let oldest = page.last().map(|sig_info| (sig_info.signature.clone(), sig_info.slot));
+            signatures.extend(
+                page.into_iter()
+                    .filter(|sig_info| sig_info.err.is_none() && slots.contains(&sig_info.slot)),
+            );
+
+            match oldest {
+                // Signatures come newest first, so nothing older can be in range
+                Some((_, slot)) if slot < *slots.start() => break,
+                Some((oldest, _)) if page_len == SIGNATURE_PAGE_LIMIT => {
+                    debug!("Paging signatures before {}", oldest);
+                    before = Some(parse_signature(&oldest)?);
+                }
+                _ => break,
+            }
+        }
+
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    /// One page of program signatures, newest first
+    async fn signature_page(
+        &self,
+        before: Option<Signature>,
+        until: Option<Signature>,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, SettlementError> {
+        self.rpc.call(|client| async move {
+            client.get_signatures_for_address_with_config(
+                &self.program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(SIGNATURE_PAGE_LIMIT),
+                    commitment: Some(self.commitment),
+                },
+            ).await
+        })
+        .await
+        .map_err(|e| SettlementError::ChainError(format!("Failed to get signatures: {}", e)))
+    }
+
+    /// Settlement events of every program transaction in `range`, oldest
+    /// first, with the slot each was seen in.
+    ///
+    /// Fetches up to `max_concurrent_fetches` transactions at once. Fails on
+    /// the first transaction that cannot be fetched rather than leaving a
+    /// hole in the range.
+    pub async fn history_events(
+        &self,
+        range: &HistoryRange,
+    ) -> Result<Vec<(u64, SettlementInstruction)>, SettlementError> {
+        let signatures = self.signatures_in_range(range).await?;
+        info!("Scanning {} program transaction(s) in {:?}", signatures.len(), range);
+
+        // Buffered keeps results in signature order however fetches complete
+        let fetched: Vec<(u64, Vec<SettlementInstruction>)> = stream::iter(signatures)
+            .map(|sig_info| async move {
+                let instructions = self.transaction_events(&sig_info).await?;
+                Ok::<_, SettlementError>((sig_info.slot, instructions))
+            })
+            .buffered(self.config.max_concurrent_fetches)
+            .try_collect()
+            .await?;
+
+        Ok(fetched
+            .into_iter()
+            .flat_map(|(slot, instructions)| {
+                instructions.into_iter().map(move |instruction| (slot, instruction))
+            })
+            .collect())
+    }
+
+    /// Settlement events of one transaction, emitted by the program
+    async fn transaction_events(
+        &self,
+        sig_info: &RpcConfirmedTransactionStatusWithSignature,
+    ) -> Result<Vec<SettlementInstruction>, SettlementError> {
+        let logs = self.rpc
+            .call(|client| Self::fetch_transaction_logs(client, &sig_info.signature, self.commitment))
+            .await
+            .map_err(|e| SettlementError::ChainError(format!("Failed to get transaction {}: {}", sig_info.signature, e)))?;
+        self.fetched.fetch_add(1, Ordering::Relaxed);
+
+        let Some(log_messages) = logs else {
+            return Ok(Vec::new());
+        };
+
+        if !Self::has_settlement_logs(&log_messages) {
+            return Ok(Vec::new());
+        }
+        Ok(Self::parse_settlement_events(
+            &self.program_id,
+            &log_messages,
+            &sig_info.signature,
+            sig_info.slot,
+            sig_info.block_time,
+        ))
+    }
+
+    /// Hold every settlement event since `cursor` for finality, advancing it
+    /// past each transaction once its events are held.
+    ///
+    /// Fetches up to `max_concurrent_fetches` transactions at once but holds
+    /// their events in signature order. Stops at the first transaction that
+    /// cannot be fetched, so the next poll retries it instead of skipping it.
+    async fn poll_signatures(
+        &self,
+        cursor: &mut Option<SourceCursor>,
+        current_slot: u64,
+    ) -> Result<(), SettlementError> {
+        let first_run = cursor.is_none();
+        let signatures = self.signatures_since(cursor.as_ref()).await?;
+        if signatures.is_empty() {
+            return Ok(());
+        }
+        debug!("Processing {} new signature(s) for {}", signatures.len(), self.program_id);
+
+        let start_slot = current_slot.saturating_sub(FIRST_RUN_SLOTS);
+        let mut fetches = stream::iter(signatures)
+            .map(|sig_info| async move {
+                // The first run only reads back a few slots
+                let events = if first_run && sig_info.slot < start_slot {
+                    None
+                } else {
+                    Some(self.transaction_events(&sig_info).await)
+                };
+                (sig_info, events)
+            })
+            .buffered(self.config.max_concurrent_fetches);
+
+        let mut outcome = Ok(());
+        while let Some((sig_info, events)) = fetches.next().await {
+            let slot = sig_info.slot;
+            if let Some(events) = events {
+                let held = match events {
+                    Ok(instructions) => self.hold_for_finality(instructions, slot).await,
+                    Err(e) => Err(e),
+                };
+                // Dropping the stream cancels the fetches still in flight
+                if let Err(e) = held {
+                    outcome = Err(e);
+                    break;
+                }
+            }
+
+            *cursor = Some(SourceCursor {
+                signature: sig_info.signature,
+                slot,
+                updated_at: chrono::Utc::now(),
+            });
+        }
+        outcome
+    }
+
+    /// Read new signatures and hold their events for finality
+    async fn poll_once(&self, cursor: &mut Option<SourceCursor>) -> Result<(), SettlementError> {
+        let current_slot = self.get_latest_slot().await?;
+        self.poll_signatures(cursor, current_slot).await
+    }
+
+    /// Put events seen at `slot` in the pending-finality buffer
+    async fn hold_for_finality(&self, instructions: Vec<SettlementInstruction>, slot: u64) -> Result<(), SettlementError> {
+        for instruction in instructions {
+            if self.store.hold_for_finality(&instruction, slot).await? {
+                debug!("Holding settlement instruction {} from slot {} for finality", instruction.id, slot);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check held events against the cluster: send those that meet the
+    /// finality policy to `events` and retract those whose transaction is gone
+    /// or failed.
+    ///
+    /// A sent event is marked confirmed only after the consumer acks it.
+    async fn release_final_events(&self, events: &mpsc::Sender<SourceEvent>) -> Result<(), SettlementError> {
+        let pending = self.store.pending_finality().await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        // An event whose signature does not parse can never be checked, so it
+        // is retracted on its own rather than holding up the rest
+        let mut held = Vec::with_capacity(pending.len());
+        let mut signatures = Vec::with_capacity(pending.len());
+        for record in pending {
+            let instruction = &record.instruction;
+            match parse_signature(&instruction.source_tx_hash.0) {
+                Ok(signature) => {
+                    signatures.push(signature);
+                    held.push(record);
+                }
+                Err(e) => {
+                    warn!("Retracting settlement instruction {}: {}", instruction.id, e);
+                    self.store.decide_finality(instruction.id, FinalityState::Retracted, &e.to_string()).await?;
+                }
+            }
+        }
+        if held.is_empty() {
+            return Ok(());
+        }
+
+        // Tip and statuses come from one endpoint: a lagging endpoint's missing
+        // status read against a healthier one's tip would retract a real
+        // transaction, and only a backfill can bring a retracted one back
+        let (tip, statuses) = self.rpc
+            .call(|client| {
+                let signatures = &signatures;
+                async move {
+                    let tip = SourceChainTip {
+                        confirmed_slot: client.get_slot_with_commitment(CommitmentConfig::confirmed()).await?,
+                        finalized_slot: client.get_slot_with_commitment(CommitmentConfig::finalized()).await?,
+                    };
+                    let mut statuses = Vec::with_capacity(signatures.len());
+                    for chunk in signatures.chunks(SIGNATURE_STATUS_LIMIT) {
+                        // Search the ledger too, so events held across a long outage are still found
+                        statuses.extend(client.get_signature_statuses_with_history(chunk).await?.value);
                     }
+                    Ok((tip, statuses))
+                }
+            })
+            .await
+            .map_err(|e| SettlementError::ChainError(format!("Failed to get signature statuses: {}", e)))?;
+
+        for (record, status) in held.iter().zip(statuses) {
+            let status = match status {
+                None => SourceTxStatus::Missing,
+                Some(status) if status.err.is_some() => SourceTxStatus::Failed,
+                Some(status) if status.satisfies_commitment(CommitmentConfig::finalized()) => {
+                    SourceTxStatus::Finalized
+                }
+                Some(status) if status.satisfies_commitment(CommitmentConfig::confirmed()) => {
+                    SourceTxStatus::Confirmed
+                }
+                Some(_) => SourceTxStatus::Processed,
+            };
+
+            let instruction = &record.instruction;
+            let mut verdict = self.config.finality.evaluate(record.slot, status, tip);
+            if verdict == FinalityVerdict::Release {
+                verdict = self.quorum_verdict(instruction).await?;
+            }
+            match verdict {
+                FinalityVerdict::Hold => {}
+                FinalityVerdict::Release => {
+                    let (ack, acked) = SourceAck::new();
+                    events.send(SourceEvent::Settlement(instruction.clone(), ack)).await.map_err(|_| {
+                        SettlementError::ChainError("Source event stream closed".to_string())
+                    })?;
+                    // Stop holding it only once it is stored, or an event still
+                    // in the channel would be lost with the process
+                    acked.await.map_err(|_| {
+                        SettlementError::ChainError(format!(
+                            "Settlement instruction {} dropped before it was stored",
+                            instruction.id
+                        ))
+                    })?;
+                    let reason = format!(
+                        "{:?} at slot {} (confirmed tip {}, finalized tip {})",
+                        status, record.slot, tip.confirmed_slot, tip.finalized_slot
+                    );
+                    self.store.decide_finality(instruction.id, FinalityState::Confirmed, &reason).await?;
+                    debug!("Settlement instruction {} is final: {}", instruction.id, reason);
+                }
+                FinalityVerdict::Retract(reason) => {
+                    warn!(
+                        "Retracting settlement instruction {} from {}: {}",
+                        instruction.id, instruction.source_tx_hash, reason
+                    );
+                    self.store.decide_finality(instruction.id, FinalityState::Retracted, &reason).await?;
                 }
             }
         }
-        None
+        Ok(())
     }
 
-    /// Process a batch of log responses
-    async fn process_log_responses(&self, responses: &[RpcLogsResponse]) -> Vec<SettlementInstruction> {
-        let mut instructions = Vec::new();
+    /// Whether enough RPC endpoints agree the event's transaction carries it.
+    ///
+    /// Holds the event while they disagree or have not all caught up, and
+    /// retracts it once they agree on logs that do not carry it. Always
+    /// releases when quorum mode is off.
+    async fn quorum_verdict(&self, instruction: &SettlementInstruction) -> Result<FinalityVerdict, SettlementError> {
+        let Some(quorum) = self.rpc.quorum_size() else {
+            return Ok(FinalityVerdict::Release);
+        };
+
+        let signature = instruction.source_tx_hash.0.as_str();
+        let agreed = self.rpc
+            .quorum(|client| Self::fetch_transaction_logs(client, signature, self.commitment))
+            .await?;
+        match agreed {
+            Some(Some(logs)) => {
+                let events = Self::parse_settlement_events(&self.program_id, &logs, signature, 0, None);
+                // IDs only cover the transaction and event index, so compare what gets paid out too
+                let carried = events.iter().any(|event| {
+                    event.id == instruction.id
+                        && event.sender == instruction.sender
+                        && event.receiver == instruction.receiver
+                        && event.token_symbol == instruction.token_symbol
+                        && event.amount == instruction.amount
+                        && event.nonce == instruction.nonce
+                });
+                if carried {
+                    Ok(FinalityVerdict::Release)
+                } else {
+                    Ok(FinalityVerdict::Retract(format!(
+                        "{} RPC endpoints agree the source transaction does not carry this event",
+                        quorum
+                    )))
+                }
+            }
+            _ => {
+                debug!("No RPC quorum of {} yet on transaction {}", quorum, signature);
+                Ok(FinalityVerdict::Hold)
+            }
+        }
+    }
+
+    /// Poll for new signatures and release final events into `events` until
+    /// the stream is dropped, checkpointing after each poll that advanced.
+    ///
+    /// `subscribed` and `reconcile` come from the log subscription, if any.
+    async fn run_poll_loop(
+        self,
+        mut cursor: Option<SourceCursor>,
+        events: mpsc::Sender<SourceEvent>,
+        subscribed: Arc<AtomicBool>,
+        reconcile: Arc<Notify>,
+    ) {
+        let poll_interval = Duration::from_millis(self.config.poll_interval_ms);
+        let mut consecutive_failures = 0u32;
+        let mut ticks_since_poll = SUBSCRIBED_POLL_FACTOR;
+        let mut checkpointed = cursor.as_ref().map(|cursor| cursor.signature.clone());
+
+        loop {
+            // Events arrive over the subscription while it is live; signature polling
+            // then only reconciles, and runs at once after a (re)subscribe to close the gap
+            let polled = if !subscribed.load(Ordering::SeqCst) || ticks_since_poll >= SUBSCRIBED_POLL_FACTOR {
+                ticks_since_poll = 0;
+                self.rpc.probe(self.commitment).await;
+                self.poll_once(&mut cursor).await
+            } else {
+                ticks_since_poll += 1;
+                Ok(())
+            };
+            let released = self.release_final_events(&events).await;
+            if events.is_closed() {
+                break;
+            }
+
+            // Sent after the release so the consumer has acked every settlement
+            // before it; even a poll that failed part-way has held what it passed
+            if let Some(advanced) = cursor.as_ref().filter(|cursor| Some(&cursor.signature) != checkpointed.as_ref()) {
+                if events.send(SourceEvent::Checkpoint(advanced.clone())).await.is_err() {
+                    break;
+                }
+                checkpointed = Some(advanced.signature.clone());
+            }
+
+            match polled.and(released) {
+                Ok(()) => consecutive_failures = 0,
+                Err(e) => {
+                    error!("Solana poll failed: {}", e);
+                    consecutive_failures += 1;
+
+                    let delay = self.poll_retry.delay(consecutive_failures);
+                    warn!(
+                        "Solana polling failed {} time(s) in a row, backing off for {:?}",
+                        consecutive_failures, delay
+                    );
+                    sleep(delay).await;
+                }
+            }
+
+            tokio::select! {
+                _ = sleep(poll_interval) => {}
+                _ = reconcile.notified() => {
+                    debug!("Reconciling Solana signatures after subscription change");
+                    ticks_since_poll = SUBSCRIBED_POLL_FACTOR;
+                }
+                _ = events.closed() => break,
+            }
+        }
+        debug!("Solana event stream dropped, stopping the poll loop for {}", self.program_id);
+    }
+
+    /// Whether any log line may carry a settlement event
+    fn has_settlement_logs(logs: &[String]) -> bool {
+        logs.iter().any(|log| {
+            log.starts_with(SettlementRequested::LOG_PREFIX) || log.contains("SETTLEMENT_EVENT:")
+        })
+    }
+
+    /// Log lines emitted while `program_id` was executing, tracked through the
+    /// `Program <id> invoke [n]` / `success` / `failed` lines so that events
+    /// printed by other programs in the same transaction are dropped. Lines
+    /// from `program_id` reached through CPI are kept.
+    fn program_logs<'a>(program_id: &Pubkey, logs: &'a [String]) -> Vec<&'a str> {
+        let mut invoke_stack: Vec<Pubkey> = Vec::new();
+        let mut emitted = Vec::new();
+
+        for log in logs {
+            if log == "Log truncated" {
+                // The rest of the invoke stack cannot be attributed
+                warn!("Transaction logs truncated; ignoring the remaining lines");
+                break;
+            }
 
-        for response in responses {
-            if let Some(signature) = &response.signature {
-                if let Ok(sig) = Signature::from_str(signature) {
-                    // Get transaction details
-                    match self.client.get_transaction(&sig, solana_client::rpc_config::RpcTransactionConfig {
-                        encoding: Some(solana_account_decoder::UiTransactionEncoding::Json),
-                        commitment: Some(self.commitment),
-                        max_supported_transaction_version: Some(0),
-                    }).await {
-                        Ok(confirmed_transaction) => {
-                            if let Some(transaction) = confirmed_transaction.transaction {
-                                if let Some(meta) = transaction.meta {
-                                    if let Some(log_messages) = meta.log_messages {
-                                        if let Some(instruction) = self.parse_settlement_event(
-                                            &log_messages,
-                                            signature,
-                                            confirmed_transaction.slot,
-                                            confirmed_transaction.block_time,
-                                        ) {
-                                            instructions.push(instruction);
-                                        }
-                                    }
+            if let Some(rest) = log.strip_prefix("Program ") {
+                let mut parts = rest.split_whitespace();
+                if let (Some(id), Some(action)) = (parts.next(), parts.next()) {
+                    if let Ok(id) = Pubkey::from_str(id) {
+                        match action {
+                            "invoke" => {
+                                invoke_stack.push(id);
+                                continue;
+                            }
+                            "success" | "failed:" => {
+                                if invoke_stack.pop() != Some(id) {
+                                    warn!("Unbalanced invoke stack at program {}", id);
                                 }
+                                continue;
                             }
-                        }
-                        Err(e) => {
-                            error!("Failed to get transaction details for {}: {}", signature, e);
+                            _ => {}
                         }
                     }
                 }
             }
+
+            if invoke_stack.last() == Some(program_id) {
+                emitted.push(log.as_str());
+            }
+        }
+        emitted
+    }
+
+    /// Parse logs to extract every settlement event emitted by `program_id`,
+    /// in log order
+    fn parse_settlement_events(program_id: &Pubkey, logs: &[String], signature: &str, slot: u64, block_time: Option<i64>) -> Vec<SettlementInstruction> {
+        let logs = Self::program_logs(program_id, logs);
+        let mut instructions = Vec::new();
+
+        // Anchor events are indexed by their order among the decoded events; one
+        // that fails to decode keeps its index so later events keep theirs
+        let mut anchor_events = 0u32;
+        for log in logs.iter().copied() {
+            match SettlementRequested::from_log(log) {
+                Ok(Some(requested)) => {
+                    let event = requested.into_event(signature, block_time, anchor_events);
+                    debug!("Decoded SettlementRequested event: {:?}", event);
+                    instructions.push(event.into());
+                    anchor_events += 1;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    warn!("Failed to decode SettlementRequested in {}: {}", signature, e);
+                    anchor_events += 1;
+                }
+            }
+        }
+        if anchor_events > 0 {
+            // The program logs the legacy JSON alongside each Anchor event, so
+            // falling back here would resurrect events that failed to decode
+            return instructions;
         }
 
+        // Legacy `SETTLEMENT_EVENT:` JSON, for program versions without Anchor events
+        for (event_index, log) in logs.iter().filter(|log| log.contains("SETTLEMENT_EVENT:")).enumerate() {
+            if let Some(json_start) = log.find('{') {
+                let json_str = &log[json_start..];
+                match serde_json::from_str::<serde_json::Value>(json_str) {
+                    Ok(event_json) => {
+                        let event = SolanaSettlementEvent {
+                            source_chain: "solana".to_string(),
+                            aptos_recipient: event_json["aptos_recipient"]
+                                .as_str()
+                                .unwrap_or_default()
+                                .to_string(),
+                            amount: event_json["amount"].as_u64().unwrap_or(0),
+                            nonce: event_json["nonce"].as_u64().unwrap_or(0),
+                            slot,
+                            timestamp: event_json["timestamp"].as_u64().unwrap_or(0),
+                            signature: signature.to_string(),
+                            block_time,
+                            event_index: event_index as u32,
+                        };
+
+                        debug!("Parsed legacy settlement event: {:?}", event);
+                        instructions.push(event.into());
+                    }
+                    Err(e) => {
+                        warn!("Failed to parse settlement event JSON: {}", e);
+                    }
+                }
+            }
+        }
         instructions
     }
+
+    /// Log messages of a transaction, or `None` if the endpoint does not know it
+    async fn fetch_transaction_logs(
+        client: Arc<RpcClient>,
+        signature: &str,
+        commitment: CommitmentConfig,
+    ) -> Result<Option<Vec<String>>, ClientError> {
+        let transaction: Option<serde_json::Value> = client.send(
+            RpcRequest::GetTransaction,
+            json!([signature, {
+                "encoding": "json",
+                "commitment": commitment.commitment,
+                "maxSupportedTransactionVersion": 0,
+            }]),
+        ).await?;
+        Ok(transaction.map(|transaction| {
+            serde_json::from_value(transaction["meta"]["logMessages"].clone()).unwrap_or_default()
+        }))
+    }
+
+    /// Settlement events carried by one `logsNotification`
+    fn log_notification_events(program_id: &Pubkey, response: &RpcLogsResponse, slot: u64) -> Vec<SettlementInstruction> {
+        // A failed transaction's events were rolled back with it
+        if response.err.is_some() || !Self::has_settlement_logs(&response.logs) {
+            return Vec::new();
+        }
+        Self::parse_settlement_events(program_id, &response.logs, &response.signature, slot, None)
+    }
+
+    /// Keep a `logsSubscribe` subscription open, reconnecting and resubscribing
+    /// with backoff whenever it drops.
+    ///
+    /// `subscribed` tells the poll loop whether the stream is live, and
+    /// `reconcile` wakes it to cover whatever landed while disconnected.
+    async fn run_log_subscription(self, ws_url: String, subscribed: Arc<AtomicBool>, reconcile: Arc<Notify>) {
+        let mut consecutive_failures = 0u32;
+
+        loop {
+            match self.stream_logs(&ws_url, &subscribed, &reconcile).await {
+                Ok(()) => {
+                    warn!("Log subscription at {} closed, reconnecting", ws_url);
+                    consecutive_failures = 1;
+                }
+                Err(e) => {
+                    consecutive_failures += 1;
+                    warn!(
+                        "Log subscription at {} failed {} time(s) in a row: {}",
+                        ws_url, consecutive_failures, e
+                    );
+                }
+            }
+
+            subscribed.store(false, Ordering::SeqCst);
+            reconcile.notify_one();
+            sleep(self.poll_retry.delay(consecutive_failures)).await;
+        }
+    }
+
+    /// Subscribe to the program's logs and hand off events until the stream ends
+    async fn stream_logs(&self, ws_url: &str, subscribed: &AtomicBool, reconcile: &Notify) -> Result<(), SettlementError> {
+        let client = PubsubClient::new(ws_url)
+            .await
+            .map_err(|e| SettlementError::ChainError(format!("Failed to connect to {}: {}", ws_url, e)))?;
+
+        let (mut notifications, unsubscribe) = client
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![self.program_id.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(self.commitment),
+                },
+            )
+            .await
+            .map_err(|e| SettlementError::ChainError(format!("Failed to subscribe to logs: {}", e)))?;
+
+        info!("Subscribed to logs for {} at {}", self.program_id, ws_url);
+        subscribed.store(true, Ordering::SeqCst);
+        // Anything that landed before the subscription started is left to a poll
+        reconcile.notify_one();
+
+        while let Some(notification) = notifications.next().await {
+            let instructions = Self::log_notification_events(
+                &self.program_id,
+                &notification.value,
+                notification.context.slot,
+            );
+            if let Err(e) = self.hold_for_finality(instructions, notification.context.slot).await {
+                // The reconciling poll picks the transaction up again
+                error!("Failed to hold streamed settlement events from {}: {}", notification.value.signature, e);
+            }
+        }
+
+        drop(notifications);
+        unsubscribe().await;
+        Ok(())
+    }
+}
+
+fn parse_signature(signature: &str) -> Result<Signature, SettlementError> {
+    Signature::from_str(signature)
+        .map_err(|e| SettlementError::ChainError(format!("Invalid signature {}: {}", signature, e)))
 }
 
 #[async_trait]
 impl SourceChain for SolanaChain {
-    async fn start_event_listener(&self) -> Result<(), SettlementError> {
+    fn source_id(&self) -> String {
+        format!("solana:{}", self.program_id)
+    }
+
+    async fn event_stream(
+        &self,
+        checkpoint: Option<SourceCursor>,
+    ) -> Result<SourceEventStream, SettlementError> {
         info!("Starting Solana event listener for program: {}", self.program_id);
-        
-        let client = Arc::clone(&self.client);
-        let program_id = self.program_id;
-        let event_sender = self.event_sender.clone();
-        let poll_interval = Duration::from_millis(self.config.poll_interval_ms);
-        let commitment = self.commitment;
-
-        tokio::spawn(async move {
-            let mut last_processed_slot = 0u64;
-            let mut retry_count = 0u32;
-            let max_retries = 3;
-
-            loop {
-                match client.get_slot_with_commitment(commitment).await {
-                    Ok(current_slot) => {
-                        retry_count = 0;
-
-                        // Only process new slots
-                        if current_slot > last_processed_slot {
-                            let start_slot = if last_processed_slot == 0 {
-                                current_slot.saturating_sub(10) // Start from 10 slots back on first run
-                            } else {
-                                last_processed_slot + 1
-                            };
-
-                            debug!("Processing slots {} to {}", start_slot, current_slot);
-
-                            // Subscribe to logs for our program
-                            let logs_config = RpcTransactionLogsConfig {
-                                commitment: Some(commitment),
-                            };
-
-                            let filter = RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]);
-
-                            // Get logs for the slot range
-                            match client.get_signatures_for_address_with_config(
-                                &program_id,
-                                solana_client::rpc_config::GetConfirmedSignaturesForAddress2Config {
-                                    before: None,
-                                    until: None,
-                                    limit: Some(100),
-                                    commitment: Some(commitment),
-                                },
-                            ).await {
-                                Ok(signatures) => {
-                                    for sig_info in signatures {
-                                        if sig_info.slot.unwrap_or(0) > last_processed_slot {
-                                            if let Ok(signature) = Signature::from_str(&sig_info.signature) {
-                                                match client.get_transaction(&signature, solana_client::rpc_config::RpcTransactionConfig {
-                                                    encoding: Some(solana_account_decoder::UiTransactionEncoding::Json),
-                                                    commitment: Some(commitment),
-                                                    max_supported_transaction_version: Some(0),
-                                                }).await {
-                                                    Ok(confirmed_transaction) => {
-                                                        if let Some(transaction) = confirmed_transaction.transaction {
-                                                            if let Some(meta) = transaction.meta {
-                                                                if let Some(log_messages) = meta.log_messages {
-                                                                    // Check if this is a settlement transaction
-                                                                    let contains_settlement = log_messages.iter()
-                                                                        .any(|log| log.contains("SETTLEMENT_EVENT:"));
-                                                                    
-                                                                    if contains_settlement {
-                                                                        if let Some(instruction) = Self::parse_settlement_event(
-                                                                            &SolanaChain {
-                                                                                client: client.clone(),
-                                                                                config: SolanaConfig {
-                                                                                    rpc_url: "".to_string(),
-                                                                                    program_id: "".to_string(),
-                                                                                    commitment: "confirmed".to_string(),
-                                                                                    poll_interval_ms: 1000,
-                                                                                    max_retries: 3,
-                                                                                },
-                                                                                program_id,
-                                                                                commitment,
-                                                                                event_sender: None,
-                                                                            },
-                                                                            &log_messages,
-                                                                            &sig_info.signature,
-                                                                            sig_info.slot.unwrap_or(0),
-                                                                            sig_info.block_time,
-                                                                        ) {
-                                                                            if let Some(sender) = &event_sender {
-                                                                                if let Err(e) = sender.send(instruction) {
-                                                                                    error!("Failed to send settlement instruction: {}", e);
-                                                                                }
-                                                                            }
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                    Err(e) => {
-                                                        debug!("Failed to get transaction {}: {}", sig_info.signature, e);
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    warn!("Failed to get signatures for program {}: {}", program_id, e);
-                                }
-                            }
 
-                            last_processed_slot = current_slot;
-                        }
-                    }
-                    Err(e) => {
-                        error!("Failed to get current slot: {}", e);
-                        retry_count += 1;
-                        
-                        if retry_count >= max_retries {
-                            error!("Max retries reached for slot polling, backing off");
-                            sleep(Duration::from_secs(60)).await;
-                            retry_count = 0;
-                        } else {
-                            sleep(Duration::from_secs(5)).await;
-                        }
-                    }
+        // Resume exactly where the previous run stopped
+        match &checkpoint {
+            Some(cursor) => info!("Resuming after signature {} (slot {})", cursor.signature, cursor.slot),
+            None => info!("No Solana checkpoint, starting {} slots back", FIRST_RUN_SLOTS),
+        }
+
+        let (events, mut receiver) = mpsc::channel(EVENT_STREAM_BUFFER);
+        let subscribed = Arc::new(AtomicBool::new(false));
+        let reconcile = Arc::new(Notify::new());
+        if self.config.subscribe_logs {
+            let ws_url = self.config.websocket_url();
+            info!("Streaming program logs from {}", ws_url);
+            let subscription = self.clone().run_log_subscription(
+                ws_url,
+                Arc::clone(&subscribed),
+                Arc::clone(&reconcile),
+            );
+            let events = events.clone();
+            tokio::spawn(async move {
+                tokio::select! {
+                    _ = subscription => {}
+                    _ = events.closed() => debug!("Solana event stream dropped, closing log subscription"),
                 }
+            });
+        }
 
-                sleep(poll_interval).await;
-            }
-        });
+        tokio::spawn(self.clone().run_poll_loop(checkpoint, events, subscribed, reconcile));
 
-        Ok(())
+        Ok(Box::pin(futures_util::stream::poll_fn(move |cx| receiver.poll_recv(cx))))
     }
 
     async fn get_settlement_events(
@@ -285,55 +893,16 @@ impl SourceChain for SolanaChain {
     ) -> Result<Vec<SettlementInstruction>, SettlementError> {
         info!("Fetching settlement events from slot: {:?}", from_slot);
 
-        let current_slot = self.client.get_slot_with_commitment(self.commitment).await
-            .map_err(|e| SettlementError::ChainError(format!("Failed to get current slot: {}", e)))?;
-
-        let start_slot = from_slot.unwrap_or(current_slot.saturating_sub(100));
-
-        let signatures = self.client.get_signatures_for_address_with_config(
-            &self.program_id,
-            solana_client::rpc_config::GetConfirmedSignaturesForAddress2Config {
-                before: None,
-                until: None,
-                limit: Some(100),
-                commitment: Some(self.commitment),
-            },
-        ).await
-        .map_err(|e| SettlementError::ChainError(format!("Failed to get signatures: {}", e)))?;
-
-        let mut instructions = Vec::new();
-
-        for sig_info in signatures {
-            if sig_info.slot.unwrap_or(0) >= start_slot {
-                if let Ok(signature) = Signature::from_str(&sig_info.signature) {
-                    match self.client.get_transaction(&signature, solana_client::rpc_config::RpcTransactionConfig {
-                        encoding: Some(solana_account_decoder::UiTransactionEncoding::Json),
-                        commitment: Some(self.commitment),
-                        max_supported_transaction_version: Some(0),
-                    }).await {
-                        Ok(confirmed_transaction) => {
-                            if let Some(transaction) = confirmed_transaction.transaction {
-                                if let Some(meta) = transaction.meta {
-                                    if let Some(log_messages) = meta.log_messages {
-                                        if let Some(instruction) = self.parse_settlement_event(
-                                            &log_messages,
-                                            &sig_info.signature,
-                                            sig_info.slot.unwrap_or(0),
-                                            sig_info.block_time,
-                                        ) {
-                                            instructions.push(instruction);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            debug!("Failed to get transaction {}: {}", sig_info.signature, e);
-                        }
-                    }
-                }
-            }
-        }
+        let from = match from_slot {
+            Some(from) => from,
+            None => self.get_latest_slot().await?.saturating_sub(100),
+        };
+        let instructions: Vec<_> = self
+            .history_events(&HistoryRange::Slots { from, to: None })
+            .await?
+            .into_iter()
+            .map(|(_, instruction)| instruction)
+            .collect();
 
         info!("Found {} settlement events", instructions.len());
         Ok(instructions)
@@ -343,7 +912,7 @@ impl SourceChain for SolanaChain {
         let signature = Signature::from_str(&tx_hash.0)
             .map_err(|e| SettlementError::ChainError(format!("Invalid signature: {}", e)))?;
 
-        match self.client.get_signature_status(&signature).await {
+        match self.rpc.call(|client| async move { client.get_signature_status(&signature).await }).await {
             Ok(Some(result)) => Ok(result.is_ok()),
             Ok(None) => Ok(false),
             Err(e) => Err(SettlementError::ChainError(format!("Failed to verify transaction: {}", e))),
@@ -351,53 +920,677 @@ impl SourceChain for SolanaChain {
     }
 
     async fn get_latest_slot(&self) -> Result<u64, SettlementError> {
-        self.client.get_slot_with_commitment(self.commitment).await
+        self.rpc.call(|client| async move { client.get_slot_with_commitment(self.commitment).await }).await
             .map_err(|e| SettlementError::ChainError(format!("Failed to get latest slot: {}", e)))
     }
+
+    fn fetch_stats(&self) -> SourceFetchStats {
+        SourceFetchStats {
+            transactions_fetched: self.fetched.load(Ordering::Relaxed),
+            endpoints: self.rpc.status(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::SolanaConfig;
+    use crate::{
+        chains::mock_rpc::serve_rpc,
+        types::{FinalityPolicy, SolanaConfig},
+    };
 
-    #[tokio::test]
-    async fn test_solana_chain_creation() {
-        let config = SolanaConfig {
+    const PROGRAM_ID: &str = "11111111111111111111111111111112";
+
+    fn program_id() -> Pubkey {
+        Pubkey::from_str(PROGRAM_ID).unwrap()
+    }
+
+    fn test_config() -> SolanaConfig {
+        SolanaConfig {
             rpc_url: "https://api.devnet.solana.com".to_string(),
-            program_id: "11111111111111111111111111111112".to_string(),
+            fallback_rpc_urls: Vec::new(),
+            rpc_quorum: None,
+            rpc_timeout_ms: 10_000,
+            rpc_requests_per_second: 0,
+            max_concurrent_fetches: 8,
+            ws_url: None,
+            program_id: PROGRAM_ID.to_string(),
             commitment: "confirmed".to_string(),
             poll_interval_ms: 1000,
             max_retries: 3,
-        };
+            subscribe_logs: true,
+            finality: FinalityPolicy::Finalized,
+        }
+    }
 
-        let chain = SolanaChain::new(config, None);
+    #[tokio::test]
+    async fn test_solana_chain_creation() {
+        let chain = SolanaChain::new(test_config());
         assert!(chain.is_ok());
     }
 
     #[test]
     fn test_settlement_event_parsing() {
-        let config = SolanaConfig {
-            rpc_url: "https://api.devnet.solana.com".to_string(),
-            program_id: "11111111111111111111111111111112".to_string(),
-            commitment: "confirmed".to_string(),
-            poll_interval_ms: 1000,
-            max_retries: 3,
-        };
-
-        let chain = SolanaChain::new(config, None).unwrap();
-        
         let logs = vec![
+            format!("Program {} invoke [1]", PROGRAM_ID),
             "Program log: Cyrus Protocol Settlement Request".to_string(),
             "Program log: SETTLEMENT_EVENT: {\"aptos_recipient\":\"0x123\",\"amount\":1000000,\"nonce\":42,\"slot\":12345,\"timestamp\":1640995200}".to_string(),
+            format!("Program {} success", PROGRAM_ID),
         ];
 
-        let instruction = chain.parse_settlement_event(&logs, "test_signature", 12345, Some(1640995200));
-        assert!(instruction.is_some());
+        let instructions = SolanaChain::parse_settlement_events(&program_id(), &logs, "test_signature", 12345, Some(1640995200));
+        assert_eq!(instructions.len(), 1);
         
-        let instruction = instruction.unwrap();
+        let instruction = &instructions[0];
         assert_eq!(instruction.amount, 1000000);
         assert_eq!(instruction.nonce, 42);
         assert_eq!(instruction.receiver.0, "0x123");
     }
-}
\ No newline at end of file
+
+    /// `Program data:` log for a `SettlementRequested` event
+    fn anchor_event_log(aptos_recipient: &str, amount: u64, nonce: u64) -> String {
+        use base64::{engine::general_purpose, Engine as _};
+
+        let mut data = SettlementRequested::DISCRIMINATOR.to_vec();
+        for field in ["solana", aptos_recipient] {
+            data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            data.extend_from_slice(field.as_bytes());
+        }
+        for field in [amount, nonce, 12345, 1640995200] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        format!("Program data: {}", general_purpose::STANDARD.encode(&data))
+    }
+
+    fn legacy_event_log(aptos_recipient: &str, amount: u64, nonce: u64) -> String {
+        format!(
+            "Program log: SETTLEMENT_EVENT: {{\"aptos_recipient\":\"{}\",\"amount\":{},\"nonce\":{},\"slot\":12345,\"timestamp\":1640995200}}",
+            aptos_recipient, amount, nonce
+        )
+    }
+
+    #[test]
+    fn test_anchor_event_parsing() {
+        // The program emits both the Anchor event and the legacy JSON; only the event is used
+        let logs = vec![
+            format!("Program {} invoke [1]", PROGRAM_ID),
+            "Program log: Instruction: RequestSettlement".to_string(),
+            anchor_event_log("0x456", 2_000_000, 7),
+            legacy_event_log("0x123", 1_000_000, 42),
+            format!("Program {} success", PROGRAM_ID),
+        ];
+        assert!(SolanaChain::has_settlement_logs(&logs));
+
+        let instructions = SolanaChain::parse_settlement_events(&program_id(), &logs, "test_signature", 12345, Some(1640995200));
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].amount, 2_000_000);
+        assert_eq!(instructions[0].nonce, 7);
+        assert_eq!(instructions[0].receiver.0, "0x456");
+
+        // A malformed event does not fall back to the legacy log
+        let valid = anchor_event_log("0x456", 2_000_000, 7);
+        let logs = vec![
+            logs[0].clone(),
+            valid[..valid.len() - 8].to_string(),
+            logs[3].clone(),
+            logs[4].clone(),
+        ];
+        assert!(SolanaChain::parse_settlement_events(&program_id(), &logs, "test_signature", 12345, None).is_empty());
+    }
+
+    #[test]
+    fn test_multiple_events_per_transaction() {
+        // Two `request_settlement` instructions in one transaction
+        let mut logs = Vec::new();
+        for (recipient, amount, nonce) in [("0x1", 100, 1), ("0x2", 200, 2)] {
+            logs.push(format!("Program {} invoke [1]", PROGRAM_ID));
+            logs.push(anchor_event_log(recipient, amount, nonce));
+            logs.push(legacy_event_log(recipient, amount, nonce));
+            logs.push(format!("Program {} success", PROGRAM_ID));
+        }
+
+        let instructions = SolanaChain::parse_settlement_events(&program_id(), &logs, "sig", 12345, None);
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].event_index, 0);
+        assert_eq!(instructions[1].event_index, 1);
+        assert_eq!(instructions[1].amount, 200);
+        assert_ne!(instructions[0].id, instructions[1].id);
+        assert_eq!(instructions[0].settlement_key(), "sig");
+        assert_eq!(instructions[1].settlement_key(), "sig:1");
+
+        // Legacy-only transactions are indexed the same way
+        let legacy: Vec<String> = logs.into_iter().filter(|log| !log.starts_with("Program data:")).collect();
+        let instructions = SolanaChain::parse_settlement_events(&program_id(), &legacy, "sig", 12345, None);
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[1].event_index, 1);
+        assert_eq!(instructions[1].nonce, 2);
+    }
+
+    #[test]
+    fn test_spoofed_events_are_ignored() {
+        let spoofer = Pubkey::new_unique();
+        let event = "Program log: SETTLEMENT_EVENT: {\"aptos_recipient\":\"0xbad\",\"amount\":1000000,\"nonce\":1,\"slot\":12345,\"timestamp\":1640995200}";
+        let genuine = "Program log: SETTLEMENT_EVENT: {\"aptos_recipient\":\"0x123\",\"amount\":5,\"nonce\":2,\"slot\":12345,\"timestamp\":1640995200}";
+
+        // Another top-level program printing the event is rejected
+        let logs = vec![
+            format!("Program {} invoke [1]", spoofer),
+            event.to_string(),
+            format!("Program {} consumed 1000 of 200000 compute units", spoofer),
+            format!("Program {} success", spoofer),
+        ];
+        assert!(SolanaChain::parse_settlement_events(&program_id(), &logs, "sig", 12345, None).is_empty());
+
+        // A program invoked by Cyrus through CPI is rejected, Cyrus's own event is kept
+        let logs = vec![
+            format!("Program {} invoke [1]", PROGRAM_ID),
+            format!("Program {} invoke [2]", spoofer),
+            event.to_string(),
+            format!("Program {} success", spoofer),
+            genuine.to_string(),
+            format!("Program {} success", PROGRAM_ID),
+        ];
+        let instructions = SolanaChain::parse_settlement_events(&program_id(), &logs, "sig", 12345, None);
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].receiver.0, "0x123");
+        assert_eq!(instructions[0].nonce, 2);
+
+        // Cyrus reached through CPI from a router is accepted
+        let logs = vec![
+            format!("Program {} invoke [1]", spoofer),
+            format!("Program {} invoke [2]", PROGRAM_ID),
+            genuine.to_string(),
+            format!("Program {} success", PROGRAM_ID),
+            event.to_string(),
+            format!("Program {} success", spoofer),
+        ];
+        let instructions = SolanaChain::parse_settlement_events(&program_id(), &logs, "sig", 12345, None);
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].nonce, 2);
+    }
+
+    fn signature_info(signature: &str, slot: u64, err: serde_json::Value) -> serde_json::Value {
+        json!({
+            "signature": signature,
+            "slot": slot,
+            "err": err,
+            "memo": null,
+            "blockTime": null,
+            "confirmationStatus": "finalized",
+        })
+    }
+
+    #[tokio::test]
+    async fn test_failed_signatures_are_skipped() {
+        let rpc_url = serve_rpc(|method, _| match method {
+            "getSignaturesForAddress" => Ok(json!([
+                signature_info("sig_3", 12, serde_json::Value::Null),
+                signature_info("sig_2", 11, json!({ "InstructionError": [0, { "Custom": 1 }] })),
+                signature_info("sig_1", 10, serde_json::Value::Null),
+            ])),
+            _ => Err(axum::http::StatusCode::NOT_FOUND),
+        })
+        .await;
+        let chain = SolanaChain::new(SolanaConfig { rpc_url, ..test_config() }).unwrap();
+
+        let signatures = chain.signatures_since(None).await.unwrap();
+        let signatures: Vec<&str> = signatures.iter().map(|sig_info| sig_info.signature.as_str()).collect();
+        assert_eq!(signatures, ["sig_1", "sig_3"]);
+    }
+
+    /// A valid signature that differs for every slot
+    fn slot_signature(slot: u64) -> String {
+        let mut bytes = [0u8; 64];
+        bytes[..8].copy_from_slice(&slot.to_le_bytes());
+        Signature::from(bytes).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_history_range_pages_past_the_newest_page() {
+        let page = |newest: u64| {
+            let page: Vec<_> = (0..SIGNATURE_PAGE_LIMIT as u64)
+                .map(|offset| signature_info(&slot_signature(newest - offset), newest - offset, serde_json::Value::Null))
+                .collect();
+            json!(page)
+        };
+
+        let first_page_oldest = slot_signature(1001);
+        let rpc_url = serve_rpc(move |method, params| match (method, params[1]["before"].as_str()) {
+            ("getSignaturesForAddress", None) => Ok(page(2000)),
+            ("getSignaturesForAddress", Some(before)) if before == first_page_oldest => Ok(page(1000)),
+            // Anything older is below the range and must not be requested
+            _ => Err(axum::http::StatusCode::NOT_FOUND),
+        })
+        .await;
+        let chain = SolanaChain::new(SolanaConfig { rpc_url, ..test_config() }).unwrap();
+
+        let signatures = chain
+            .signatures_in_range(&HistoryRange::Slots { from: 950, to: Some(1500) })
+            .await
+            .unwrap();
+        let slots: Vec<u64> = signatures.iter().map(|sig_info| sig_info.slot).collect();
+        assert_eq!(slots, (950..=1500).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_history_events_are_fetched_concurrently_in_slot_order() {
+        use std::sync::atomic::AtomicUsize;
+
+        let slots = 101..=120u64;
+        let throttled = Arc::new(AtomicUsize::new(0));
+        let rpc_url = serve_rpc({
+            let throttled = Arc::clone(&throttled);
+            move |method, params| match method {
+                "getSignaturesForAddress" => Ok(json!(slots
+                    .clone()
+                    .rev()
+                    .map(|slot| signature_info(&slot_signature(slot), slot, serde_json::Value::Null))
+                    .collect::<Vec<_>>())),
+                "getTransaction" => {
+                    let slot = slots
+                        .clone()
+                        .find(|slot| params[0].as_str() == Some(slot_signature(*slot).as_str()))
+                        .ok_or(axum::http::StatusCode::NOT_FOUND)?;
+                    // The provider rate limits one request mid-burst
+                    if slot == 105 && throttled.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return Err(axum::http::StatusCode::TOO_MANY_REQUESTS);
+                    }
+                    let logs = vec![
+                        format!("Program {} invoke [1]", PROGRAM_ID),
+                        legacy_event_log("0x123", 100, slot),
+                        format!("Program {} success", PROGRAM_ID),
+                    ];
+                    Ok(json!({ "slot": slot, "meta": { "err": null, "logMessages": logs } }))
+                }
+                _ => Err(axum::http::StatusCode::NOT_FOUND),
+            }
+        })
+        .await;
+        let chain = SolanaChain::new(SolanaConfig {
+            rpc_url,
+            max_concurrent_fetches: 4,
+            ..test_config()
+        })
+        .unwrap();
+
+        let events = chain
+            .history_events(&HistoryRange::Slots { from: 101, to: None })
+            .await
+            .unwrap();
+        let nonces: Vec<u64> = events.iter().map(|(_, instruction)| instruction.nonce).collect();
+        assert_eq!(nonces, (101..=120).collect::<Vec<_>>());
+        assert!(events.iter().all(|(slot, instruction)| *slot == instruction.nonce));
+
+        let stats = chain.fetch_stats();
+        assert_eq!(stats.transactions_fetched, 20);
+        assert_eq!(stats.endpoints[0].throttled, 1);
+        assert_eq!(stats.endpoints[0].consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_yields_final_events_and_stops_when_dropped() {
+        use std::sync::atomic::AtomicUsize;
+
+        let signature = slot_signature(95);
+        let logs = vec![
+            format!("Program {} invoke [1]", PROGRAM_ID),
+            legacy_event_log("0x123", 100, 7),
+            format!("Program {} success", PROGRAM_ID),
+        ];
+        let requests = Arc::new(AtomicUsize::new(0));
+        let rpc_url = serve_rpc({
+            let signature = signature.clone();
+            let requests = Arc::clone(&requests);
+            move |method, _| {
+                requests.fetch_add(1, Ordering::SeqCst);
+                match method {
+                    "getSlot" => Ok(json!(100)),
+                    "getSignaturesForAddress" => Ok(json!([signature_info(&signature, 95, serde_json::Value::Null)])),
+                    "getTransaction" => Ok(json!({ "slot": 95, "meta": { "err": null, "logMessages": logs } })),
+                    "getSignatureStatuses" => Ok(json!({
+                        "context": { "slot": 100 },
+                        "value": [{
+                            "slot": 95,
+                            "confirmations": null,
+                            "err": null,
+                            "status": { "Ok": null },
+                            "confirmationStatus": "finalized",
+                        }],
+                    })),
+                    _ => Err(axum::http::StatusCode::NOT_FOUND),
+                }
+            }
+        })
+        .await;
+        let config = SolanaConfig {
+            rpc_url,
+            poll_interval_ms: 10,
+            subscribe_logs: false,
+            ..test_config()
+        };
+        let store: Arc<dyn SettlementStore> = Arc::new(MemoryStore::new());
+        let chain = SolanaChain::new(config).unwrap().with_store(Arc::clone(&store));
+        assert_eq!(chain.source_id(), format!("solana:{}", PROGRAM_ID));
+
+        let mut events = chain.event_stream(None).await.unwrap();
+        async fn next(events: &mut SourceEventStream) -> SourceEvent {
+            tokio::time::timeout(Duration::from_secs(5), events.next()).await.unwrap().unwrap()
+        }
+
+        // The transaction is read and held, then released once final
+        let (instruction, ack) = match next(&mut events).await {
+            SourceEvent::Settlement(instruction, ack) => (instruction, ack),
+            other => panic!("Expected a settlement, got {:?}", other),
+        };
+        assert_eq!(instruction.source_tx_hash.0, signature);
+        assert_eq!(instruction.nonce, 7);
+
+        // It stays held until the consumer has stored it
+        let state = || async { store.get_finality(instruction.id).await.unwrap().unwrap().state };
+        assert_eq!(state().await, FinalityState::Pending);
+        ack.ack();
+
+        // The checkpoint past it only follows the ack
+        match next(&mut events).await {
+            SourceEvent::Checkpoint(cursor) => {
+                assert_eq!(cursor.signature, signature);
+                assert_eq!(cursor.slot, 95);
+            }
+            other => panic!("Expected a checkpoint, got {:?}", other),
+        }
+        assert_eq!(state().await, FinalityState::Confirmed);
+
+        // Dropping the stream stops the listener
+        drop(events);
+        sleep(Duration::from_millis(100)).await;
+        let stopped_at = requests.load(Ordering::SeqCst);
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(requests.load(Ordering::SeqCst), stopped_at);
+    }
+
+    #[tokio::test]
+    async fn test_unacked_events_stay_held() {
+        let signature = slot_signature(95);
+        let rpc_url = serve_rpc(|method, _| match method {
+            "getSlot" => Ok(json!(100)),
+            "getSignatureStatuses" => Ok(json!({
+                "context": { "slot": 100 },
+                "value": [{
+                    "slot": 95,
+                    "confirmations": null,
+                    "err": null,
+                    "status": { "Ok": null },
+                    "confirmationStatus": "finalized",
+                }],
+            })),
+            _ => Err(axum::http::StatusCode::NOT_FOUND),
+        })
+        .await;
+        let store: Arc<dyn SettlementStore> = Arc::new(MemoryStore::new());
+        let chain = SolanaChain::new(SolanaConfig { rpc_url, ..test_config() })
+            .unwrap()
+            .with_store(Arc::clone(&store));
+        let logs = vec![
+            format!("Program {} invoke [1]", PROGRAM_ID),
+            legacy_event_log("0x123", 100, 7),
+            format!("Program {} success", PROGRAM_ID),
+        ];
+        let instruction = SolanaChain::parse_settlement_events(&program_id(), &logs, &signature, 95, None).remove(0);
+        store.hold_for_finality(&instruction, 95).await.unwrap();
+        let state = || async { store.get_finality(instruction.id).await.unwrap().unwrap().state };
+
+        // The consumer goes away with the event still unstored
+        let (events, mut receiver) = mpsc::channel(EVENT_STREAM_BUFFER);
+        let (released, _) = tokio::join!(chain.release_final_events(&events), async move {
+            let event = receiver.recv().await;
+            assert!(matches!(event, Some(SourceEvent::Settlement(..))));
+        });
+        assert!(released.is_err());
+        assert_eq!(state().await, FinalityState::Pending);
+
+        // Released again, it is confirmed once acked
+        let (events, mut receiver) = mpsc::channel(EVENT_STREAM_BUFFER);
+        let (released, _) = tokio::join!(chain.release_final_events(&events), async move {
+            if let Some(SourceEvent::Settlement(_, ack)) = receiver.recv().await {
+                ack.ack();
+            }
+        });
+        released.unwrap();
+        assert_eq!(state().await, FinalityState::Confirmed);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_signature_does_not_block_release() {
+        let signature = slot_signature(95);
+        let rpc_url = serve_rpc(|method, params| match method {
+            "getSlot" => Ok(json!(100)),
+            "getSignatureStatuses" => {
+                // Only the well-formed signature is asked about
+                assert_eq!(params[0].as_array().map(Vec::len), Some(1));
+                Ok(json!({
+                    "context": { "slot": 100 },
+                    "value": [{
+                        "slot": 95,
+                        "confirmations": null,
+                        "err": null,
+                        "status": { "Ok": null },
+                        "confirmationStatus": "finalized",
+                    }],
+                }))
+            }
+            _ => Err(axum::http::StatusCode::NOT_FOUND),
+        })
+        .await;
+        let store: Arc<dyn SettlementStore> = Arc::new(MemoryStore::new());
+        let chain = SolanaChain::new(SolanaConfig { rpc_url, ..test_config() })
+            .unwrap()
+            .with_store(Arc::clone(&store));
+        let logs = vec![
+            format!("Program {} invoke [1]", PROGRAM_ID),
+            legacy_event_log("0x123", 100, 7),
+            format!("Program {} success", PROGRAM_ID),
+        ];
+        let malformed = SolanaChain::parse_settlement_events(&program_id(), &logs, "not-a-signature", 94, None).remove(0);
+        let instruction = SolanaChain::parse_settlement_events(&program_id(), &logs, &signature, 95, None).remove(0);
+        store.hold_for_finality(&malformed, 94).await.unwrap();
+        store.hold_for_finality(&instruction, 95).await.unwrap();
+
+        let (events, mut receiver) = mpsc::channel(EVENT_STREAM_BUFFER);
+        let (released, _) = tokio::join!(chain.release_final_events(&events), async move {
+            if let Some(SourceEvent::Settlement(_, ack)) = receiver.recv().await {
+                ack.ack();
+            }
+        });
+        released.unwrap();
+        let state = |id| {
+            let store = Arc::clone(&store);
+            async move { store.get_finality(id).await.unwrap().unwrap().state }
+        };
+        assert_eq!(state(malformed.id).await, FinalityState::Retracted);
+        assert_eq!(state(instruction.id).await, FinalityState::Confirmed);
+    }
+
+    #[tokio::test]
+    async fn test_tip_and_statuses_come_from_one_endpoint() {
+        let signature = slot_signature(95);
+        // The primary has finalized past the event but cannot serve statuses
+        let primary = serve_rpc(|method, _| match method {
+            "getSlot" => Ok(json!(100)),
+            _ => Err(axum::http::StatusCode::SERVICE_UNAVAILABLE),
+        })
+        .await;
+        // The fallback lags behind and has not seen the transaction yet
+        let lagging = serve_rpc(|method, _| match method {
+            "getSlot" => Ok(json!(90)),
+            "getSignatureStatuses" => Ok(json!({ "context": { "slot": 90 }, "value": [null] })),
+            _ => Err(axum::http::StatusCode::NOT_FOUND),
+        })
+        .await;
+        let store: Arc<dyn SettlementStore> = Arc::new(MemoryStore::new());
+        let chain = SolanaChain::new(SolanaConfig {
+            rpc_url: primary,
+            fallback_rpc_urls: vec![lagging],
+            ..test_config()
+        })
+        .unwrap()
+        .with_store(Arc::clone(&store));
+        let logs = vec![
+            format!("Program {} invoke [1]", PROGRAM_ID),
+            legacy_event_log("0x123", 100, 7),
+            format!("Program {} success", PROGRAM_ID),
+        ];
+        let instruction = SolanaChain::parse_settlement_events(&program_id(), &logs, &signature, 95, None).remove(0);
+        store.hold_for_finality(&instruction, 95).await.unwrap();
+
+        // Read against the primary's tip the missing status would retract it
+        let (events, _receiver) = mpsc::channel(EVENT_STREAM_BUFFER);
+        chain.release_final_events(&events).await.unwrap();
+        assert_eq!(store.get_finality(instruction.id).await.unwrap().unwrap().state, FinalityState::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_quorum_decides_release() {
+        let signature = slot_signature(95);
+        let logs = |nonce| {
+            vec![
+                format!("Program {} invoke [1]", PROGRAM_ID),
+                legacy_event_log("0x123", 100, nonce),
+                format!("Program {} success", PROGRAM_ID),
+            ]
+        };
+        let endpoint = |logs: Option<Vec<String>>| {
+            serve_rpc(move |method, _| match method {
+                "getTransaction" => Ok(logs.as_ref().map_or(serde_json::Value::Null, |logs| json!({ "meta": { "logMessages": logs } }))),
+                _ => Err(axum::http::StatusCode::NOT_FOUND),
+            })
+        };
+        let honest = endpoint(Some(logs(7))).await;
+        let also_honest = endpoint(Some(logs(7))).await;
+        let forged = endpoint(Some(logs(8))).await;
+        let lagging = endpoint(None).await;
+
+        let chain = |urls: [&String; 2]| {
+            SolanaChain::new(SolanaConfig {
+                rpc_url: urls[0].clone(),
+                fallback_rpc_urls: vec![urls[1].clone()],
+                rpc_quorum: Some(2),
+                ..test_config()
+            })
+            .unwrap()
+        };
+        let instruction = SolanaChain::parse_settlement_events(&program_id(), &logs(7), &signature, 95, None).remove(0);
+
+        assert_eq!(chain([&honest, &also_honest]).quorum_verdict(&instruction).await.unwrap(), FinalityVerdict::Release);
+        // Endpoints that disagree or have not caught up hold the event back
+        assert_eq!(chain([&honest, &forged]).quorum_verdict(&instruction).await.unwrap(), FinalityVerdict::Hold);
+        assert_eq!(chain([&honest, &lagging]).quorum_verdict(&instruction).await.unwrap(), FinalityVerdict::Hold);
+        // Endpoints agreeing on logs without the event retract it
+        let forged_instruction = SolanaChain::parse_settlement_events(&program_id(), &logs(8), &signature, 95, None).remove(0);
+        assert!(matches!(
+            chain([&honest, &also_honest]).quorum_verdict(&forged_instruction).await.unwrap(),
+            FinalityVerdict::Retract(_)
+        ));
+    }
+
+    /// Accept one PubSub connection on the mock server, confirm its
+    /// `logsSubscribe`, push `notifications` and then drop the connection
+    async fn serve_log_subscription(listener: &tokio::net::TcpListener, notifications: Vec<serde_json::Value>) {
+        use futures_util::SinkExt;
+        use serde_json::json;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+        let request: serde_json::Value = loop {
+            if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                break serde_json::from_str(&text).unwrap();
+            }
+        };
+        assert_eq!(request["method"], "logsSubscribe");
+        assert_eq!(request["params"][0]["mentions"][0], PROGRAM_ID);
+        let confirmation = json!({ "jsonrpc": "2.0", "result": 7, "id": request["id"] });
+        socket.send(Message::Text(confirmation.to_string())).await.unwrap();
+
+        for value in notifications {
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "logsNotification",
+                "params": {
+                    "result": { "context": { "slot": 12345 }, "value": value },
+                    "subscription": 7,
+                },
+            });
+            socket.send(Message::Text(notification.to_string())).await.unwrap();
+        }
+        socket.close(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_log_subscription_streams_and_reconnects() {
+        use serde_json::json;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let store: Arc<dyn SettlementStore> = Arc::new(MemoryStore::new());
+        let chain = SolanaChain::new(test_config())
+            .unwrap()
+            .with_store(Arc::clone(&store))
+            .with_poll_retry(RetryPolicy {
+                max_attempts: 1,
+                base_delay_ms: 10,
+                max_delay_ms: 10,
+                jitter: 0.0,
+            });
+        let subscribed = Arc::new(AtomicBool::new(false));
+        let reconcile = Arc::new(Notify::new());
+        tokio::spawn(chain.run_log_subscription(ws_url, Arc::clone(&subscribed), Arc::clone(&reconcile)));
+
+        let logs = |nonce| {
+            vec![
+                format!("Program {} invoke [1]", PROGRAM_ID),
+                legacy_event_log("0x123", 100, nonce),
+                format!("Program {} success", PROGRAM_ID),
+            ]
+        };
+
+        // Streamed events wait in the pending-finality buffer rather than going straight out
+        let held = |count: usize| {
+            let store = Arc::clone(&store);
+            async move {
+                loop {
+                    let pending = store.pending_finality().await.unwrap();
+                    if pending.len() >= count {
+                        return pending;
+                    }
+                    sleep(Duration::from_millis(10)).await;
+                }
+            }
+        };
+
+        // A failed transaction is skipped; the next one is held as it arrives
+        serve_log_subscription(&listener, vec![
+            json!({ "signature": "sig_failed", "err": { "InstructionError": [0, { "Custom": 1 }] }, "logs": logs(1) }),
+            json!({ "signature": "sig_1", "err": null, "logs": logs(2) }),
+        ]).await;
+        let pending = tokio::time::timeout(Duration::from_secs(5), held(1)).await.unwrap();
+        assert_eq!(pending[0].instruction.source_tx_hash.0, "sig_1");
+        assert_eq!(pending[0].instruction.nonce, 2);
+        assert_eq!(pending[0].slot, 12345);
+
+        // After the server drops the connection the listener reconnects and resubscribes
+        serve_log_subscription(&listener, vec![
+            json!({ "signature": "sig_2", "err": null, "logs": logs(3) }),
+        ]).await;
+        let pending = tokio::time::timeout(Duration::from_secs(5), held(2)).await.unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[1].instruction.source_tx_hash.0, "sig_2");
+        assert_eq!(pending[1].instruction.nonce, 3);
+        assert_eq!(pending[1].state, FinalityState::Pending);
+
+        // Each (re)subscribe asks the poll loop to reconcile
+        tokio::time::timeout(Duration::from_secs(5), reconcile.notified()).await.unwrap();
+    }
+}
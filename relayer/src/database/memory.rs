@@ -0,0 +1,596 @@
+use super::{
+    duration_millis, ingest_actor, now_millis, parse_millis, result_reason, DatabaseStatistics,
+    DeadLetter, FinalityRecord, QueueLease, SettlementAttempt, SettlementStore, SourceCursor,
+    StatusTransition, PENDING_STATUSES, TERMINAL_STATUSES,
+};
+use crate::types::{
+    FinalityState, GasOverrides, SettlementError, SettlementInstruction, SettlementResult,
+    SettlementStatus,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::{collections::HashMap, time::Duration};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// In-memory settlement store for tests and local demos. Nothing survives a restart.
+#[derive(Default)]
+pub struct MemoryStore {
+    state: RwLock<MemoryState>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    settlements: HashMap<Uuid, (SettlementInstruction, SettlementResult)>,
+    queue: HashMap<Uuid, QueueEntry>,
+    next_sequence: u64,
+    attempts: HashMap<Uuid, Vec<SettlementAttempt>>,
+    dead_letters: HashMap<Uuid, DeadLetterEntry>,
+    timelines: HashMap<Uuid, Vec<StatusTransition>>,
+    cursors: HashMap<String, SourceCursor>,
+    finality: HashMap<Uuid, FinalityRecord>,
+}
+
+/// Work queue bookkeeping, times in unix milliseconds
+struct QueueEntry {
+    sequence: u64,
+    available_at: i64,
+    lease_owner: Option<String>,
+    lease_expires_at: Option<i64>,
+    attempts: u32,
+    gas_overrides: GasOverrides,
+}
+
+struct DeadLetterEntry {
+    error_class: String,
+    last_error: String,
+    dead_lettered_at: DateTime<Utc>,
+}
+
+impl QueueEntry {
+    fn is_available(&self, now: i64) -> bool {
+        self.available_at <= now && self.lease_expires_at.is_none_or(|expires| expires <= now)
+    }
+
+    fn is_leased_by(&self, worker_id: &str) -> bool {
+        self.lease_owner.as_deref() == Some(worker_id)
+    }
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stored settlements matching `filter`, oldest first
+    async fn collect_sorted<F>(&self, filter: F) -> Vec<(SettlementInstruction, SettlementResult)>
+    where
+        F: Fn(&SettlementResult) -> bool,
+    {
+        let state = self.state.read().await;
+        let mut matching: Vec<_> = state
+            .settlements
+            .values()
+            .filter(|(_, result)| filter(result))
+            .cloned()
+            .collect();
+        matching.sort_by_key(|(instruction, _)| instruction.created_at);
+        matching
+    }
+}
+
+impl MemoryState {
+    fn insert_instruction(&mut self, instruction: &SettlementInstruction) {
+        if self.settlements.contains_key(&instruction.id) {
+            return;
+        }
+
+        let result = SettlementResult::pending(instruction.id);
+        self.timelines.insert(
+            instruction.id,
+            vec![StatusTransition {
+                from: None,
+                to: SettlementStatus::Pending,
+                actor: ingest_actor(instruction),
+                reason: None,
+                at: result.processed_at,
+            }],
+        );
+        self.settlements.insert(instruction.id, (instruction.clone(), result));
+    }
+
+    /// Check that the settlement can move to `to` and append the move to its timeline.
+    ///
+    /// Returns the result to update; the caller sets the new status on it.
+    fn record_transition(
+        &mut self,
+        instruction_id: Uuid,
+        to: &SettlementStatus,
+        actor: &str,
+        reason: Option<String>,
+    ) -> Result<&mut SettlementResult, SettlementError> {
+        let Some((_, result)) = self.settlements.get_mut(&instruction_id) else {
+            return Err(SettlementError::DatabaseError(format!(
+                "Unknown settlement instruction: {}",
+                instruction_id
+            )));
+        };
+
+        let from = result.status.clone();
+        from.transition(to.clone())?;
+        self.timelines.entry(instruction_id).or_default().push(StatusTransition {
+            from: Some(from),
+            to: to.clone(),
+            actor: actor.to_string(),
+            reason,
+            at: Utc::now(),
+        });
+        Ok(result)
+    }
+
+    /// Move a settlement to `status`, keeping the rest of its latest result
+    fn set_status(
+        &mut self,
+        instruction_id: Uuid,
+        status: SettlementStatus,
+        actor: &str,
+        reason: Option<String>,
+    ) -> Result<(), SettlementError> {
+        let result = self.record_transition(instruction_id, &status, actor, reason)?;
+        result.status = status;
+        result.processed_at = Utc::now();
+        Ok(())
+    }
+
+    /// Add an instruction to the work queue unless it is already there
+    fn push_queue(&mut self, instruction_id: Uuid, gas_overrides: GasOverrides) -> bool {
+        if self.queue.contains_key(&instruction_id) {
+            return false;
+        }
+
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.queue.insert(
+            instruction_id,
+            QueueEntry {
+                sequence,
+                available_at: now_millis(),
+                lease_owner: None,
+                lease_expires_at: None,
+                attempts: 0,
+                gas_overrides,
+            },
+        );
+        true
+    }
+
+    fn dead_letter(&self, instruction_id: Uuid) -> Option<DeadLetter> {
+        let entry = self.dead_letters.get(&instruction_id)?;
+        Some(DeadLetter {
+            instruction: self.settlements[&instruction_id].0.clone(),
+            error_class: entry.error_class.clone(),
+            last_error: entry.last_error.clone(),
+            dead_lettered_at: entry.dead_lettered_at,
+            attempts: self.attempts.get(&instruction_id).cloned().unwrap_or_default(),
+        })
+    }
+}
+
+#[async_trait]
+impl SettlementStore for MemoryStore {
+    async fn store_instruction(
+        &self,
+        instruction: &SettlementInstruction,
+    ) -> Result<(), SettlementError> {
+        self.state.write().await.insert_instruction(instruction);
+        Ok(())
+    }
+
+    async fn store_result(
+        &self,
+        result: &SettlementResult,
+        actor: &str,
+    ) -> Result<(), SettlementError> {
+        let mut state = self.state.write().await;
+        let stored = state.record_transition(
+            result.instruction_id,
+            &result.status,
+            actor,
+            result_reason(result),
+        )?;
+        *stored = result.clone();
+        Ok(())
+    }
+
+    async fn transition_status(
+        &self,
+        instruction_id: Uuid,
+        status: SettlementStatus,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> Result<(), SettlementError> {
+        self.state.write().await.set_status(
+            instruction_id,
+            status,
+            actor,
+            reason.map(str::to_string),
+        )
+    }
+
+    async fn get_timeline(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Vec<StatusTransition>, SettlementError> {
+        Ok(self
+            .state
+            .read()
+            .await
+            .timelines
+            .get(&instruction_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn get_settlement(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Option<(SettlementInstruction, SettlementResult)>, SettlementError> {
+        Ok(self.state.read().await.settlements.get(&instruction_id).cloned())
+    }
+
+    async fn get_pending_instructions(&self) -> Result<Vec<SettlementInstruction>, SettlementError> {
+        Ok(self
+            .collect_sorted(|result| PENDING_STATUSES.contains(&result.status))
+            .await
+            .into_iter()
+            .map(|(instruction, _)| instruction)
+            .collect())
+    }
+
+    async fn get_instructions_by_status(
+        &self,
+        status: SettlementStatus,
+        limit: Option<u32>,
+    ) -> Result<Vec<(SettlementInstruction, SettlementResult)>, SettlementError> {
+        let mut matching = self.collect_sorted(|result| result.status == status).await;
+        if let Some(limit) = limit {
+            matching.truncate(limit as usize);
+        }
+        Ok(matching)
+    }
+
+    async fn get_statistics(&self) -> Result<DatabaseStatistics, SettlementError> {
+        let state = self.state.read().await;
+        let mut stats = DatabaseStatistics {
+            total_instructions: state.settlements.len() as u64,
+            ..Default::default()
+        };
+
+        for (instruction, result) in state.settlements.values() {
+            match result.status {
+                SettlementStatus::Completed => {
+                    stats.completed_settlements += 1;
+                    stats.total_volume += instruction.amount;
+                }
+                SettlementStatus::Failed | SettlementStatus::Discarded => {
+                    stats.failed_settlements += 1
+                }
+                SettlementStatus::DeadLettered => stats.dead_lettered_settlements += 1,
+                _ => stats.pending_settlements += 1,
+            }
+        }
+
+        Ok(stats)
+    }
+
+    async fn enqueue_instruction(
+        &self,
+        instruction: &SettlementInstruction,
+    ) -> Result<bool, SettlementError> {
+        let mut state = self.state.write().await;
+        state.insert_instruction(instruction);
+
+        if TERMINAL_STATUSES.contains(&state.settlements[&instruction.id].1.status) {
+            return Ok(false);
+        }
+        Ok(state.push_queue(instruction.id, GasOverrides::default()))
+    }
+
+    async fn lease_instructions(
+        &self,
+        worker_id: &str,
+        max_items: usize,
+        lease_duration: Duration,
+    ) -> Result<Vec<QueueLease>, SettlementError> {
+        let mut state = self.state.write().await;
+        let now = now_millis();
+        let leased_until = now.saturating_add(duration_millis(lease_duration));
+
+        let mut available: Vec<_> = state
+            .queue
+            .iter()
+            .filter(|(_, entry)| entry.is_available(now))
+            .map(|(id, entry)| (entry.sequence, *id))
+            .collect();
+        available.sort_unstable();
+        available.truncate(max_items);
+
+        let mut leases = Vec::with_capacity(available.len());
+        for (_, id) in available {
+            let (attempts, gas_overrides) = {
+                let entry = state.queue.get_mut(&id).expect("queued instruction");
+                entry.lease_owner = Some(worker_id.to_string());
+                entry.lease_expires_at = Some(leased_until);
+                entry.attempts += 1;
+                (entry.attempts, entry.gas_overrides)
+            };
+
+            leases.push(QueueLease {
+                instruction: state.settlements[&id].0.clone(),
+                worker_id: worker_id.to_string(),
+                leased_until: parse_millis(leased_until),
+                attempts,
+                gas_overrides,
+            });
+        }
+
+        Ok(leases)
+    }
+
+    async fn ack_instruction(
+        &self,
+        instruction_id: Uuid,
+        worker_id: &str,
+    ) -> Result<bool, SettlementError> {
+        let mut state = self.state.write().await;
+        match state.queue.get(&instruction_id) {
+            Some(entry) if entry.is_leased_by(worker_id) => {
+                state.queue.remove(&instruction_id);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release_instruction(
+        &self,
+        instruction_id: Uuid,
+        worker_id: &str,
+        delay: Duration,
+    ) -> Result<bool, SettlementError> {
+        let mut state = self.state.write().await;
+        match state.queue.get_mut(&instruction_id) {
+            Some(entry) if entry.is_leased_by(worker_id) => {
+                entry.lease_owner = None;
+                entry.lease_expires_at = None;
+                entry.available_at = now_millis().saturating_add(duration_millis(delay));
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn queue_depth(&self) -> Result<u64, SettlementError> {
+        Ok(self.state.read().await.queue.len() as u64)
+    }
+
+    async fn record_attempt(
+        &self,
+        result: &SettlementResult,
+        error_class: Option<&str>,
+    ) -> Result<u32, SettlementError> {
+        let mut state = self.state.write().await;
+        if !state.settlements.contains_key(&result.instruction_id) {
+            return Err(SettlementError::DatabaseError(format!(
+                "Unknown settlement instruction: {}",
+                result.instruction_id
+            )));
+        }
+
+        let history = state.attempts.entry(result.instruction_id).or_default();
+        let attempt = history.len() as u32 + 1;
+        history.push(SettlementAttempt {
+            attempt,
+            status: result.status.clone(),
+            error_class: error_class.map(str::to_string),
+            error_message: result.error_message.clone(),
+            recorded_at: result.processed_at,
+        });
+        Ok(attempt)
+    }
+
+    async fn get_attempts(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Vec<SettlementAttempt>, SettlementError> {
+        Ok(self
+            .state
+            .read()
+            .await
+            .attempts
+            .get(&instruction_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn dead_letter_instruction(
+        &self,
+        instruction_id: Uuid,
+        error_class: &str,
+        last_error: &str,
+        actor: &str,
+    ) -> Result<bool, SettlementError> {
+        let mut state = self.state.write().await;
+        if state.dead_letters.contains_key(&instruction_id)
+            || !state.settlements.contains_key(&instruction_id)
+        {
+            return Ok(false);
+        }
+
+        state.set_status(
+            instruction_id,
+            SettlementStatus::DeadLettered,
+            actor,
+            Some(format!("{}: {}", error_class, last_error)),
+        )?;
+
+        state.queue.remove(&instruction_id);
+        state.dead_letters.insert(
+            instruction_id,
+            DeadLetterEntry {
+                error_class: error_class.to_string(),
+                last_error: last_error.to_string(),
+                dead_lettered_at: Utc::now(),
+            },
+        );
+        Ok(true)
+    }
+
+    async fn list_dead_letters(&self, limit: Option<u32>) -> Result<Vec<DeadLetter>, SettlementError> {
+        let state = self.state.read().await;
+        let mut dead_letters: Vec<_> = state
+            .dead_letters
+            .keys()
+            .filter_map(|id| state.dead_letter(*id))
+            .collect();
+        dead_letters.sort_by_key(|dead_letter| dead_letter.dead_lettered_at);
+        if let Some(limit) = limit {
+            dead_letters.truncate(limit as usize);
+        }
+        Ok(dead_letters)
+    }
+
+    async fn get_dead_letter(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Option<DeadLetter>, SettlementError> {
+        Ok(self.state.read().await.dead_letter(instruction_id))
+    }
+
+    async fn requeue_dead_letter(
+        &self,
+        instruction_id: Uuid,
+        gas_overrides: GasOverrides,
+        actor: &str,
+    ) -> Result<bool, SettlementError> {
+        let mut state = self.state.write().await;
+        if !state.dead_letters.contains_key(&instruction_id) {
+            return Ok(false);
+        }
+
+        let result = state.record_transition(
+            instruction_id,
+            &SettlementStatus::Pending,
+            actor,
+            Some("Requeued from dead-letter queue".to_string()),
+        )?;
+        *result = SettlementResult::pending(instruction_id);
+        state.dead_letters.remove(&instruction_id);
+        state.push_queue(instruction_id, gas_overrides);
+        Ok(true)
+    }
+
+    async fn discard_dead_letter(
+        &self,
+        instruction_id: Uuid,
+        actor: &str,
+    ) -> Result<bool, SettlementError> {
+        let mut state = self.state.write().await;
+        if !state.dead_letters.contains_key(&instruction_id) {
+            return Ok(false);
+        }
+
+        state.set_status(
+            instruction_id,
+            SettlementStatus::Discarded,
+            actor,
+            Some("Discarded from dead-letter queue".to_string()),
+        )?;
+        state.dead_letters.remove(&instruction_id);
+        Ok(true)
+    }
+
+    async fn get_source_cursor(
+        &self,
+        source: &str,
+    ) -> Result<Option<SourceCursor>, SettlementError> {
+        Ok(self.state.read().await.cursors.get(source).cloned())
+    }
+
+    async fn save_source_cursor(
+        &self,
+        source: &str,
+        signature: &str,
+        slot: u64,
+    ) -> Result<(), SettlementError> {
+        let cursor = SourceCursor {
+            signature: signature.to_string(),
+            slot,
+            updated_at: Utc::now(),
+        };
+        self.state.write().await.cursors.insert(source.to_string(), cursor);
+        Ok(())
+    }
+
+    async fn hold_for_finality(
+        &self,
+        instruction: &SettlementInstruction,
+        slot: u64,
+    ) -> Result<bool, SettlementError> {
+        let mut state = self.state.write().await;
+        // Only a retracted event is held again
+        let held = state.finality.get(&instruction.id);
+        if held.is_some_and(|record| record.state != FinalityState::Retracted) {
+            return Ok(false);
+        }
+        state.finality.insert(
+            instruction.id,
+            FinalityRecord {
+                instruction: instruction.clone(),
+                slot,
+                state: FinalityState::Pending,
+                reason: None,
+                held_at: Utc::now(),
+                decided_at: None,
+            },
+        );
+        Ok(true)
+    }
+
+    async fn pending_finality(&self) -> Result<Vec<FinalityRecord>, SettlementError> {
+        let state = self.state.read().await;
+        let mut pending: Vec<_> = state
+            .finality
+            .values()
+            .filter(|record| record.state == FinalityState::Pending)
+            .cloned()
+            .collect();
+        pending.sort_by_key(|record| (record.held_at, record.slot));
+        Ok(pending)
+    }
+
+    async fn decide_finality(
+        &self,
+        instruction_id: Uuid,
+        decision: FinalityState,
+        reason: &str,
+    ) -> Result<bool, SettlementError> {
+        let mut state = self.state.write().await;
+        match state.finality.get_mut(&instruction_id) {
+            Some(record) if record.state == FinalityState::Pending => {
+                record.state = decision;
+                record.reason = Some(reason.to_string());
+                record.decided_at = Some(Utc::now());
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn get_finality(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Option<FinalityRecord>, SettlementError> {
+        Ok(self.state.read().await.finality.get(&instruction_id).cloned())
+    }
+}
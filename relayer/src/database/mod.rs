@@ -0,0 +1,411 @@
+//! Settlement storage backends
+//!
+//! The relayer talks to storage through the [`SettlementStore`] trait. The
+//! backend is picked from the scheme of `DatabaseConfig.url`:
+//!
+//! - `memory:` keeps everything in process memory (tests, local demos)
+//! - `sqlite:` (or `:memory:`) uses an embedded SQLite database
+//! - `postgres://` / `postgresql://` uses Postgres (requires the `postgres` feature)
+
+pub mod memory;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod sqlite;
+
+use crate::types::{
+    DatabaseConfig, FinalityState, GasOverrides, SettlementError, SettlementInstruction,
+    SettlementResult, SettlementStatus,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tracing::info;
+use uuid::Uuid;
+
+pub use memory::MemoryStore;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresStore;
+pub use sqlite::SqliteStore;
+
+/// Statuses that still need to be driven to completion after a restart
+pub(crate) const PENDING_STATUSES: [SettlementStatus; 3] = [
+    SettlementStatus::Pending,
+    SettlementStatus::Processing,
+    SettlementStatus::Retrying,
+];
+
+/// Statuses after which an instruction is never queued again
+pub(crate) const TERMINAL_STATUSES: [SettlementStatus; 3] = [
+    SettlementStatus::Completed,
+    SettlementStatus::DeadLettered,
+    SettlementStatus::Discarded,
+];
+
+/// Aggregate settlement statistics
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct DatabaseStatistics {
+    pub total_instructions: u64,
+    pub pending_settlements: u64,
+    pub completed_settlements: u64,
+    pub failed_settlements: u64, // Failed or discarded
+    pub dead_lettered_settlements: u64,
+    pub total_volume: u64, // Completed amount in smallest unit (e.g., micro USDC)
+}
+
+impl DatabaseStatistics {
+    pub fn total_volume_usdc(&self) -> f64 {
+        self.total_volume as f64 / 1_000_000.0
+    }
+}
+
+/// An instruction leased from the work queue by a single worker.
+///
+/// The lease must be acknowledged before `leased_until`; otherwise the
+/// instruction becomes available to other workers again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueLease {
+    pub instruction: SettlementInstruction,
+    pub worker_id: String,
+    pub leased_until: DateTime<Utc>,
+    pub attempts: u32,
+    /// Gas settings an operator attached when requeueing from the dead-letter queue
+    pub gas_overrides: GasOverrides,
+}
+
+/// One processing round of a settlement, kept as history for the dead-letter queue
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SettlementAttempt {
+    pub attempt: u32,
+    pub status: SettlementStatus,
+    pub error_class: Option<String>,
+    pub error_message: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// One entry in a settlement's audit timeline
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatusTransition {
+    /// `None` for the transition that created the settlement
+    pub from: Option<SettlementStatus>,
+    pub to: SettlementStatus,
+    /// Who made the change: a worker ID, `cli`, `admin-api`, ...
+    pub actor: String,
+    pub reason: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+/// A settlement parked for operator review
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeadLetter {
+    pub instruction: SettlementInstruction,
+    pub error_class: String,
+    pub last_error: String,
+    pub dead_lettered_at: DateTime<Utc>,
+    /// Oldest attempt first
+    pub attempts: Vec<SettlementAttempt>,
+}
+
+/// How far a source-chain listener has read, so a restart resumes where it stopped
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SourceCursor {
+    /// Newest source transaction whose events have been handed off
+    pub signature: String,
+    pub slot: u64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A source event held until it is final, with the decision taken on it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FinalityRecord {
+    pub instruction: SettlementInstruction,
+    /// Source slot the event was seen in
+    pub slot: u64,
+    pub state: FinalityState,
+    pub reason: Option<String>,
+    pub held_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+/// Trait for persisting settlement instructions and their results
+#[async_trait]
+pub trait SettlementStore: Send + Sync {
+    /// Store a new instruction with a pending result.
+    ///
+    /// Storing an instruction that already exists is a no-op, so requeued
+    /// instructions keep their current status until a new result is stored.
+    /// New instructions start their timeline with a transition to `Pending`.
+    async fn store_instruction(
+        &self,
+        instruction: &SettlementInstruction,
+    ) -> Result<(), SettlementError>;
+
+    /// Record the latest processing result for a stored instruction.
+    ///
+    /// Fails with `InvalidTransition` if the current status cannot move to
+    /// `result.status`; the move is added to the timeline otherwise.
+    async fn store_result(&self, result: &SettlementResult, actor: &str)
+        -> Result<(), SettlementError>;
+
+    /// Move a settlement to `status` without replacing the rest of its result
+    async fn transition_status(
+        &self,
+        instruction_id: Uuid,
+        status: SettlementStatus,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> Result<(), SettlementError>;
+
+    /// Every status change of a settlement, oldest first
+    async fn get_timeline(&self, instruction_id: Uuid)
+        -> Result<Vec<StatusTransition>, SettlementError>;
+
+    /// A stored instruction and its latest result
+    async fn get_settlement(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Option<(SettlementInstruction, SettlementResult)>, SettlementError>;
+
+    /// Instructions that have not reached a terminal status, oldest first
+    async fn get_pending_instructions(&self) -> Result<Vec<SettlementInstruction>, SettlementError>;
+
+    /// Instructions whose latest result has the given status, oldest first
+    async fn get_instructions_by_status(
+        &self,
+        status: SettlementStatus,
+        limit: Option<u32>,
+    ) -> Result<Vec<(SettlementInstruction, SettlementResult)>, SettlementError>;
+
+    /// Aggregate counts and completed volume across all instructions
+    async fn get_statistics(&self) -> Result<DatabaseStatistics, SettlementError>;
+
+    /// Store the instruction (if new) and add it to the work queue in one step.
+    ///
+    /// Returns `false` if the instruction was already queued or its settlement
+    /// reached a terminal status, so duplicate events never reach the chain twice.
+    async fn enqueue_instruction(
+        &self,
+        instruction: &SettlementInstruction,
+    ) -> Result<bool, SettlementError>;
+
+    /// Lease up to `max_items` available instructions, oldest first.
+    ///
+    /// An instruction is available when it is not leased or its lease has expired.
+    async fn lease_instructions(
+        &self,
+        worker_id: &str,
+        max_items: usize,
+        lease_duration: Duration,
+    ) -> Result<Vec<QueueLease>, SettlementError>;
+
+    /// Remove a processed instruction from the queue.
+    ///
+    /// Returns `false` if the worker no longer holds the lease.
+    async fn ack_instruction(
+        &self,
+        instruction_id: Uuid,
+        worker_id: &str,
+    ) -> Result<bool, SettlementError>;
+
+    /// Give up a lease so the instruction becomes available again after `delay`.
+    ///
+    /// Returns `false` if the worker no longer holds the lease.
+    async fn release_instruction(
+        &self,
+        instruction_id: Uuid,
+        worker_id: &str,
+        delay: Duration,
+    ) -> Result<bool, SettlementError>;
+
+    /// Number of instructions in the work queue, leased or not
+    async fn queue_depth(&self) -> Result<u64, SettlementError>;
+
+    /// Append a processing round to the instruction's attempt history.
+    ///
+    /// Returns the attempt number, starting at 1.
+    async fn record_attempt(
+        &self,
+        result: &SettlementResult,
+        error_class: Option<&str>,
+    ) -> Result<u32, SettlementError>;
+
+    /// Attempt history for an instruction, oldest first
+    async fn get_attempts(&self, instruction_id: Uuid)
+        -> Result<Vec<SettlementAttempt>, SettlementError>;
+
+    /// Move a failed instruction to the dead-letter queue and out of the work queue.
+    ///
+    /// Returns `false` if the instruction is unknown or already dead-lettered.
+    async fn dead_letter_instruction(
+        &self,
+        instruction_id: Uuid,
+        error_class: &str,
+        last_error: &str,
+        actor: &str,
+    ) -> Result<bool, SettlementError>;
+
+    /// Dead-lettered settlements, oldest first
+    async fn list_dead_letters(&self, limit: Option<u32>) -> Result<Vec<DeadLetter>, SettlementError>;
+
+    /// A single dead-lettered settlement with its attempt history
+    async fn get_dead_letter(&self, instruction_id: Uuid)
+        -> Result<Option<DeadLetter>, SettlementError>;
+
+    /// Put a dead-lettered settlement back on the work queue as pending.
+    ///
+    /// Returns `false` if the instruction is not dead-lettered.
+    async fn requeue_dead_letter(
+        &self,
+        instruction_id: Uuid,
+        gas_overrides: GasOverrides,
+        actor: &str,
+    ) -> Result<bool, SettlementError>;
+
+    /// Permanently drop a dead-lettered settlement, marking it discarded.
+    ///
+    /// Returns `false` if the instruction is not dead-lettered.
+    async fn discard_dead_letter(&self, instruction_id: Uuid, actor: &str)
+        -> Result<bool, SettlementError>;
+
+    /// Checkpoint saved for a source-chain listener, e.g. `solana:<program id>`
+    async fn get_source_cursor(&self, source: &str)
+        -> Result<Option<SourceCursor>, SettlementError>;
+
+    /// Save the listener's checkpoint, replacing any previous one
+    async fn save_source_cursor(
+        &self,
+        source: &str,
+        signature: &str,
+        slot: u64,
+    ) -> Result<(), SettlementError>;
+
+    /// Hold a source event until it is final.
+    ///
+    /// A retracted event is held again, since seeing it on chain once more
+    /// means the retraction may have been wrong. Returns `false` if the event
+    /// is already held or was confirmed.
+    async fn hold_for_finality(
+        &self,
+        instruction: &SettlementInstruction,
+        slot: u64,
+    ) -> Result<bool, SettlementError>;
+
+    /// Events still waiting for finality, oldest first
+    async fn pending_finality(&self) -> Result<Vec<FinalityRecord>, SettlementError>;
+
+    /// Record the decision on a held event.
+    ///
+    /// Returns `false` if the event is not pending.
+    async fn decide_finality(
+        &self,
+        instruction_id: Uuid,
+        state: FinalityState,
+        reason: &str,
+    ) -> Result<bool, SettlementError>;
+
+    /// Finality record of a source event, pending or decided
+    async fn get_finality(&self, instruction_id: Uuid)
+        -> Result<Option<FinalityRecord>, SettlementError>;
+}
+
+/// Open the store selected by the scheme of `config.url`
+pub async fn connect(config: &DatabaseConfig) -> Result<Arc<dyn SettlementStore>, SettlementError> {
+    let scheme = config.url.split(':').next().unwrap_or_default();
+
+    let store: Arc<dyn SettlementStore> = match scheme {
+        "memory" => Arc::new(MemoryStore::new()),
+        // A bare ":memory:" URL has an empty scheme and is handled by SQLite
+        "sqlite" | "" => Arc::new(SqliteStore::new(config).await?),
+        #[cfg(feature = "postgres")]
+        "postgres" | "postgresql" => Arc::new(PostgresStore::new(config).await?),
+        #[cfg(not(feature = "postgres"))]
+        "postgres" | "postgresql" => {
+            return Err(SettlementError::ConfigError(
+                "Postgres database URLs require the `postgres` feature".to_string(),
+            ))
+        }
+        other => {
+            return Err(SettlementError::ConfigError(format!(
+                "Unsupported database URL scheme: {}",
+                other
+            )))
+        }
+    };
+
+    info!("Using {} settlement store", if scheme.is_empty() { "sqlite" } else { scheme });
+    Ok(store)
+}
+
+/// Current time as unix milliseconds, the unit used for queue timestamps
+pub(crate) fn now_millis() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+/// Convert a duration to whole milliseconds for queue arithmetic
+pub(crate) fn duration_millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
+
+/// Parse a queue timestamp stored as unix milliseconds
+pub(crate) fn parse_millis(value: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(value).unwrap_or_default()
+}
+
+/// Timeline reason recorded when a result is stored
+pub(crate) fn result_reason(result: &SettlementResult) -> Option<String> {
+    result.error_message.clone().or_else(|| match &result.destination_tx_hash {
+        Some(tx_hash) => Some(format!("Settled in {}", tx_hash)),
+        None if result.status == SettlementStatus::Completed => {
+            Some("Already settled on chain".to_string())
+        }
+        None => None,
+    })
+}
+
+/// Actor recorded for the transition that creates a settlement
+pub(crate) fn ingest_actor(instruction: &SettlementInstruction) -> String {
+    format!("{}-listener", instruction.source_chain)
+}
+
+/// Convert an optional gas setting to the signed integer column type
+pub(crate) fn gas_to_column(value: Option<u64>) -> Option<i64> {
+    value.map(|gas| i64::try_from(gas).unwrap_or(i64::MAX))
+}
+
+/// Parse a settlement ID stored as text
+pub(crate) fn parse_uuid(value: String) -> Result<Uuid, SettlementError> {
+    Uuid::parse_str(&value)
+        .map_err(|e| SettlementError::SerializationError(format!("Invalid UUID {}: {}", value, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_config(url: &str) -> DatabaseConfig {
+        DatabaseConfig {
+            url: url.to_string(),
+            max_connections: 5,
+            connection_timeout_secs: 30,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_selects_backend_from_scheme() {
+        assert!(connect(&create_test_config("memory:")).await.is_ok());
+        assert!(connect(&create_test_config("sqlite::memory:")).await.is_ok());
+        assert!(connect(&create_test_config(":memory:")).await.is_ok());
+
+        match connect(&create_test_config("mysql://localhost/cyrus")).await {
+            Err(SettlementError::ConfigError(_)) => {}
+            other => panic!("Expected config error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    #[tokio::test]
+    async fn test_postgres_requires_feature() {
+        let result = connect(&create_test_config("postgres://localhost/cyrus")).await;
+        assert!(matches!(result, Err(SettlementError::ConfigError(_))));
+    }
+}
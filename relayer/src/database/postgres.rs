@@ -0,0 +1,877 @@
+use super::{
+    duration_millis, gas_to_column, ingest_actor, now_millis, parse_millis, parse_uuid,
+    result_reason, DatabaseStatistics, DeadLetter, FinalityRecord, QueueLease, SettlementAttempt,
+    SettlementStore, SourceCursor, StatusTransition, PENDING_STATUSES, TERMINAL_STATUSES,
+};
+use crate::types::{
+    Address, ChainId, DatabaseConfig, FinalityState, GasOverrides, SettlementError,
+    SettlementInstruction, SettlementResult, SettlementStatus, TransactionHash,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::{
+    migrate::Migrator,
+    postgres::{PgPool, PgPoolOptions, PgRow},
+    Row,
+};
+use std::time::Duration;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+/// Versioned schema migrations, applied in order on startup
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+/// Postgres-backed settlement store
+pub struct PostgresStore {
+    pool: PgPool,
+}
+
+impl PostgresStore {
+    /// Connect to Postgres and apply any outstanding migrations
+    pub async fn new(config: &DatabaseConfig) -> Result<Self, SettlementError> {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(Duration::from_secs(config.connection_timeout_secs))
+            .connect(&config.url)
+            .await?;
+
+        MIGRATOR
+            .run(&pool)
+            .await
+            .map_err(|e| SettlementError::DatabaseError(format!("Migration failed: {}", e)))?;
+
+        info!("Postgres database ready");
+        Ok(Self { pool })
+    }
+
+    /// Insert an instruction with a pending result unless it is already stored
+    async fn insert_instruction(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        instruction: &SettlementInstruction,
+    ) -> Result<(), SettlementError> {
+        let inserted = sqlx::query(
+            "INSERT INTO settlement_instructions (
+                id, source_chain, source_tx_hash, event_index, destination_chain, sender,
+                receiver, token_symbol, amount, nonce, timestamp, payload, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(instruction.id.to_string())
+        .bind(&instruction.source_chain.0)
+        .bind(&instruction.source_tx_hash.0)
+        .bind(i64::from(instruction.event_index))
+        .bind(&instruction.destination_chain.0)
+        .bind(&instruction.sender.0)
+        .bind(&instruction.receiver.0)
+        .bind(&instruction.token_symbol)
+        .bind(instruction.amount as i64)
+        .bind(instruction.nonce as i64)
+        .bind(instruction.timestamp)
+        .bind(&instruction.payload)
+        .bind(instruction.created_at)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
+
+        if inserted > 0 {
+            let result = SettlementResult::pending(instruction.id);
+            Self::upsert_result(tx, &result).await?;
+            sqlx::query(
+                "INSERT INTO settlement_transitions (
+                    instruction_id, from_status, to_status, actor, reason, transitioned_at
+                ) VALUES ($1, NULL, $2, $3, NULL, $4)",
+            )
+            .bind(instruction.id.to_string())
+            .bind(result.status.as_str())
+            .bind(ingest_actor(instruction))
+            .bind(result.processed_at)
+            .execute(&mut **tx)
+            .await?;
+            debug!("Stored settlement instruction: {}", instruction.id);
+        } else {
+            debug!("Settlement instruction already stored: {}", instruction.id);
+        }
+
+        Ok(())
+    }
+
+    /// Insert or replace the result row for an instruction
+    async fn upsert_result(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        result: &SettlementResult,
+    ) -> Result<(), SettlementError> {
+        sqlx::query(
+            "INSERT INTO settlement_results (
+                instruction_id, status, destination_tx_hash, gas_used,
+                error_message, processed_at, retry_count
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (instruction_id) DO UPDATE SET
+                status = excluded.status,
+                destination_tx_hash = excluded.destination_tx_hash,
+                gas_used = excluded.gas_used,
+                error_message = excluded.error_message,
+                processed_at = excluded.processed_at,
+                retry_count = excluded.retry_count",
+        )
+        .bind(result.instruction_id.to_string())
+        .bind(result.status.as_str())
+        .bind(result.destination_tx_hash.as_ref().map(|hash| hash.0.as_str()))
+        .bind(result.gas_used.map(|gas| gas as i64))
+        .bind(&result.error_message)
+        .bind(result.processed_at)
+        .bind(result.retry_count as i64)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Append a status change to the timeline, failing if the move is not allowed
+    ///
+    /// The result row is locked first so concurrent writers see each other's
+    /// transitions in order.
+    async fn record_transition(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        instruction_id: Uuid,
+        to: &SettlementStatus,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> Result<(), SettlementError> {
+        let from: String = sqlx::query_scalar(
+            "SELECT status FROM settlement_results WHERE instruction_id = $1 FOR UPDATE",
+        )
+        .bind(instruction_id.to_string())
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| {
+            SettlementError::DatabaseError(format!(
+                "Unknown settlement instruction: {}",
+                instruction_id
+            ))
+        })?;
+
+        let from: SettlementStatus = from.parse()?;
+        from.transition(to.clone())?;
+
+        sqlx::query(
+            "INSERT INTO settlement_transitions (
+                instruction_id, from_status, to_status, actor, reason, transitioned_at
+            ) VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(instruction_id.to_string())
+        .bind(from.as_str())
+        .bind(to.as_str())
+        .bind(actor)
+        .bind(reason)
+        .bind(Utc::now())
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Set the status of an existing result row
+    async fn update_status(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        instruction_id: Uuid,
+        status: SettlementStatus,
+    ) -> Result<(), SettlementError> {
+        sqlx::query(
+            "UPDATE settlement_results SET status = $1, processed_at = $2 WHERE instruction_id = $3",
+        )
+        .bind(status.as_str())
+        .bind(Utc::now())
+        .bind(instruction_id.to_string())
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Build dead letters from joined instruction rows, loading each attempt history
+    async fn dead_letters_from_rows(&self, rows: &[PgRow]) -> Result<Vec<DeadLetter>, SettlementError> {
+        let mut dead_letters = Vec::with_capacity(rows.len());
+        for row in rows {
+            let instruction = Self::instruction_from_row(row)?;
+            let attempts = self.get_attempts(instruction.id).await?;
+            dead_letters.push(DeadLetter {
+                instruction,
+                error_class: row.try_get("error_class")?,
+                last_error: row.try_get("last_error")?,
+                dead_lettered_at: row.try_get("dead_lettered_at")?,
+                attempts,
+            });
+        }
+
+        Ok(dead_letters)
+    }
+
+    fn instruction_from_row(row: &PgRow) -> Result<SettlementInstruction, SettlementError> {
+        Ok(SettlementInstruction {
+            id: parse_uuid(row.try_get("id")?)?,
+            source_chain: ChainId(row.try_get("source_chain")?),
+            source_tx_hash: TransactionHash(row.try_get("source_tx_hash")?),
+            event_index: row.try_get::<i64, _>("event_index")? as u32,
+            destination_chain: ChainId(row.try_get("destination_chain")?),
+            sender: Address(row.try_get("sender")?),
+            receiver: Address(row.try_get("receiver")?),
+            token_symbol: row.try_get("token_symbol")?,
+            amount: row.try_get::<i64, _>("amount")? as u64,
+            nonce: row.try_get::<i64, _>("nonce")? as u64,
+            timestamp: row.try_get("timestamp")?,
+            payload: row.try_get("payload")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+
+    fn result_from_row(row: &PgRow) -> Result<SettlementResult, SettlementError> {
+        Ok(SettlementResult {
+            instruction_id: parse_uuid(row.try_get("instruction_id")?)?,
+            status: row.try_get::<String, _>("status")?.parse()?,
+            destination_tx_hash: row
+                .try_get::<Option<String>, _>("destination_tx_hash")?
+                .map(TransactionHash),
+            gas_used: row.try_get::<Option<i64>, _>("gas_used")?.map(|gas| gas as u64),
+            error_message: row.try_get("error_message")?,
+            processed_at: row.try_get("processed_at")?,
+            retry_count: row.try_get::<i64, _>("retry_count")? as u32,
+        })
+    }
+
+    fn finality_from_row(row: &PgRow) -> Result<FinalityRecord, SettlementError> {
+        Ok(FinalityRecord {
+            instruction: serde_json::from_str(&row.try_get::<String, _>("instruction")?)?,
+            slot: row.try_get::<i64, _>("slot")? as u64,
+            state: row.try_get::<String, _>("state")?.parse()?,
+            reason: row.try_get("reason")?,
+            held_at: row.try_get("held_at")?,
+            decided_at: row.try_get("decided_at")?,
+        })
+    }
+}
+
+#[async_trait]
+impl SettlementStore for PostgresStore {
+    async fn store_instruction(
+        &self,
+        instruction: &SettlementInstruction,
+    ) -> Result<(), SettlementError> {
+        let mut tx = self.pool.begin().await?;
+        Self::insert_instruction(&mut tx, instruction).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn store_result(
+        &self,
+        result: &SettlementResult,
+        actor: &str,
+    ) -> Result<(), SettlementError> {
+        let mut tx = self.pool.begin().await?;
+        let reason = result_reason(result);
+        Self::record_transition(
+            &mut tx,
+            result.instruction_id,
+            &result.status,
+            actor,
+            reason.as_deref(),
+        )
+        .await?;
+        Self::upsert_result(&mut tx, result).await?;
+        tx.commit().await?;
+
+        debug!(
+            "Stored settlement result: {} ({})",
+            result.instruction_id, result.status
+        );
+        Ok(())
+    }
+
+    async fn transition_status(
+        &self,
+        instruction_id: Uuid,
+        status: SettlementStatus,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> Result<(), SettlementError> {
+        let mut tx = self.pool.begin().await?;
+        Self::record_transition(&mut tx, instruction_id, &status, actor, reason).await?;
+        Self::update_status(&mut tx, instruction_id, status).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_timeline(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Vec<StatusTransition>, SettlementError> {
+        let rows = sqlx::query(
+            "SELECT * FROM settlement_transitions WHERE instruction_id = $1 ORDER BY id ASC",
+        )
+        .bind(instruction_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(StatusTransition {
+                    from: row
+                        .try_get::<Option<String>, _>("from_status")?
+                        .map(|status| status.parse())
+                        .transpose()?,
+                    to: row.try_get::<String, _>("to_status")?.parse()?,
+                    actor: row.try_get("actor")?,
+                    reason: row.try_get("reason")?,
+                    at: row.try_get("transitioned_at")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn get_settlement(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Option<(SettlementInstruction, SettlementResult)>, SettlementError> {
+        let row = sqlx::query(
+            "SELECT i.*, r.instruction_id, r.status, r.destination_tx_hash, r.gas_used,
+                r.error_message, r.processed_at, r.retry_count
+            FROM settlement_instructions i
+            JOIN settlement_results r ON r.instruction_id = i.id
+            WHERE i.id = $1",
+        )
+        .bind(instruction_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| Ok((Self::instruction_from_row(&row)?, Self::result_from_row(&row)?)))
+            .transpose()
+    }
+
+    async fn get_pending_instructions(&self) -> Result<Vec<SettlementInstruction>, SettlementError> {
+        let rows = sqlx::query(
+            "SELECT i.* FROM settlement_instructions i
+            JOIN settlement_results r ON r.instruction_id = i.id
+            WHERE r.status IN ($1, $2, $3)
+            ORDER BY i.created_at ASC",
+        )
+        .bind(PENDING_STATUSES[0].as_str())
+        .bind(PENDING_STATUSES[1].as_str())
+        .bind(PENDING_STATUSES[2].as_str())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::instruction_from_row).collect()
+    }
+
+    async fn get_instructions_by_status(
+        &self,
+        status: SettlementStatus,
+        limit: Option<u32>,
+    ) -> Result<Vec<(SettlementInstruction, SettlementResult)>, SettlementError> {
+        let rows = sqlx::query(
+            "SELECT i.*, r.instruction_id, r.status, r.destination_tx_hash, r.gas_used,
+                r.error_message, r.processed_at, r.retry_count
+            FROM settlement_instructions i
+            JOIN settlement_results r ON r.instruction_id = i.id
+            WHERE r.status = $1
+            ORDER BY i.created_at ASC
+            LIMIT $2",
+        )
+        .bind(status.as_str())
+        .bind(limit.map(i64::from))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| Ok((Self::instruction_from_row(row)?, Self::result_from_row(row)?)))
+            .collect()
+    }
+
+    async fn get_statistics(&self) -> Result<DatabaseStatistics, SettlementError> {
+        let row = sqlx::query(
+            "SELECT
+                COUNT(*) AS total_instructions,
+                COUNT(*) FILTER (WHERE r.status IN ($1, $2, $3)) AS pending,
+                COUNT(*) FILTER (WHERE r.status = $4) AS completed,
+                COUNT(*) FILTER (WHERE r.status IN ($5, $6)) AS failed,
+                COUNT(*) FILTER (WHERE r.status = $7) AS dead_lettered,
+                COALESCE(SUM(i.amount) FILTER (WHERE r.status = $4), 0)::BIGINT AS total_volume
+            FROM settlement_instructions i
+            LEFT JOIN settlement_results r ON r.instruction_id = i.id",
+        )
+        .bind(PENDING_STATUSES[0].as_str())
+        .bind(PENDING_STATUSES[1].as_str())
+        .bind(PENDING_STATUSES[2].as_str())
+        .bind(SettlementStatus::Completed.as_str())
+        .bind(SettlementStatus::Failed.as_str())
+        .bind(SettlementStatus::Discarded.as_str())
+        .bind(SettlementStatus::DeadLettered.as_str())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(DatabaseStatistics {
+            total_instructions: row.try_get::<i64, _>("total_instructions")? as u64,
+            pending_settlements: row.try_get::<i64, _>("pending")? as u64,
+            completed_settlements: row.try_get::<i64, _>("completed")? as u64,
+            failed_settlements: row.try_get::<i64, _>("failed")? as u64,
+            dead_lettered_settlements: row.try_get::<i64, _>("dead_lettered")? as u64,
+            total_volume: row.try_get::<i64, _>("total_volume")? as u64,
+        })
+    }
+
+    async fn enqueue_instruction(
+        &self,
+        instruction: &SettlementInstruction,
+    ) -> Result<bool, SettlementError> {
+        let mut tx = self.pool.begin().await?;
+        Self::insert_instruction(&mut tx, instruction).await?;
+
+        let now = now_millis();
+        let queued = sqlx::query(
+            "INSERT INTO settlement_queue (instruction_id, enqueued_at, available_at)
+            SELECT instruction_id, $1, $1 FROM settlement_results
+            WHERE instruction_id = $2 AND status NOT IN ($3, $4, $5)
+            ON CONFLICT (instruction_id) DO NOTHING",
+        )
+        .bind(now)
+        .bind(instruction.id.to_string())
+        .bind(TERMINAL_STATUSES[0].as_str())
+        .bind(TERMINAL_STATUSES[1].as_str())
+        .bind(TERMINAL_STATUSES[2].as_str())
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        Ok(queued > 0)
+    }
+
+    async fn lease_instructions(
+        &self,
+        worker_id: &str,
+        max_items: usize,
+        lease_duration: Duration,
+    ) -> Result<Vec<QueueLease>, SettlementError> {
+        let now = now_millis();
+        let leased_until = now.saturating_add(duration_millis(lease_duration));
+        let mut tx = self.pool.begin().await?;
+
+        // SKIP LOCKED lets concurrent workers claim disjoint rows without blocking
+        let leased: Vec<String> = sqlx::query_scalar(
+            "UPDATE settlement_queue
+            SET lease_owner = $1, lease_expires_at = $2, attempts = attempts + 1
+            WHERE instruction_id IN (
+                SELECT instruction_id FROM settlement_queue
+                WHERE available_at <= $3 AND (lease_expires_at IS NULL OR lease_expires_at <= $3)
+                ORDER BY enqueued_at ASC
+                LIMIT $4
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING instruction_id",
+        )
+        .bind(worker_id)
+        .bind(leased_until)
+        .bind(now)
+        .bind(i64::try_from(max_items).unwrap_or(i64::MAX))
+        .fetch_all(&mut *tx)
+        .await?;
+
+        // Only the rows claimed above: an earlier lease by this worker may share owner and expiry
+        let rows = sqlx::query(
+            "SELECT i.*, q.attempts, q.max_gas_amount, q.gas_unit_price FROM settlement_queue q
+            JOIN settlement_instructions i ON i.id = q.instruction_id
+            WHERE q.instruction_id = ANY($1)
+            ORDER BY q.enqueued_at ASC",
+        )
+        .bind(&leased)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(QueueLease {
+                    instruction: Self::instruction_from_row(row)?,
+                    worker_id: worker_id.to_string(),
+                    leased_until: parse_millis(leased_until),
+                    attempts: row.try_get::<i64, _>("attempts")? as u32,
+                    gas_overrides: GasOverrides {
+                        max_gas_amount: row
+                            .try_get::<Option<i64>, _>("max_gas_amount")?
+                            .map(|gas| gas as u64),
+                        gas_unit_price: row
+                            .try_get::<Option<i64>, _>("gas_unit_price")?
+                            .map(|gas| gas as u64),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    async fn ack_instruction(
+        &self,
+        instruction_id: Uuid,
+        worker_id: &str,
+    ) -> Result<bool, SettlementError> {
+        let deleted = sqlx::query(
+            "DELETE FROM settlement_queue WHERE instruction_id = $1 AND lease_owner = $2",
+        )
+        .bind(instruction_id.to_string())
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(deleted > 0)
+    }
+
+    async fn release_instruction(
+        &self,
+        instruction_id: Uuid,
+        worker_id: &str,
+        delay: Duration,
+    ) -> Result<bool, SettlementError> {
+        let released = sqlx::query(
+            "UPDATE settlement_queue
+            SET lease_owner = NULL, lease_expires_at = NULL, available_at = $1
+            WHERE instruction_id = $2 AND lease_owner = $3",
+        )
+        .bind(now_millis().saturating_add(duration_millis(delay)))
+        .bind(instruction_id.to_string())
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(released > 0)
+    }
+
+    async fn queue_depth(&self) -> Result<u64, SettlementError> {
+        let depth: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM settlement_queue")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(depth as u64)
+    }
+
+    async fn record_attempt(
+        &self,
+        result: &SettlementResult,
+        error_class: Option<&str>,
+    ) -> Result<u32, SettlementError> {
+        let attempt: i64 = sqlx::query_scalar(
+            "INSERT INTO settlement_attempts (
+                instruction_id, attempt, status, error_class, error_message, recorded_at
+            )
+            SELECT $1, COALESCE(MAX(attempt), 0) + 1, $2, $3, $4, $5
+            FROM settlement_attempts WHERE instruction_id = $1
+            RETURNING attempt",
+        )
+        .bind(result.instruction_id.to_string())
+        .bind(result.status.as_str())
+        .bind(error_class)
+        .bind(&result.error_message)
+        .bind(result.processed_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(attempt as u32)
+    }
+
+    async fn get_attempts(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Vec<SettlementAttempt>, SettlementError> {
+        let rows = sqlx::query(
+            "SELECT * FROM settlement_attempts WHERE instruction_id = $1 ORDER BY attempt ASC",
+        )
+        .bind(instruction_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(SettlementAttempt {
+                    attempt: row.try_get::<i64, _>("attempt")? as u32,
+                    status: row.try_get::<String, _>("status")?.parse()?,
+                    error_class: row.try_get("error_class")?,
+                    error_message: row.try_get("error_message")?,
+                    recorded_at: row.try_get("recorded_at")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn dead_letter_instruction(
+        &self,
+        instruction_id: Uuid,
+        error_class: &str,
+        last_error: &str,
+        actor: &str,
+    ) -> Result<bool, SettlementError> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query(
+            "INSERT INTO settlement_dead_letters (
+                instruction_id, error_class, last_error, dead_lettered_at
+            )
+            SELECT id, $1, $2, $3 FROM settlement_instructions WHERE id = $4
+            ON CONFLICT (instruction_id) DO NOTHING",
+        )
+        .bind(error_class)
+        .bind(last_error)
+        .bind(Utc::now())
+        .bind(instruction_id.to_string())
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        let reason = format!("{}: {}", error_class, last_error);
+        Self::record_transition(
+            &mut tx,
+            instruction_id,
+            &SettlementStatus::DeadLettered,
+            actor,
+            Some(&reason),
+        )
+        .await?;
+        Self::update_status(&mut tx, instruction_id, SettlementStatus::DeadLettered).await?;
+        sqlx::query("DELETE FROM settlement_queue WHERE instruction_id = $1")
+            .bind(instruction_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn list_dead_letters(&self, limit: Option<u32>) -> Result<Vec<DeadLetter>, SettlementError> {
+        let rows = sqlx::query(
+            "SELECT i.*, d.error_class, d.last_error, d.dead_lettered_at
+            FROM settlement_dead_letters d
+            JOIN settlement_instructions i ON i.id = d.instruction_id
+            ORDER BY d.dead_lettered_at ASC
+            LIMIT $1",
+        )
+        .bind(limit.map(i64::from))
+        .fetch_all(&self.pool)
+        .await?;
+
+        self.dead_letters_from_rows(&rows).await
+    }
+
+    async fn get_dead_letter(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Option<DeadLetter>, SettlementError> {
+        let row = sqlx::query(
+            "SELECT i.*, d.error_class, d.last_error, d.dead_lettered_at
+            FROM settlement_dead_letters d
+            JOIN settlement_instructions i ON i.id = d.instruction_id
+            WHERE d.instruction_id = $1",
+        )
+        .bind(instruction_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let dead_letters = self.dead_letters_from_rows(row.as_slice()).await?;
+        Ok(dead_letters.into_iter().next())
+    }
+
+    async fn requeue_dead_letter(
+        &self,
+        instruction_id: Uuid,
+        gas_overrides: GasOverrides,
+        actor: &str,
+    ) -> Result<bool, SettlementError> {
+        let mut tx = self.pool.begin().await?;
+
+        let removed = sqlx::query("DELETE FROM settlement_dead_letters WHERE instruction_id = $1")
+            .bind(instruction_id.to_string())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        if removed == 0 {
+            return Ok(false);
+        }
+
+        Self::record_transition(
+            &mut tx,
+            instruction_id,
+            &SettlementStatus::Pending,
+            actor,
+            Some("Requeued from dead-letter queue"),
+        )
+        .await?;
+        Self::upsert_result(&mut tx, &SettlementResult::pending(instruction_id)).await?;
+
+        let now = now_millis();
+        sqlx::query(
+            "INSERT INTO settlement_queue (
+                instruction_id, enqueued_at, available_at, max_gas_amount, gas_unit_price
+            ) VALUES ($1, $2, $2, $3, $4)
+            ON CONFLICT (instruction_id) DO UPDATE SET
+                max_gas_amount = excluded.max_gas_amount,
+                gas_unit_price = excluded.gas_unit_price",
+        )
+        .bind(instruction_id.to_string())
+        .bind(now)
+        .bind(gas_to_column(gas_overrides.max_gas_amount))
+        .bind(gas_to_column(gas_overrides.gas_unit_price))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn discard_dead_letter(
+        &self,
+        instruction_id: Uuid,
+        actor: &str,
+    ) -> Result<bool, SettlementError> {
+        let mut tx = self.pool.begin().await?;
+
+        let removed = sqlx::query("DELETE FROM settlement_dead_letters WHERE instruction_id = $1")
+            .bind(instruction_id.to_string())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        if removed == 0 {
+            return Ok(false);
+        }
+
+        Self::record_transition(
+            &mut tx,
+            instruction_id,
+            &SettlementStatus::Discarded,
+            actor,
+            Some("Discarded from dead-letter queue"),
+        )
+        .await?;
+        Self::update_status(&mut tx, instruction_id, SettlementStatus::Discarded).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn get_source_cursor(
+        &self,
+        source: &str,
+    ) -> Result<Option<SourceCursor>, SettlementError> {
+        let row = sqlx::query("SELECT * FROM source_cursors WHERE source = $1")
+            .bind(source)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|row| {
+            Ok(SourceCursor {
+                signature: row.try_get("signature")?,
+                slot: row.try_get::<i64, _>("slot")? as u64,
+                updated_at: row.try_get("updated_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn save_source_cursor(
+        &self,
+        source: &str,
+        signature: &str,
+        slot: u64,
+    ) -> Result<(), SettlementError> {
+        sqlx::query(
+            "INSERT INTO source_cursors (source, signature, slot, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (source) DO UPDATE SET
+                signature = excluded.signature,
+                slot = excluded.slot,
+                updated_at = excluded.updated_at",
+        )
+        .bind(source)
+        .bind(signature)
+        .bind(i64::try_from(slot).unwrap_or(i64::MAX))
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn hold_for_finality(
+        &self,
+        instruction: &SettlementInstruction,
+        slot: u64,
+    ) -> Result<bool, SettlementError> {
+        let held = sqlx::query(
+            "INSERT INTO settlement_finality (instruction_id, instruction, slot, state, held_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (instruction_id) DO UPDATE SET
+                instruction = EXCLUDED.instruction,
+                slot = EXCLUDED.slot,
+                state = EXCLUDED.state,
+                reason = NULL,
+                held_at = EXCLUDED.held_at,
+                decided_at = NULL
+            WHERE settlement_finality.state = $6",
+        )
+        .bind(instruction.id.to_string())
+        .bind(serde_json::to_string(instruction)?)
+        .bind(i64::try_from(slot).unwrap_or(i64::MAX))
+        .bind(FinalityState::Pending.as_str())
+        .bind(Utc::now())
+        .bind(FinalityState::Retracted.as_str())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(held > 0)
+    }
+
+    async fn pending_finality(&self) -> Result<Vec<FinalityRecord>, SettlementError> {
+        let rows = sqlx::query(
+            "SELECT * FROM settlement_finality WHERE state = $1 ORDER BY held_at ASC, slot ASC",
+        )
+        .bind(FinalityState::Pending.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::finality_from_row).collect()
+    }
+
+    async fn decide_finality(
+        &self,
+        instruction_id: Uuid,
+        state: FinalityState,
+        reason: &str,
+    ) -> Result<bool, SettlementError> {
+        let decided = sqlx::query(
+            "UPDATE settlement_finality SET state = $1, reason = $2, decided_at = $3
+            WHERE instruction_id = $4 AND state = $5",
+        )
+        .bind(state.as_str())
+        .bind(reason)
+        .bind(Utc::now())
+        .bind(instruction_id.to_string())
+        .bind(FinalityState::Pending.as_str())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(decided > 0)
+    }
+
+    async fn get_finality(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Option<FinalityRecord>, SettlementError> {
+        let row = sqlx::query("SELECT * FROM settlement_finality WHERE instruction_id = $1")
+            .bind(instruction_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(Self::finality_from_row).transpose()
+    }
+}
@@ -0,0 +1,539 @@
+//! Relayer signing keys for Aptos
+//!
+//! Resolves the key the relayer signs with from [`AptosConfig`]: a raw hex
+//! key, a profile in an Aptos CLI `config.yaml`, or an encrypted keyfile.
+//! The account address is derived from the public key the way Aptos derives
+//! it, unless an explicit address is configured for an account whose key has
+//! been rotated.
+//!
+//! Exposed on the binary as `cyrus-relayer key <address|encrypt>`.
+
+use crate::types::{AptosConfig, SettlementError};
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use clap::{Arg, ArgMatches, Command};
+use ed25519_dalek::SigningKey;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::{collections::HashMap, fmt, path::Path};
+use tracing::info;
+use zeroize::Zeroizing;
+
+/// Environment variable holding the passphrase for encrypted keyfiles
+pub const PASSPHRASE_ENV: &str = "CYRUS_KEY_PASSPHRASE";
+
+/// Authentication key scheme byte for single Ed25519 keys
+const ED25519_SCHEME: u8 = 0x00;
+
+/// Prefix the Aptos CLI puts on Ed25519 private keys (AIP-80)
+const ED25519_KEY_PREFIX: &str = "ed25519-priv-";
+
+/// scrypt cost used for new keyfiles (2^15 iterations)
+const DEFAULT_SCRYPT_LOG_N: u8 = 15;
+
+/// Derive the address of an account that still uses its original key:
+/// the SHA3-256 authentication key of the public key and scheme byte.
+pub fn derive_address(public_key: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(public_key);
+    hasher.update([ED25519_SCHEME]);
+    hasher.finalize().into()
+}
+
+/// Parse an Ed25519 private key given as hex, with or without `0x` or the
+/// Aptos CLI `ed25519-priv-` prefix
+pub fn parse_private_key(value: &str) -> Result<SigningKey, SettlementError> {
+    let value = value.trim();
+    let value = value.strip_prefix(ED25519_KEY_PREFIX).unwrap_or(value);
+    let value = value.strip_prefix("0x").unwrap_or(value);
+
+    let bytes = Zeroizing::new(
+        hex::decode(value)
+            .map_err(|e| SettlementError::ConfigError(format!("Invalid private key: {}", e)))?,
+    );
+    let seed: &[u8; 32] = bytes.as_slice().try_into().map_err(|_| {
+        SettlementError::ConfigError(format!(
+            "Invalid private key: expected 32 bytes, got {}",
+            bytes.len()
+        ))
+    })?;
+    Ok(SigningKey::from_bytes(seed))
+}
+
+/// Parse an account address, accepting short forms such as `0x1`
+pub fn parse_address(value: &str) -> Result<[u8; 32], SettlementError> {
+    let value = value.trim();
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.is_empty() || digits.len() > 64 {
+        return Err(SettlementError::ConfigError(format!("Invalid account address: {}", value)));
+    }
+
+    let padded = format!("{:0>64}", digits);
+    let bytes = hex::decode(&padded)
+        .map_err(|e| SettlementError::ConfigError(format!("Invalid account address {}: {}", value, e)))?;
+    let mut address = [0u8; 32];
+    address.copy_from_slice(&bytes);
+    Ok(address)
+}
+
+/// The key the relayer signs with and the account it signs for
+#[derive(Clone)]
+pub struct RelayerKey {
+    signing_key: SigningKey,
+    address: [u8; 32],
+}
+
+impl RelayerKey {
+    /// Use `address` for a rotated key, otherwise derive it from the key
+    pub fn new(signing_key: SigningKey, address: Option<[u8; 32]>) -> Self {
+        let address =
+            address.unwrap_or_else(|| derive_address(&signing_key.verifying_key().to_bytes()));
+        Self { signing_key, address }
+    }
+
+    pub fn private_key_bytes(&self) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.signing_key.to_bytes())
+    }
+
+    pub fn public_key_bytes(&self) -> [u8; 32] {
+        self.signing_key.verifying_key().to_bytes()
+    }
+
+    pub fn address(&self) -> [u8; 32] {
+        self.address
+    }
+
+    /// Full-length `0x`-prefixed account address
+    pub fn address_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.address))
+    }
+
+    /// Whether the account's key has been rotated away from the one its address was derived from
+    pub fn is_rotated(&self) -> bool {
+        self.address != derive_address(&self.public_key_bytes())
+    }
+}
+
+impl fmt::Debug for RelayerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RelayerKey")
+            .field("public_key", &hex::encode(self.public_key_bytes()))
+            .field("address", &self.address_hex())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Load the relayer key selected by the Aptos configuration.
+///
+/// `key_file` takes precedence over `private_key`; an encrypted keyfile is
+/// unlocked with the passphrase in [`PASSPHRASE_ENV`]. The account address is
+/// `account_address` if set, then the address stored alongside the key, then
+/// the address derived from the key.
+pub fn load(config: &AptosConfig) -> Result<RelayerKey, SettlementError> {
+    let (signing_key, stored_address) = match &config.key_file {
+        Some(path) => {
+            let passphrase = std::env::var(PASSPHRASE_ENV).ok().map(Zeroizing::new);
+            load_key_file(
+                Path::new(path),
+                config.key_profile.as_deref(),
+                passphrase.as_ref().map(|p| p.as_str()),
+            )?
+        }
+        None if !config.private_key.is_empty() => (parse_private_key(&config.private_key)?, None),
+        None => {
+            return Err(SettlementError::ConfigError(
+                "Either aptos.private_key or aptos.key_file is required".to_string(),
+            ))
+        }
+    };
+
+    let address = match &config.account_address {
+        Some(address) => Some(parse_address(address)?),
+        None => stored_address,
+    };
+
+    let key = RelayerKey::new(signing_key, address);
+    if key.is_rotated() {
+        info!("Relayer account {} uses a rotated key", key.address_hex());
+    } else {
+        info!("Relayer account {}", key.address_hex());
+    }
+    Ok(key)
+}
+
+/// Read a key from an Aptos CLI `config.yaml` or an encrypted JSON keyfile
+fn load_key_file(
+    path: &Path,
+    profile: Option<&str>,
+    passphrase: Option<&str>,
+) -> Result<(SigningKey, Option<[u8; 32]>), SettlementError> {
+    let contents = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| {
+        SettlementError::ConfigError(format!("Failed to read key file {}: {}", path.display(), e))
+    })?);
+
+    if contents.trim_start().starts_with('{') {
+        let key_file: EncryptedKeyFile = serde_json::from_str(&contents).map_err(|e| {
+            SettlementError::ConfigError(format!("Invalid keyfile {}: {}", path.display(), e))
+        })?;
+        let passphrase = passphrase.ok_or_else(|| {
+            SettlementError::ConfigError(format!(
+                "{} must be set to unlock {}",
+                PASSPHRASE_ENV,
+                path.display()
+            ))
+        })?;
+        let key = key_file.decrypt(passphrase)?;
+        let stored_address = key_file.address.is_some().then(|| key.address());
+        Ok((key.signing_key, stored_address))
+    } else {
+        parse_cli_profile(&contents, profile.unwrap_or("default"))
+    }
+}
+
+/// Profiles section of an Aptos CLI `config.yaml`
+#[derive(Deserialize)]
+struct CliConfig {
+    profiles: HashMap<String, CliProfile>,
+}
+
+#[derive(Deserialize)]
+struct CliProfile {
+    private_key: Option<String>,
+    account: Option<String>,
+}
+
+/// Key and account of one profile in an Aptos CLI `config.yaml`
+fn parse_cli_profile(
+    contents: &str,
+    profile: &str,
+) -> Result<(SigningKey, Option<[u8; 32]>), SettlementError> {
+    let config: CliConfig = serde_yaml::from_str(contents)
+        .map_err(|e| SettlementError::ConfigError(format!("Invalid Aptos CLI config: {}", e)))?;
+    let entry = config.profiles.get(profile).ok_or_else(|| {
+        SettlementError::ConfigError(format!("Profile '{}' not found in Aptos CLI config", profile))
+    })?;
+
+    let private_key = entry.private_key.as_deref().ok_or_else(|| {
+        SettlementError::ConfigError(format!("Profile '{}' has no private key", profile))
+    })?;
+    let address = entry.account.as_deref().map(parse_address).transpose()?;
+    Ok((parse_private_key(private_key)?, address))
+}
+
+/// scrypt cost parameters stored in a keyfile
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ScryptParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+/// A private key encrypted with AES-256-GCM under a scrypt-derived key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedKeyFile {
+    pub version: u32,
+    pub kdf: ScryptParams,
+    /// Hex-encoded scrypt salt
+    pub salt: String,
+    /// Hex-encoded AES-GCM nonce
+    pub nonce: String,
+    /// Hex-encoded encrypted private key
+    pub ciphertext: String,
+    /// Account address, stored for keys that no longer match their derived address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+impl EncryptedKeyFile {
+    pub const VERSION: u32 = 1;
+
+    /// Encrypt `key` under `passphrase`
+    pub fn encrypt(key: &RelayerKey, passphrase: &str) -> Result<Self, SettlementError> {
+        Self::encrypt_with(
+            key,
+            passphrase,
+            ScryptParams { log_n: DEFAULT_SCRYPT_LOG_N, r: 8, p: 1 },
+        )
+    }
+
+    fn encrypt_with(
+        key: &RelayerKey,
+        passphrase: &str,
+        kdf: ScryptParams,
+    ) -> Result<Self, SettlementError> {
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        rand::rng().fill_bytes(&mut salt);
+        rand::rng().fill_bytes(&mut nonce);
+
+        let cipher = Self::cipher(passphrase, &salt, kdf)?;
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), key.private_key_bytes().as_slice())
+            .map_err(|e| SettlementError::ConfigError(format!("Failed to encrypt key: {}", e)))?;
+
+        Ok(Self {
+            version: Self::VERSION,
+            kdf,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+            address: key.is_rotated().then(|| key.address_hex()),
+        })
+    }
+
+    /// Decrypt the key; fails on a wrong passphrase or a tampered file
+    pub fn decrypt(&self, passphrase: &str) -> Result<RelayerKey, SettlementError> {
+        if self.version != Self::VERSION {
+            return Err(SettlementError::ConfigError(format!(
+                "Unsupported keyfile version {}",
+                self.version
+            )));
+        }
+
+        let decode = |field: &str, value: &str| {
+            hex::decode(value)
+                .map_err(|e| SettlementError::ConfigError(format!("Invalid keyfile {}: {}", field, e)))
+        };
+        let salt = decode("salt", &self.salt)?;
+        let nonce = decode("nonce", &self.nonce)?;
+        let ciphertext = decode("ciphertext", &self.ciphertext)?;
+        if nonce.len() != 12 {
+            return Err(SettlementError::ConfigError("Invalid keyfile nonce length".to_string()));
+        }
+
+        let cipher = Self::cipher(passphrase, &salt, self.kdf)?;
+        let plaintext = Zeroizing::new(
+            cipher
+                .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+                .map_err(|_| {
+                    SettlementError::ConfigError(
+                        "Failed to decrypt keyfile: wrong passphrase or corrupted file".to_string(),
+                    )
+                })?,
+        );
+        let seed: &[u8; 32] = plaintext.as_slice().try_into().map_err(|_| {
+            SettlementError::ConfigError("Keyfile does not hold a 32 byte key".to_string())
+        })?;
+
+        let address = self.address.as_deref().map(parse_address).transpose()?;
+        Ok(RelayerKey::new(SigningKey::from_bytes(seed), address))
+    }
+
+    fn cipher(passphrase: &str, salt: &[u8], kdf: ScryptParams) -> Result<Aes256Gcm, SettlementError> {
+        let params = scrypt::Params::new(kdf.log_n, kdf.r, kdf.p, 32)
+            .map_err(|e| SettlementError::ConfigError(format!("Invalid scrypt parameters: {}", e)))?;
+        let mut derived = Zeroizing::new([0u8; 32]);
+        scrypt::scrypt(passphrase.as_bytes(), salt, &params, derived.as_mut_slice())
+            .map_err(|e| SettlementError::ConfigError(format!("Key derivation failed: {}", e)))?;
+        Aes256Gcm::new_from_slice(derived.as_slice())
+            .map_err(|e| SettlementError::ConfigError(format!("Invalid encryption key: {}", e)))
+    }
+}
+
+/// A parsed `key` subcommand
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyCommand {
+    /// Print the account address for a private key
+    Address {
+        private_key: String,
+        account_address: Option<String>,
+    },
+    /// Write an encrypted keyfile for a private key
+    Encrypt {
+        private_key: String,
+        account_address: Option<String>,
+        output: String,
+    },
+}
+
+/// Command line definition for `cyrus-relayer key`
+pub fn key_cli() -> Command {
+    let private_key_arg = || {
+        Arg::new("private-key")
+            .long("private-key")
+            .value_name("HEX")
+            .help("Ed25519 private key")
+            .env("APTOS_PRIVATE_KEY")
+            .hide_env_values(true)
+            .required(true)
+    };
+    let address_arg = || {
+        Arg::new("account-address")
+            .long("account-address")
+            .value_name("ADDRESS")
+            .help("Account address, for keys that have been rotated")
+    };
+
+    Command::new("key")
+        .bin_name("cyrus-relayer key")
+        .about("Inspect and protect the relayer's Aptos key")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("address")
+                .about("Print the Aptos account address for a private key")
+                .arg(private_key_arg())
+                .arg(address_arg()),
+        )
+        .subcommand(
+            Command::new("encrypt")
+                .about(format!("Write an encrypted keyfile, using the passphrase in {}", PASSPHRASE_ENV))
+                .arg(private_key_arg())
+                .arg(address_arg())
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .value_name("PATH")
+                        .help("Where to write the keyfile")
+                        .required(true),
+                ),
+        )
+}
+
+impl KeyCommand {
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, SettlementError> {
+        let value = |matches: &ArgMatches, name: &str| matches.get_one::<String>(name).cloned();
+
+        match matches.subcommand() {
+            Some(("address", sub)) => Ok(Self::Address {
+                private_key: value(sub, "private-key").unwrap_or_default(),
+                account_address: value(sub, "account-address"),
+            }),
+            Some(("encrypt", sub)) => Ok(Self::Encrypt {
+                private_key: value(sub, "private-key").unwrap_or_default(),
+                account_address: value(sub, "account-address"),
+                output: value(sub, "output").unwrap_or_default(),
+            }),
+            _ => Err(SettlementError::ConfigError("Unknown key command".to_string())),
+        }
+    }
+
+    /// Run the command and return its output
+    pub fn run(&self) -> Result<String, SettlementError> {
+        match self {
+            Self::Address { private_key, account_address } => {
+                let key = Self::key(private_key, account_address.as_deref())?;
+                Ok(key.address_hex())
+            }
+            Self::Encrypt { private_key, account_address, output } => {
+                let key = Self::key(private_key, account_address.as_deref())?;
+                let passphrase = Zeroizing::new(std::env::var(PASSPHRASE_ENV).map_err(|_| {
+                    SettlementError::ConfigError(format!("{} must be set", PASSPHRASE_ENV))
+                })?);
+                let key_file = EncryptedKeyFile::encrypt(&key, &passphrase)?;
+                let json = serde_json::to_string_pretty(&key_file)
+                    .map_err(|e| SettlementError::SerializationError(e.to_string()))?;
+                std::fs::write(output, json).map_err(|e| {
+                    SettlementError::ConfigError(format!("Failed to write {}: {}", output, e))
+                })?;
+                Ok(format!("Wrote keyfile for {} to {}", key.address_hex(), output))
+            }
+        }
+    }
+
+    fn key(private_key: &str, account_address: Option<&str>) -> Result<RelayerKey, SettlementError> {
+        let address = account_address.map(parse_address).transpose()?;
+        Ok(RelayerKey::new(parse_private_key(private_key)?, address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+    fn test_key() -> RelayerKey {
+        RelayerKey::new(parse_private_key(PRIVATE_KEY).unwrap(), None)
+    }
+
+    #[test]
+    fn test_address_derivation() {
+        let key = test_key();
+
+        // Checked against the Aptos SDK's own derivation rather than the same formula
+        let account = aptos_sdk::account::Ed25519Account::from_private_key_bytes(&key.private_key_bytes()[..]).unwrap();
+        assert_eq!(account.public_key().to_bytes(), key.public_key_bytes());
+        assert_eq!(key.address(), account.address().to_bytes());
+        // The old behaviour used the public key itself as the address
+        assert_ne!(key.address(), key.public_key_bytes());
+        assert!(!key.is_rotated());
+        assert_eq!(key.address_hex().len(), 66);
+    }
+
+    #[test]
+    fn test_key_and_address_formats() {
+        let bare = parse_private_key(PRIVATE_KEY.trim_start_matches("0x")).unwrap();
+        let prefixed = parse_private_key(&format!("ed25519-priv-{}", PRIVATE_KEY)).unwrap();
+        assert_eq!(bare.to_bytes(), prefixed.to_bytes());
+        assert!(parse_private_key("0x1234").is_err());
+
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        assert_eq!(parse_address("0x1").unwrap(), one);
+        assert!(parse_address("0x").is_err());
+        assert!(parse_address("0xzz").is_err());
+
+        let rotated = RelayerKey::new(parse_private_key(PRIVATE_KEY).unwrap(), Some(one));
+        assert!(rotated.is_rotated());
+        assert_eq!(rotated.address(), one);
+    }
+
+    #[test]
+    fn test_cli_profile() {
+        let contents = format!(
+            r#"---
+profiles:
+  default:
+    network: Testnet
+    private_key: "ed25519-priv-{}"
+    rest_url: "https://fullnode.testnet.aptoslabs.com"
+  rotated:
+    private_key: "{}"
+    account: 000000000000000000000000000000000000000000000000000000000000abcd
+"#,
+            PRIVATE_KEY, PRIVATE_KEY
+        );
+
+        let (key, address) = parse_cli_profile(&contents, "default").unwrap();
+        assert_eq!(key.to_bytes(), test_key().private_key_bytes().as_slice());
+        assert_eq!(address, None);
+
+        let (_, address) = parse_cli_profile(&contents, "rotated").unwrap();
+        assert_eq!(address, Some(parse_address("0xabcd").unwrap()));
+
+        assert!(parse_cli_profile(&contents, "missing").is_err());
+    }
+
+    #[test]
+    fn test_encrypted_keyfile_round_trip() {
+        // Cheap scrypt cost keeps the test fast
+        let kdf = ScryptParams { log_n: 4, r: 8, p: 1 };
+        let key = test_key();
+
+        let key_file = EncryptedKeyFile::encrypt_with(&key, "correct horse", kdf).unwrap();
+        assert!(!key_file.ciphertext.contains(&PRIVATE_KEY[2..]));
+        assert_eq!(key_file.address, None);
+
+        let decrypted = key_file.decrypt("correct horse").unwrap();
+        assert_eq!(decrypted.private_key_bytes(), key.private_key_bytes());
+        assert_eq!(decrypted.address(), key.address());
+        assert!(key_file.decrypt("wrong").is_err());
+
+        // Rotated accounts keep their address in the file
+        let rotated = RelayerKey::new(parse_private_key(PRIVATE_KEY).unwrap(), Some([7u8; 32]));
+        let key_file = EncryptedKeyFile::encrypt_with(&rotated, "pw", kdf).unwrap();
+        assert_eq!(key_file.decrypt("pw").unwrap().address(), [7u8; 32]);
+
+        // Loading goes through the file on disk
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), serde_json::to_string(&key_file).unwrap()).unwrap();
+        let (loaded, address) = load_key_file(file.path(), None, Some("pw")).unwrap();
+        assert_eq!(loaded.to_bytes(), rotated.private_key_bytes().as_slice());
+        assert_eq!(address, Some([7u8; 32]));
+        assert!(load_key_file(file.path(), None, None).is_err());
+    }
+}
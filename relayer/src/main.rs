@@ -1,6 +1,13 @@
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
 use chrono::{DateTime, Utc};
+use cyrus_relayer::{
+    admin::{dead_letter_cli, DeadLetterCommand},
+    backfill::{backfill_cli, BackfillCommand},
+    database,
+    keys::{key_cli, KeyCommand},
+    DatabaseConfig,
+};
 use log::{error, info, warn};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -119,6 +126,7 @@ impl SettlementResult {
 }
 
 // Solana Event Listener
+#[allow(dead_code)] // Connection details are unused until the demo talks to real RPC
 pub struct SolanaListener {
     rpc_url: String,
     program_id: String,
@@ -147,13 +155,13 @@ impl SolanaListener {
         // Simulate finding some settlement events
         let mock_events = vec![
             SettlementInstruction::new(
-                format!("solana_tx_{}", hex::encode(&rand::random::<[u8; 8]>())),
+                format!("solana_tx_{}", hex::encode(rand::random::<[u8; 8]>())),
                 "0xcd63ab17ff17b42a9d5c893cf3be1ceba94243111380ff2ce76f6a6083a090dd".to_string(),
                 0.5,
                 1,
             ),
             SettlementInstruction::new(
-                format!("solana_tx_{}", hex::encode(&rand::random::<[u8; 8]>())),
+                format!("solana_tx_{}", hex::encode(rand::random::<[u8; 8]>())),
                 "0xcd63ab17ff17b42a9d5c893cf3be1ceba94243111380ff2ce76f6a6083a090dd".to_string(),
                 1.0,
                 2,
@@ -166,6 +174,7 @@ impl SolanaListener {
 }
 
 // Aptos Executor
+#[allow(dead_code)] // Connection details are unused until the demo talks to real RPC
 pub struct AptosExecutor {
     rpc_url: String,
     contract_address: String,
@@ -205,7 +214,7 @@ impl AptosExecutor {
         sleep(Duration::from_millis(1500)).await;
 
         // Generate mock transaction hash
-        let mock_tx_hash = format!("0x{}", hex::encode(&rand::random::<[u8; 32]>()));
+        let mock_tx_hash = format!("0x{}", hex::encode(rand::random::<[u8; 32]>()));
         let gas_used = 2000 + rand::random::<u64>() % 1000;
 
         info!("✅ Settlement executed on Aptos!");
@@ -354,11 +363,71 @@ impl SettlementProcessor {
     }
 }
 
+/// Run `cyrus-relayer dlq ...` against the settlement database and print the result
+async fn run_dead_letter_command(args: impl Iterator<Item = String>) -> Result<()> {
+    let matches = dead_letter_cli().get_matches_from(args);
+    let command = DeadLetterCommand::from_matches(&matches)?;
+
+    let store = database::connect(&DatabaseConfig {
+        url: matches
+            .get_one::<String>("database-url")
+            .cloned()
+            .unwrap_or_default(),
+        max_connections: 1,
+        connection_timeout_secs: 30,
+    })
+    .await?;
+
+    println!("{}", command.run(store.as_ref()).await?);
+    Ok(())
+}
+
+/// Run `cyrus-relayer backfill ...` against the settlement database and print the report
+async fn run_backfill_command(args: impl Iterator<Item = String>) -> Result<()> {
+    let matches = backfill_cli().get_matches_from(args);
+    let command = BackfillCommand::from_matches(&matches)?;
+
+    let store = database::connect(&DatabaseConfig {
+        url: matches
+            .get_one::<String>("database-url")
+            .cloned()
+            .unwrap_or_default(),
+        max_connections: 1,
+        connection_timeout_secs: 30,
+    })
+    .await?;
+
+    println!("{}", command.run(store).await?);
+    Ok(())
+}
+
+/// Run `cyrus-relayer key ...` and print the result
+fn run_key_command(args: impl Iterator<Item = String>) -> Result<()> {
+    let matches = key_cli().get_matches_from(args);
+    println!("{}", KeyCommand::from_matches(&matches)?.run()?);
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
+    // Operator commands for the dead-letter queue
+    if std::env::args().nth(1).as_deref() == Some("dlq") {
+        return run_dead_letter_command(std::env::args().skip(1)).await;
+    }
+
+    // Re-scan Solana history after an outage
+    if std::env::args().nth(1).as_deref() == Some("backfill") {
+        return run_backfill_command(std::env::args().skip(1)).await;
+    }
+
+    // Relayer key inspection and encryption
+    if std::env::args().nth(1).as_deref() == Some("key") {
+        return run_key_command(std::env::args().skip(1));
+    }
+
     info!("🎪 Cyrus Protocol Cross-Chain Settlement Demo");
     info!("============================================");
     info!("");
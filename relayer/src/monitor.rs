@@ -1,17 +1,22 @@
 use crate::{
     database::DatabaseStatistics,
-    processor::SettlementProcessor,
-    types::{ApiResponse, HealthStatus, MonitoringConfig, RelayerMetrics, ServiceStatus},
+    settlement_processor::SettlementProcessor,
+    database::DeadLetter,
+    retry::CircuitState,
+    types::{
+        ApiResponse, GasOverrides, HealthStatus, MonitoringConfig, RelayerMetrics, ServiceStatus,
+    },
 };
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Path, Query, Request, State},
     http::{header, StatusCode},
-    response::{IntoResponse, Json},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
 use chrono::Utc;
-use prometheus::{Counter, Gauge, Histogram, Registry, TextEncoder};
+use prometheus::{Counter, Gauge, Histogram, HistogramOpts, Registry, TextEncoder};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::{net::TcpListener, time::interval};
@@ -20,9 +25,12 @@ use tower_http::{
     cors::CorsLayer,
     trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer},
 };
-use tracing::{error, info, Level};
+use tracing::{info, warn, Level};
 use uuid::Uuid;
 
+/// Actor recorded on status changes made through the admin endpoints
+const ADMIN_API_ACTOR: &str = "admin-api";
+
 /// Monitoring server for metrics and health checks
 pub struct MonitoringServer {
     config: MonitoringConfig,
@@ -41,6 +49,13 @@ pub struct PrometheusMetrics {
     pub vault_balance: Gauge,
     pub pending_settlements: Gauge,
     pub relayer_uptime: Gauge,
+    pub destination_circuit_state: Gauge,
+    pub destination_circuit_trips: Counter,
+    pub unknown_settlement_checks: Counter,
+    pub source_transactions_fetched: Counter,
+    pub source_rpc_requests: Counter,
+    pub source_rpc_throttled: Counter,
+    pub source_rpc_throttle_wait: Counter,
 }
 
 /// Query parameters for API endpoints
@@ -54,6 +69,11 @@ pub struct HealthQuery {
     pub component: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    pub limit: Option<u32>,
+}
+
 /// API response types
 #[derive(Debug, Serialize)]
 pub struct MetricsResponse {
@@ -106,6 +126,12 @@ impl MonitoringServer {
         info!("  GET  /api/v1/metrics - JSON metrics");
         info!("  GET  /api/v1/statistics - Database statistics");
         info!("  GET  /api/v1/health - Detailed health status");
+        info!("  GET  /api/v1/dead-letters - Dead-lettered settlements");
+        info!("  POST /api/v1/dead-letters/:id/requeue - Requeue a dead-lettered settlement (admin token)");
+        info!("  POST /api/v1/dead-letters/:id/discard - Discard a dead-lettered settlement (admin token)");
+        if self.config.admin_token.is_none() {
+            warn!("No monitoring.admin_token set, dead-letter requeue and discard are disabled");
+        }
 
         axum::serve(listener, app).await?;
 
@@ -128,6 +154,19 @@ impl MonitoringServer {
             .route("/api/v1/status", get(relayer_status))
             .route("/api/v1/settlements", get(recent_settlements))
             .route("/api/v1/settlements/:id", get(settlement_details))
+
+            // Dead-letter queue administration
+            .route("/api/v1/dead-letters", get(list_dead_letters))
+            .route("/api/v1/dead-letters/:id", get(dead_letter_details))
+            .merge(
+                Router::new()
+                    .route("/api/v1/dead-letters/:id/requeue", post(requeue_dead_letter))
+                    .route("/api/v1/dead-letters/:id/discard", post(discard_dead_letter))
+                    .route_layer(middleware::from_fn_with_state(
+                        self.config.admin_token.clone(),
+                        require_admin_token,
+                    )),
+            )
             
             // Root endpoint
             .route("/", get(root))
@@ -143,7 +182,6 @@ impl MonitoringServer {
             )
             .with_state(AppState {
                 processor: Arc::clone(&self.processor),
-                prometheus_metrics: self.prometheus_metrics.clone(),
                 registry: self.metrics_registry.clone(),
             })
     }
@@ -174,6 +212,30 @@ impl MonitoringServer {
                 metrics.vault_balance.set(relayer_metrics.vault_balance_usdc);
                 metrics.pending_settlements.set(relayer_metrics.pending_settlements as f64);
                 metrics.relayer_uptime.set(relayer_metrics.uptime_seconds as f64);
+
+                let circuit = &relayer_metrics.destination_circuit;
+                metrics.destination_circuit_state.set(match circuit.state {
+                    CircuitState::Closed => 0.0,
+                    CircuitState::HalfOpen => 1.0,
+                    CircuitState::Open => 2.0,
+                });
+                metrics.destination_circuit_trips.reset();
+                metrics.destination_circuit_trips.inc_by(circuit.trips as f64);
+
+                metrics.unknown_settlement_checks.reset();
+                metrics.unknown_settlement_checks.inc_by(relayer_metrics.unknown_settlement_checks as f64);
+
+                let fetch = &relayer_metrics.source_fetch;
+                metrics.source_transactions_fetched.reset();
+                metrics.source_transactions_fetched.inc_by(fetch.transactions_fetched as f64);
+                metrics.source_rpc_requests.reset();
+                metrics.source_rpc_requests.inc_by(fetch.endpoints.iter().map(|e| e.requests).sum::<u64>() as f64);
+                metrics.source_rpc_throttled.reset();
+                metrics.source_rpc_throttled.inc_by(fetch.endpoints.iter().map(|e| e.throttled).sum::<u64>() as f64);
+                metrics.source_rpc_throttle_wait.reset();
+                metrics.source_rpc_throttle_wait.inc_by(
+                    fetch.endpoints.iter().map(|e| e.throttle_wait_ms).sum::<u64>() as f64 / 1000.0,
+                );
             }
         });
     }
@@ -196,10 +258,10 @@ impl PrometheusMetrics {
             "Total number of failed settlements"
         )?;
         
-        let settlement_duration = Histogram::new(
+        let settlement_duration = Histogram::with_opts(HistogramOpts::new(
             "cyrus_settlement_duration_seconds",
             "Settlement processing duration in seconds"
-        )?;
+        ))?;
         
         let vault_balance = Gauge::new(
             "cyrus_vault_balance_usdc",
@@ -216,6 +278,41 @@ impl PrometheusMetrics {
             "Relayer uptime in seconds"
         )?;
 
+        let destination_circuit_state = Gauge::new(
+            "cyrus_destination_circuit_state",
+            "Aptos circuit breaker state (0 = closed, 1 = half open, 2 = open)"
+        )?;
+
+        let destination_circuit_trips = Counter::new(
+            "cyrus_destination_circuit_trips_total",
+            "Number of times the Aptos circuit breaker has opened"
+        )?;
+
+        let unknown_settlement_checks = Counter::new(
+            "cyrus_settlement_checks_unknown_total",
+            "Settlements held because their on-chain status could not be read"
+        )?;
+
+        let source_transactions_fetched = Counter::new(
+            "cyrus_source_transactions_fetched_total",
+            "Solana transactions fetched"
+        )?;
+
+        let source_rpc_requests = Counter::new(
+            "cyrus_source_rpc_requests_total",
+            "Requests sent to Solana RPC endpoints, including retries"
+        )?;
+
+        let source_rpc_throttled = Counter::new(
+            "cyrus_source_rpc_throttled_total",
+            "Solana RPC requests rate limited with HTTP 429"
+        )?;
+
+        let source_rpc_throttle_wait = Counter::new(
+            "cyrus_source_rpc_throttle_wait_seconds_total",
+            "Time Solana RPC requests spent held back by rate limits"
+        )?;
+
         // Register metrics
         registry.register(Box::new(settlements_total.clone()))?;
         registry.register(Box::new(settlements_successful.clone()))?;
@@ -224,6 +321,13 @@ impl PrometheusMetrics {
         registry.register(Box::new(vault_balance.clone()))?;
         registry.register(Box::new(pending_settlements.clone()))?;
         registry.register(Box::new(relayer_uptime.clone()))?;
+        registry.register(Box::new(destination_circuit_state.clone()))?;
+        registry.register(Box::new(destination_circuit_trips.clone()))?;
+        registry.register(Box::new(unknown_settlement_checks.clone()))?;
+        registry.register(Box::new(source_transactions_fetched.clone()))?;
+        registry.register(Box::new(source_rpc_requests.clone()))?;
+        registry.register(Box::new(source_rpc_throttled.clone()))?;
+        registry.register(Box::new(source_rpc_throttle_wait.clone()))?;
 
         Ok(Self {
             settlements_total,
@@ -233,6 +337,13 @@ impl PrometheusMetrics {
             vault_balance,
             pending_settlements,
             relayer_uptime,
+            destination_circuit_state,
+            destination_circuit_trips,
+            unknown_settlement_checks,
+            source_transactions_fetched,
+            source_rpc_requests,
+            source_rpc_throttled,
+            source_rpc_throttle_wait,
         })
     }
 }
@@ -241,7 +352,6 @@ impl PrometheusMetrics {
 #[derive(Clone)]
 struct AppState {
     processor: Arc<SettlementProcessor>,
-    prometheus_metrics: PrometheusMetrics,
     registry: Registry,
 }
 
@@ -393,7 +503,7 @@ async fn relayer_status(State(state): State<AppState>) -> impl IntoResponse {
 }
 
 /// Recent settlements endpoint
-async fn recent_settlements(State(state): State<AppState>) -> impl IntoResponse {
+async fn recent_settlements(State(_state): State<AppState>) -> impl IntoResponse {
     // This would require additional database methods to get recent settlements
     // For now, return a placeholder
     Json(ApiResponse::success(serde_json::json!({
@@ -402,22 +512,191 @@ async fn recent_settlements(State(state): State<AppState>) -> impl IntoResponse
     })))
 }
 
-/// Settlement details endpoint
+/// Settlement details endpoint, including the full status timeline
 async fn settlement_details(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
-    match Uuid::parse_str(&id) {
-        Ok(uuid) => {
-            // This would require additional database methods to get settlement details
+    let Ok(uuid) = Uuid::parse_str(&id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<serde_json::Value>::error(
+                "Invalid settlement ID format".to_string()
+            )),
+        );
+    };
+
+    match state.processor.get_settlement_timeline(uuid).await {
+        Ok(Some((instruction, result, timeline))) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(serde_json::json!({
+                "instruction": instruction,
+                "result": result,
+                "timeline": timeline,
+            }))),
+        ),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<serde_json::Value>::error(format!(
+                "Settlement {} not found", uuid
+            ))),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<serde_json::Value>::error(format!(
+                "Failed to get settlement: {}", e
+            ))),
+        ),
+    }
+}
+
+/// Dead-letter queue listing
+async fn list_dead_letters(
+    State(state): State<AppState>,
+    Query(params): Query<DeadLetterQuery>,
+) -> impl IntoResponse {
+    match state.processor.list_dead_letters(params.limit).await {
+        Ok(dead_letters) => (StatusCode::OK, Json(ApiResponse::success(dead_letters))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<Vec<DeadLetter>>::error(format!(
+                "Failed to list dead letters: {}", e
+            ))),
+        ),
+    }
+}
+
+/// Dead-lettered settlement with its attempt history
+async fn dead_letter_details(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(uuid) = Uuid::parse_str(&id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<DeadLetter>::error("Invalid settlement ID format".to_string())),
+        );
+    };
+
+    match state.processor.get_dead_letter(uuid).await {
+        Ok(Some(dead_letter)) => (StatusCode::OK, Json(ApiResponse::success(dead_letter))),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<DeadLetter>::error(format!(
+                "Settlement {} is not dead-lettered", uuid
+            ))),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<DeadLetter>::error(format!(
+                "Failed to get dead letter: {}", e
+            ))),
+        ),
+    }
+}
+
+/// Let a request through only with `Authorization: Bearer <admin_token>`;
+/// without a configured token every request is refused
+async fn require_admin_token(
+    State(admin_token): State<Option<String>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(admin_token) = admin_token else {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<serde_json::Value>::error(
+                "Admin endpoints are disabled; set monitoring.admin_token".to_string()
+            )),
+        )
+            .into_response();
+    };
+
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match presented {
+        Some(token) if tokens_match(token, &admin_token) => next.run(request).await,
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(ApiResponse::<serde_json::Value>::error(
+                "Missing or invalid admin token".to_string()
+            )),
+        )
+            .into_response(),
+    }
+}
+
+/// Compare tokens in time independent of where they first differ
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Requeue a dead-lettered settlement; the optional body overrides gas settings
+async fn requeue_dead_letter(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    gas_overrides: Option<Json<GasOverrides>>,
+) -> impl IntoResponse {
+    let gas_overrides = gas_overrides.map(|Json(gas)| gas).unwrap_or_default();
+    dead_letter_action(&id, "requeued", |uuid| {
+        state.processor.requeue_dead_letter(uuid, gas_overrides, ADMIN_API_ACTOR)
+    })
+    .await
+}
+
+/// Permanently drop a dead-lettered settlement
+async fn discard_dead_letter(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    dead_letter_action(&id, "discarded", |uuid| {
+        state.processor.discard_dead_letter(uuid, ADMIN_API_ACTOR)
+    })
+    .await
+}
+
+/// Shared response handling for requeue and discard
+async fn dead_letter_action<F, Fut>(
+    id: &str,
+    action: &str,
+    run: F,
+) -> (StatusCode, Json<ApiResponse<serde_json::Value>>)
+where
+    F: FnOnce(Uuid) -> Fut,
+    Fut: std::future::Future<Output = Result<bool, crate::types::SettlementError>>,
+{
+    let Ok(uuid) = Uuid::parse_str(id) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("Invalid settlement ID format".to_string())),
+        );
+    };
+
+    match run(uuid).await {
+        Ok(true) => (
+            StatusCode::OK,
             Json(ApiResponse::success(serde_json::json!({
                 "settlement_id": uuid,
-                "note": "Implementation pending - requires additional database queries"
-            })))
-        }
-        Err(_) => Json(ApiResponse::<serde_json::Value>::error(
-            "Invalid settlement ID format".to_string()
-        )),
+                "action": action,
+            }))),
+        ),
+        Ok(false) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error(format!("Settlement {} is not dead-lettered", uuid))),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(format!("Failed to update dead letter: {}", e))),
+        ),
     }
 }
 
@@ -425,8 +704,8 @@ async fn settlement_details(
 mod tests {
     use super::*;
     use crate::types::{
-        AptosConfig, DatabaseConfig, ProcessingConfig, RelayerConfig, 
-        SolanaConfig, MonitoringConfig,
+        AptosAsset, AptosConfig, DatabaseConfig, FinalityPolicy, MonitoringConfig, ProcessingConfig,
+        RelayerConfig, SolanaConfig,
     };
 
     fn create_test_config() -> MonitoringConfig {
@@ -435,6 +714,7 @@ mod tests {
             health_check_port: 8080,
             log_level: "info".to_string(),
             enable_metrics: true,
+            admin_token: None,
         }
     }
 
@@ -447,10 +727,111 @@ mod tests {
 
     #[tokio::test]
     async fn test_monitoring_server_creation() {
-        let config = create_test_config();
+        let _config = create_test_config();
         
         // Create mock processor (would need actual implementation)
         // For now, just test that the monitoring server can be created
         // when provided with valid config
     }
-}
\ No newline at end of file
+
+    fn create_relayer_config(monitoring: MonitoringConfig) -> RelayerConfig {
+        RelayerConfig {
+            solana: SolanaConfig {
+                rpc_url: "https://api.devnet.solana.com".to_string(),
+                fallback_rpc_urls: Vec::new(),
+                rpc_quorum: None,
+                rpc_timeout_ms: 10_000,
+                rpc_requests_per_second: 10,
+                max_concurrent_fetches: 8,
+                ws_url: None,
+                program_id: "11111111111111111111111111111112".to_string(),
+                commitment: "confirmed".to_string(),
+                poll_interval_ms: 1000,
+                max_retries: 3,
+                subscribe_logs: true,
+                finality: FinalityPolicy::Finalized,
+            },
+            aptos: AptosConfig {
+                rpc_url: "https://fullnode.testnet.aptoslabs.com/v1".to_string(),
+                contract_address: "0x1".to_string(),
+                vault_owner: "0x1".to_string(),
+                private_key: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+                max_gas_amount: 200000,
+                gas_unit_price: 100,
+                gas_margin_percent: 20,
+                min_gas_unit_price: 100,
+                max_gas_unit_price: 10000,
+                transaction_timeout_secs: 30,
+                transaction_expiry_secs: 60,
+                max_resubmissions: 2,
+                gas_price_bump_percent: 20,
+                account_address: None,
+                key_file: None,
+                key_profile: None,
+                assets: [("USDC".to_string(), AptosAsset::VaultCoin)].into(),
+            },
+            processing: ProcessingConfig {
+                max_concurrent_settlements: 5,
+                batch_size: 10,
+                batch_max_wait_ms: 500,
+                settlement_timeout_seconds: 100,
+                queue_capacity: 100,
+                lease_timeout_seconds: 120,
+                queue_pause_seconds: 300,
+            },
+            retry: crate::retry::RetryConfig::default(),
+            monitoring,
+            database: DatabaseConfig {
+                url: "memory:".to_string(),
+                max_connections: 5,
+                connection_timeout_secs: 30,
+            },
+        }
+    }
+
+    /// Serve the monitoring router on a local port and return its base URL
+    async fn serve_monitoring(admin_token: Option<&str>) -> String {
+        let monitoring = MonitoringConfig {
+            admin_token: admin_token.map(str::to_string),
+            ..create_test_config()
+        };
+        let processor = SettlementProcessor::new(create_relayer_config(monitoring.clone())).await.unwrap();
+        let server = MonitoringServer::new(monitoring, Arc::new(processor)).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let app = server.create_router();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_actions_require_the_admin_token() {
+        let client = reqwest::Client::new();
+        let discard = |url: &str| format!("{}/api/v1/dead-letters/{}/discard", url, Uuid::new_v4());
+
+        let url = serve_monitoring(Some("secret")).await;
+        let response = client.post(discard(&url)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = client.post(discard(&url)).bearer_auth("guess").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = client
+            .post(format!("{}/api/v1/dead-letters/{}/requeue", url, Uuid::new_v4()))
+            .json(&serde_json::json!({ "gas_unit_price": 1_000_000 }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // With the token the request reaches the handler, which finds no such dead letter
+        let response = client.post(discard(&url)).bearer_auth("secret").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        // Reading the queue needs no token
+        let response = client.get(format!("{}/api/v1/dead-letters", url)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Without a configured token the actions are refused outright
+        let url = serve_monitoring(None).await;
+        let response = client.post(discard(&url)).bearer_auth("").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,465 @@
+//! Retry policies and the destination chain circuit breaker
+//!
+//! Policies are looked up by error class (see [`SettlementError::class`]), so a
+//! timeout can back off differently from a network error. The same policies
+//! drive the settlement workers, the retry sweep and the source chain poll loop.
+
+use crate::{sync::lock_unpoisoned, types::SettlementError};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// Backoff settings for one class of error
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Attempts allowed in total, including the first
+    pub max_attempts: u32,
+    /// Delay after the first failure; doubles with every further failure
+    pub base_delay_ms: u64,
+    /// Upper bound on any single delay
+    pub max_delay_ms: u64,
+    /// Fraction of each delay that is randomized, from 0.0 to 1.0
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 5_000,
+            max_delay_ms: 300_000,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether another attempt may follow `attempt` failed ones
+    pub fn allows_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// Delay after `attempt` failures (1-based) before jitter is applied
+    pub fn ceiling(&self, attempt: u32) -> Duration {
+        let factor = 1u64
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u64::MAX);
+        let delay = self.base_delay_ms.saturating_mul(factor).min(self.max_delay_ms);
+        Duration::from_millis(delay)
+    }
+
+    /// Delay after `attempt` failures, shortened by a random share of up to `jitter`
+    /// so that settlements failing together do not retry together
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self.ceiling(attempt);
+        let spread = self.jitter.clamp(0.0, 1.0);
+        if spread == 0.0 {
+            return ceiling;
+        }
+        ceiling.mul_f64(1.0 - rand::rng().random_range(0.0..=spread))
+    }
+
+    fn validate(&self, name: &str) -> Result<(), SettlementError> {
+        if self.max_attempts == 0 {
+            return Err(SettlementError::ConfigError(format!(
+                "Retry policy {} must allow at least one attempt",
+                name
+            )));
+        }
+        if self.base_delay_ms == 0 {
+            return Err(SettlementError::ConfigError(format!(
+                "Retry policy {} base delay must be greater than 0",
+                name
+            )));
+        }
+        if self.base_delay_ms > self.max_delay_ms {
+            return Err(SettlementError::ConfigError(format!(
+                "Retry policy {} has a base delay above its maximum",
+                name
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err(SettlementError::ConfigError(format!(
+                "Retry policy {} jitter must be between 0.0 and 1.0",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Retry and circuit breaker configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Used for any error class without its own entry
+    pub default: RetryPolicy,
+    /// Per error class overrides, e.g. `timeout` or `network`
+    #[serde(default)]
+    pub classes: HashMap<String, RetryPolicy>,
+    /// Backoff for the source chain poll loop; polling never gives up, so
+    /// `max_attempts` is ignored
+    pub source_poll: RetryPolicy,
+    /// How often failed settlements are checked for a retry that has come due
+    pub sweep_interval_ms: u64,
+    pub circuit_breaker: CircuitBreakerConfig,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            default: RetryPolicy::default(),
+            classes: HashMap::new(),
+            source_poll: RetryPolicy {
+                max_attempts: 1,
+                base_delay_ms: 5_000,
+                max_delay_ms: 60_000,
+                jitter: 0.2,
+            },
+            sweep_interval_ms: 5_000,
+            circuit_breaker: CircuitBreakerConfig::default(),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Policy for an error class
+    pub fn policy(&self, error_class: &str) -> &RetryPolicy {
+        self.classes.get(error_class).unwrap_or(&self.default)
+    }
+
+    /// Check every policy and the breaker settings
+    pub fn validate(&self) -> Result<(), SettlementError> {
+        self.default.validate("default")?;
+        self.source_poll.validate("source_poll")?;
+        for (class, policy) in &self.classes {
+            policy.validate(class)?;
+        }
+
+        if self.sweep_interval_ms == 0 {
+            return Err(SettlementError::ConfigError(
+                "Retry sweep interval must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.circuit_breaker.failure_threshold == 0 {
+            return Err(SettlementError::ConfigError(
+                "Circuit breaker failure threshold must be greater than 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// When the circuit breaker opens and how long it stays open
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failed submissions that open the circuit
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before one probe submission is let through
+    pub reset_timeout_ms: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            reset_timeout_ms: 30_000,
+        }
+    }
+}
+
+/// Circuit breaker state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Submissions flow normally
+    #[default]
+    Closed,
+    /// Submissions are refused until the reset timeout passes
+    Open,
+    /// One probe submission is in flight to test whether the chain recovered
+    HalfOpen,
+}
+
+/// Point-in-time view of a circuit breaker for health checks and metrics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CircuitBreakerStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Number of times the circuit has opened since startup
+    pub trips: u64,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    status: CircuitBreakerStatus,
+    opened_at: Option<Instant>,
+}
+
+/// Stops submissions to a chain after repeated failures, then probes for recovery
+pub struct CircuitBreaker {
+    name: String,
+    config: CircuitBreakerConfig,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: impl Into<String>, config: CircuitBreakerConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Ask to submit. Refused while the circuit is open; once the reset timeout
+    /// has passed, exactly one caller is let through as a probe.
+    pub fn acquire(&self) -> Result<(), SettlementError> {
+        let reset_timeout = Duration::from_millis(self.config.reset_timeout_ms);
+        let mut state = lock_unpoisoned(&self.state);
+
+        match state.status.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open => {
+                let elapsed = state.opened_at.map_or(reset_timeout, |at| at.elapsed());
+                if elapsed >= reset_timeout {
+                    info!("Circuit for {} half-open, probing", self.name);
+                    state.status.state = CircuitState::HalfOpen;
+                    Ok(())
+                } else {
+                    Err(self.open_error(reset_timeout - elapsed))
+                }
+            }
+            // A probe is already in flight
+            CircuitState::HalfOpen => Err(self.open_error(reset_timeout)),
+        }
+    }
+
+    /// Like [`acquire`](Self::acquire), but the returned attempt records a
+    /// failure if it is dropped before an outcome is recorded on it
+    pub fn attempt(&self) -> Result<BreakerAttempt<'_>, SettlementError> {
+        self.acquire()?;
+        Ok(BreakerAttempt {
+            breaker: self,
+            recorded: false,
+        })
+    }
+
+    /// Record a submission that reached the chain
+    pub fn record_success(&self) {
+        let mut state = lock_unpoisoned(&self.state);
+        if state.status.state != CircuitState::Closed {
+            info!("Circuit for {} closed", self.name);
+        }
+        state.status.state = CircuitState::Closed;
+        state.status.consecutive_failures = 0;
+        state.opened_at = None;
+    }
+
+    /// Record a submission that failed for reasons on the chain's side
+    pub fn record_failure(&self) {
+        let mut state = lock_unpoisoned(&self.state);
+        state.status.consecutive_failures += 1;
+
+        let trip = match state.status.state {
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => {
+                state.status.consecutive_failures >= self.config.failure_threshold
+            }
+            CircuitState::Open => false,
+        };
+        if trip {
+            warn!(
+                "Circuit for {} opened after {} consecutive failures",
+                self.name, state.status.consecutive_failures
+            );
+            state.status.state = CircuitState::Open;
+            state.status.trips += 1;
+            state.opened_at = Some(Instant::now());
+        }
+    }
+
+    /// Record the outcome of a submission made after [`acquire`](Self::acquire).
+    ///
+    /// Only transient errors and unanswered status checks count against the
+    /// chain; an instruction the contract rejects still shows the chain is up.
+    pub fn record<T>(&self, outcome: &Result<T, SettlementError>) {
+        match outcome {
+            Err(e) if e.is_chain_failure() => self.record_failure(),
+            _ => self.record_success(),
+        }
+    }
+
+    pub fn status(&self) -> CircuitBreakerStatus {
+        lock_unpoisoned(&self.state).status.clone()
+    }
+
+    fn open_error(&self, retry_after: Duration) -> SettlementError {
+        SettlementError::CircuitOpen {
+            chain: self.name.clone(),
+            retry_after,
+        }
+    }
+}
+
+/// A submission let through by [`CircuitBreaker::attempt`].
+///
+/// Dropping it without recording an outcome, e.g. because the caller's timeout
+/// fired, counts as a failure. Otherwise an abandoned half-open probe would
+/// leave the circuit half-open, refusing every later submission.
+#[must_use = "dropping an attempt records a failure"]
+pub struct BreakerAttempt<'a> {
+    breaker: &'a CircuitBreaker,
+    recorded: bool,
+}
+
+impl BreakerAttempt<'_> {
+    /// See [`CircuitBreaker::record_success`]
+    pub fn record_success(mut self) {
+        self.recorded = true;
+        self.breaker.record_success();
+    }
+
+    /// See [`CircuitBreaker::record_failure`]
+    pub fn record_failure(mut self) {
+        self.recorded = true;
+        self.breaker.record_failure();
+    }
+
+    /// See [`CircuitBreaker::record`]
+    pub fn record<T>(mut self, outcome: &Result<T, SettlementError>) {
+        self.recorded = true;
+        self.breaker.record(outcome);
+    }
+}
+
+impl Drop for BreakerAttempt<'_> {
+    fn drop(&mut self) {
+        if !self.recorded {
+            warn!("Submission to {} abandoned before it finished", self.breaker.name);
+            self.breaker.record_failure();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_delays() {
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            base_delay_ms: 100,
+            max_delay_ms: 350,
+            jitter: 0.0,
+        };
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(350));
+        assert_eq!(policy.delay(200), Duration::from_millis(350));
+        assert!(policy.allows_retry(3));
+        assert!(!policy.allows_retry(4));
+
+        let jittered = RetryPolicy { jitter: 0.5, ..policy };
+        for _ in 0..100 {
+            let delay = jittered.delay(2);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn test_policy_lookup_and_validation() {
+        let mut config = RetryConfig::default();
+        let timeout = RetryPolicy {
+            max_attempts: 10,
+            ..RetryPolicy::default()
+        };
+        config.classes.insert("timeout".to_string(), timeout);
+
+        assert_eq!(config.policy("timeout"), &timeout);
+        assert_eq!(config.policy("network"), &config.default);
+        assert!(config.validate().is_ok());
+
+        config.classes.insert(
+            "network".to_string(),
+            RetryPolicy {
+                jitter: 1.5,
+                ..RetryPolicy::default()
+            },
+        );
+        assert!(config.validate().is_err());
+
+        // A zero delay would retry in a tight loop, a zero sweep would panic
+        config.classes.clear();
+        config.default.base_delay_ms = 0;
+        config.default.max_delay_ms = 0;
+        assert!(config.validate().is_err());
+        config.default = RetryPolicy::default();
+        config.sweep_interval_ms = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_and_probes() {
+        let breaker = CircuitBreaker::new(
+            "aptos",
+            CircuitBreakerConfig {
+                failure_threshold: 2,
+                reset_timeout_ms: 20,
+            },
+        );
+
+        let failure: Result<(), _> = Err(SettlementError::NetworkError("down".to_string()));
+        breaker.record(&failure);
+        assert!(breaker.acquire().is_ok());
+        breaker.record(&failure);
+        assert_eq!(breaker.status().state, CircuitState::Open);
+        assert_eq!(breaker.status().trips, 1);
+        assert!(matches!(
+            breaker.acquire(),
+            Err(SettlementError::CircuitOpen { .. })
+        ));
+
+        // After the reset timeout a single probe goes through
+        std::thread::sleep(Duration::from_millis(25));
+        assert!(breaker.acquire().is_ok());
+        assert_eq!(breaker.status().state, CircuitState::HalfOpen);
+        assert!(breaker.acquire().is_err());
+
+        // A failed probe reopens the circuit, a successful one closes it
+        breaker.record(&failure);
+        assert_eq!(breaker.status().state, CircuitState::Open);
+        assert_eq!(breaker.status().trips, 2);
+        std::thread::sleep(Duration::from_millis(25));
+        assert!(breaker.acquire().is_ok());
+        breaker.record(&Ok::<_, SettlementError>(()));
+        assert_eq!(
+            breaker.status(),
+            CircuitBreakerStatus {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                trips: 2,
+            }
+        );
+
+        // Rejected instructions show the chain is reachable
+        let rejected: Result<(), _> = Err(SettlementError::InvalidInstruction("bad".to_string()));
+        for _ in 0..5 {
+            breaker.record(&rejected);
+        }
+        assert_eq!(breaker.status().state, CircuitState::Closed);
+
+        // Status checks the chain could not answer count against it
+        let unknown: Result<(), _> = Err(SettlementError::StatusUnknown("no answer".to_string()));
+        breaker.record(&unknown);
+        breaker.record(&unknown);
+        assert_eq!(breaker.status().state, CircuitState::Open);
+    }
+}
@@ -1,61 +1,89 @@
 use crate::{
-    chains::{aptos::AptosChain, solana::SolanaChain, DestinationChain, SourceChain},
-    database::{Database, DatabaseStatistics},
+    chains::{
+        aptos::{AptosChain, CircuitBreakerChain},
+        solana::{SolanaChain, SourceEvent, SourceEventStream},
+        DestinationChain, SourceChain,
+    },
+    database::{
+        self, DatabaseStatistics, DeadLetter, QueueLease, SettlementStore, StatusTransition,
+    },
+    retry::{CircuitBreaker, CircuitState, RetryConfig},
     types::{
-        ProcessingConfig, RelayerConfig, RelayerMetrics, SettlementError, SettlementInstruction,
-        SettlementResult, SettlementStatus,
+        GasOverrides, ProcessingConfig, RelayerConfig, RelayerMetrics, RetryClass,
+        SettlementError, SettlementInstruction, SettlementResult, SettlementStatus,
     },
 };
-use backoff::{future::retry, ExponentialBackoff};
 use chrono::Utc;
-use futures::StreamExt;
+use futures_util::{future::join_all, StreamExt};
 use std::{
     collections::HashMap,
-    sync::{
-        atomic::{AtomicU64, Ordering},
-        Arc,
-    },
+    sync::Arc,
     time::{Duration, Instant},
 };
 use tokio::{
-    sync::{mpsc, RwLock, Semaphore},
+    sync::{RwLock, Semaphore},
+    task::JoinHandle,
     time::{interval, sleep},
 };
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+/// Actor recorded on status changes made by the retry sweep
+const RETRY_ACTOR: &str = "retry-processor";
+
+/// Actor recorded on status changes made by [`SettlementProcessor::process_instruction`]
+const MANUAL_ACTOR: &str = "relayer";
+
 /// Core settlement processor that orchestrates cross-chain settlements
 pub struct SettlementProcessor {
     config: RelayerConfig,
     source_chain: Arc<dyn SourceChain>,
     destination_chain: Arc<dyn DestinationChain>,
-    database: Arc<Database>,
+    database: Arc<dyn SettlementStore>,
     metrics: Arc<RwLock<RelayerMetrics>>,
     processing_semaphore: Arc<Semaphore>,
-    instruction_queue: mpsc::UnboundedSender<SettlementInstruction>,
+    relayer_id: Uuid,
     processing_times: Arc<RwLock<Vec<Duration>>>,
+    /// Workers stop leasing until this instant after an error that blocks every settlement
+    paused_until: Arc<RwLock<Option<Instant>>>,
+    /// Stops submissions to Aptos while it keeps failing
+    breaker: Arc<CircuitBreaker>,
+    /// Drains the source chain's event stream; aborting it stops the listener
+    source_ingest: RwLock<Option<JoinHandle<()>>>,
     start_time: Instant,
 }
 
+/// Shared state a queue worker needs to settle its leases
+#[derive(Clone)]
+struct WorkerContext {
+    database: Arc<dyn SettlementStore>,
+    destination_chain: Arc<dyn DestinationChain>,
+    metrics: Arc<RwLock<RelayerMetrics>>,
+    processing_times: Arc<RwLock<Vec<Duration>>>,
+    paused_until: Arc<RwLock<Option<Instant>>>,
+    config: ProcessingConfig,
+    retry: RetryConfig,
+}
+
 impl SettlementProcessor {
     /// Create new settlement processor
     pub async fn new(config: RelayerConfig) -> Result<Self, SettlementError> {
         info!("Initializing settlement processor");
 
-        // Create database connection
-        let database = Arc::new(Database::new(&config.database).await?);
-
-        // Create instruction queue
-        let (instruction_sender, instruction_receiver) = mpsc::unbounded_channel();
+        // Open the settlement store selected by the database URL
+        let database = database::connect(&config.database).await?;
 
         // Create source chain (Solana)
-        let source_chain = Arc::new(SolanaChain::new(
-            config.solana.clone(),
-            Some(instruction_sender.clone()),
-        )?);
+        let source_chain = Arc::new(
+            SolanaChain::new(config.solana.clone())?
+                .with_poll_retry(config.retry.source_poll)
+                .with_store(Arc::clone(&database)),
+        );
 
-        // Create destination chain (Aptos)
-        let destination_chain = Arc::new(AptosChain::new(config.aptos.clone()).await?);
+        // Create destination chain (Aptos) behind a circuit breaker
+        let breaker = Arc::new(CircuitBreaker::new("aptos", config.retry.circuit_breaker));
+        let aptos: Arc<dyn DestinationChain> = Arc::new(AptosChain::new(config.aptos.clone()).await?);
+        let destination_chain = Arc::new(CircuitBreakerChain::new(aptos, Arc::clone(&breaker)));
 
         // Initialize metrics
         let metrics = Arc::new(RwLock::new(RelayerMetrics::default()));
@@ -70,13 +98,16 @@ impl SettlementProcessor {
             database,
             metrics,
             processing_semaphore,
-            instruction_queue: instruction_sender,
+            relayer_id: Uuid::new_v4(),
             processing_times: Arc::new(RwLock::new(Vec::new())),
+            paused_until: Arc::new(RwLock::new(None)),
+            breaker,
+            source_ingest: RwLock::new(None),
             start_time: Instant::now(),
         };
 
         // Start background tasks
-        processor.start_instruction_processor(instruction_receiver).await;
+        processor.start_queue_workers().await;
         processor.start_metrics_updater().await;
         processor.start_retry_processor().await;
 
@@ -88,164 +119,590 @@ impl SettlementProcessor {
     pub async fn start(&self) -> Result<(), SettlementError> {
         info!("Starting Cyrus Protocol Relayer");
 
-        // Start source chain event listener
-        self.source_chain.start_event_listener().await?;
+        // Start source chain event listener from its last checkpoint
+        let checkpoint = self
+            .database
+            .get_source_cursor(&self.source_chain.source_id())
+            .await?;
+        let events = self.source_chain.event_stream(checkpoint).await?;
+        self.start_queue_ingest(events).await;
 
         // Process any pending instructions from database
         self.process_pending_instructions().await?;
 
+        info!("Settlement queue depth: {}", self.database.queue_depth().await?);
+
         info!("Cyrus Protocol Relayer started successfully");
         Ok(())
     }
 
-    /// Start instruction processor task
-    async fn start_instruction_processor(
-        &self,
-        mut instruction_receiver: mpsc::UnboundedReceiver<SettlementInstruction>,
-    ) {
+    /// Move instructions from the source event stream into the durable queue
+    /// and persist its checkpoints.
+    ///
+    /// Stops pulling from the stream while the queue holds `queue_capacity`
+    /// instructions, which in turn pauses the listener instead of growing memory.
+    async fn start_queue_ingest(&self, mut events: SourceEventStream) {
         let database = Arc::clone(&self.database);
-        let destination_chain = Arc::clone(&self.destination_chain);
-        let metrics = Arc::clone(&self.metrics);
-        let semaphore = Arc::clone(&self.processing_semaphore);
-        let processing_times = Arc::clone(&self.processing_times);
-        let config = self.config.processing.clone();
-
-        tokio::spawn(async move {
-            while let Some(instruction) = instruction_receiver.recv().await {
+        let source = self.source_chain.source_id();
+        let capacity = self.config.processing.queue_capacity as u64;
+        let retry_policy = *self.config.retry.policy("database");
+        let poll_interval = Duration::from_millis(self.config.solana.poll_interval_ms);
+
+        let ingest = tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                let (instruction, ack) = match event {
+                    SourceEvent::Settlement(instruction, ack) => (instruction, ack),
+                    SourceEvent::Checkpoint(cursor) => {
+                        if let Err(e) = database.save_source_cursor(&source, &cursor.signature, cursor.slot).await {
+                            // The next checkpoint supersedes it; a restart only re-reads a little more
+                            warn!("Failed to save {} checkpoint {}: {}", source, cursor.signature, e);
+                        }
+                        continue;
+                    }
+                };
                 info!("Received settlement instruction: {}", instruction.id);
 
-                // Store instruction in database
-                if let Err(e) = database.store_instruction(&instruction).await {
-                    error!("Failed to store instruction: {}", e);
-                    continue;
+                Self::wait_for_queue_capacity(&database, capacity, poll_interval).await;
+
+                // Never drop an instruction: keep trying until the store accepts it
+                let mut failures = 0;
+                loop {
+                    match database.enqueue_instruction(&instruction).await {
+                        Ok(true) => break,
+                        Ok(false) => {
+                            // Same source event seen again (re-delivery, restart, overlapping poll)
+                            debug!("Duplicate settlement instruction skipped: {}", instruction.id);
+                            break;
+                        }
+                        Err(e) => {
+                            error!("Failed to queue instruction {}: {}", instruction.id, e);
+                            failures += 1;
+                            sleep(retry_policy.delay(failures)).await;
+                        }
+                    }
                 }
+                // Stored, so the listener may stop holding it for finality
+                ack.ack();
+            }
+            debug!("Source event stream ended");
+        });
 
-                // Acquire semaphore permit for processing
-                let permit = semaphore.acquire().await.unwrap();
-
-                // Spawn processing task
-                let database_clone = Arc::clone(&database);
-                let destination_chain_clone = Arc::clone(&destination_chain);
-                let metrics_clone = Arc::clone(&metrics);
-                let processing_times_clone = Arc::clone(&processing_times);
-                let config_clone = config.clone();
+        // A restarted listener replaces the previous one
+        if let Some(previous) = self.source_ingest.write().await.replace(ingest) {
+            previous.abort();
+        }
+    }
 
-                tokio::spawn(async move {
-                    let start_time = Instant::now();
+    /// Wait until the durable queue has room for another instruction
+    async fn wait_for_queue_capacity(
+        database: &Arc<dyn SettlementStore>,
+        capacity: u64,
+        poll_interval: Duration,
+    ) {
+        loop {
+            match database.queue_depth().await {
+                Ok(depth) if depth < capacity => return,
+                Ok(depth) => debug!("Settlement queue full ({} instructions), waiting", depth),
+                Err(e) => error!("Failed to read queue depth: {}", e),
+            }
+            sleep(poll_interval).await;
+        }
+    }
 
-                    let result = Self::process_instruction_with_retry(
-                        &instruction,
-                        destination_chain_clone,
-                        &config_clone,
-                    ).await;
+    /// Start workers that lease instructions from the durable queue.
+    ///
+    /// Each worker leases up to `batch_size` instructions at a time and settles
+    /// them together. A worker that dies mid-settlement leaves its leases to
+    /// expire, after which another worker (possibly in another relayer instance)
+    /// picks them up.
+    async fn start_queue_workers(&self) {
+        let lease_duration = Duration::from_secs(self.config.processing.lease_timeout_seconds);
+        let poll_interval = Duration::from_millis(self.config.solana.poll_interval_ms);
+
+        let context = WorkerContext {
+            database: Arc::clone(&self.database),
+            destination_chain: Arc::clone(&self.destination_chain),
+            metrics: Arc::clone(&self.metrics),
+            processing_times: Arc::clone(&self.processing_times),
+            paused_until: Arc::clone(&self.paused_until),
+            config: self.config.processing.clone(),
+            retry: self.config.retry.clone(),
+        };
 
-                    let processing_time = start_time.elapsed();
+        for worker in 0..self.config.processing.max_concurrent_settlements {
+            let worker_id = format!("{}-{}", self.relayer_id, worker);
+            let semaphore = Arc::clone(&self.processing_semaphore);
+            let context = context.clone();
+
+            tokio::spawn(async move {
+                loop {
+                    let pause = *context.paused_until.read().await;
+                    let remaining =
+                        pause.and_then(|until| until.checked_duration_since(Instant::now()));
+                    if let Some(remaining) = remaining {
+                        debug!("Worker {} waiting {:?} for the queue to resume", worker_id, remaining);
+                        sleep(remaining).await;
+                        continue;
+                    }
 
-                    // Update processing times
+                    let leases = match Self::lease_batch(
+                        &context,
+                        &worker_id,
+                        lease_duration,
+                        poll_interval,
+                    )
+                    .await
                     {
-                        let mut times = processing_times_clone.write().await;
-                        times.push(processing_time);
-                        // Keep only last 1000 processing times
-                        if times.len() > 1000 {
-                            times.remove(0);
+                        Ok(leases) => leases,
+                        Err(e) => {
+                            error!("Worker {} failed to lease instructions: {}", worker_id, e);
+                            sleep(poll_interval).await;
+                            continue;
                         }
-                    }
+                    };
 
-                    // Store result in database
-                    if let Err(e) = database_clone.store_result(&result).await {
-                        error!("Failed to store result: {}", e);
+                    if leases.is_empty() {
+                        sleep(poll_interval).await;
+                        continue;
                     }
 
-                    // Update metrics
-                    Self::update_metrics_for_result(&result, &metrics_clone).await;
+                    // Acquire semaphore permit so shutdown can wait for in-flight work
+                    let Ok(permit) = semaphore.acquire().await else {
+                        return;
+                    };
 
-                    // Log result
-                    match result.status {
-                        SettlementStatus::Completed => {
-                            info!(
-                                "Settlement completed successfully: {} in {:?}",
-                                instruction.id, processing_time
-                            );
-                        }
-                        SettlementStatus::Failed => {
-                            error!(
-                                "Settlement failed: {} - {}",
-                                instruction.id,
-                                result.error_message.unwrap_or_default()
-                            );
-                        }
-                        _ => {}
-                    }
+                    Self::process_leases(leases, &context).await;
 
                     drop(permit);
-                });
+                }
+            });
+        }
+    }
+
+    /// Lease up to `batch_size` instructions.
+    ///
+    /// Once the first instruction is in hand, keeps topping the batch up for at
+    /// most `batch_max_wait_ms` so a quiet queue does not hold settlements back.
+    async fn lease_batch(
+        context: &WorkerContext,
+        worker_id: &str,
+        lease_duration: Duration,
+        poll_interval: Duration,
+    ) -> Result<Vec<QueueLease>, SettlementError> {
+        let batch_size = context.config.batch_size.max(1);
+        let mut leases = context
+            .database
+            .lease_instructions(worker_id, batch_size, lease_duration)
+            .await?;
+        if leases.is_empty() {
+            return Ok(leases);
+        }
+
+        let deadline = Instant::now() + Duration::from_millis(context.config.batch_max_wait_ms);
+        while leases.len() < batch_size {
+            let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                break;
+            };
+            sleep(remaining.min(poll_interval)).await;
+
+            match context
+                .database
+                .lease_instructions(worker_id, batch_size - leases.len(), lease_duration)
+                .await
+            {
+                Ok(more) => leases.extend(more),
+                Err(e) => {
+                    // Settle what we already hold rather than letting those leases lapse
+                    warn!("Worker {} failed to fill batch: {}", worker_id, e);
+                    break;
+                }
             }
-        });
+        }
+
+        Ok(leases)
     }
 
-    /// Process instruction with retry logic
-    async fn process_instruction_with_retry(
-        instruction: &SettlementInstruction,
-        destination_chain: Arc<dyn DestinationChain>,
-        config: &ProcessingConfig,
-    ) -> SettlementResult {
-        let backoff = ExponentialBackoff {
-            max_elapsed_time: Some(Duration::from_secs(config.settlement_timeout_seconds)),
-            max_interval: Duration::from_secs(config.retry_delay_seconds),
-            ..Default::default()
+    /// Settle a set of leased instructions.
+    ///
+    /// Instructions requeued with their own gas settings are submitted on their
+    /// own; the rest go out together in a single destination transaction. All
+    /// submissions run at once, so the whole set finishes within one settlement
+    /// timeout. Config validation makes `lease_timeout_seconds` cover that, the
+    /// batch wait before it and the bookkeeping around it, so no lease lapses.
+    async fn process_leases(leases: Vec<QueueLease>, context: &WorkerContext) {
+        let mut ready = Vec::with_capacity(leases.len());
+        for lease in leases {
+            if Self::start_lease(&lease, context).await {
+                ready.push(lease);
+            }
+        }
+
+        let (batch, custom_gas): (Vec<_>, Vec<_>) = ready
+            .into_iter()
+            .partition(|lease| lease.gas_overrides == GasOverrides::default());
+
+        let custom_gas = join_all(custom_gas.into_iter().map(|lease| async move {
+            let start_time = Instant::now();
+            let (result, error) = Self::submit_instruction(
+                &lease.instruction,
+                Arc::clone(&context.destination_chain),
+                &context.config,
+                &lease.gas_overrides,
+            ).await;
+            Self::finish_lease(lease, result, error, start_time.elapsed(), context).await;
+        }));
+        tokio::join!(custom_gas, Self::process_batch(batch, context));
+    }
+
+    /// Submit leased instructions without gas overrides in one destination transaction
+    async fn process_batch(batch: Vec<QueueLease>, context: &WorkerContext) {
+        if batch.is_empty() {
+            return;
+        }
+
+        let start_time = Instant::now();
+        let outcomes = if let [lease] = batch.as_slice() {
+            vec![
+                Self::submit_instruction(
+                    &lease.instruction,
+                    Arc::clone(&context.destination_chain),
+                    &context.config,
+                    &lease.gas_overrides,
+                ).await,
+            ]
+        } else {
+            let instructions: Vec<SettlementInstruction> =
+                batch.iter().map(|lease| lease.instruction.clone()).collect();
+            Self::submit_batch(
+                &instructions,
+                Arc::clone(&context.destination_chain),
+                &context.config,
+            ).await
         };
+        let processing_time = start_time.elapsed();
 
-        let mut retry_count = 0;
-
-        let result = retry(backoff, || async {
-            retry_count += 1;
-            
-            debug!("Processing settlement attempt {}: {}", retry_count, instruction.id);
-
-            match destination_chain.submit_settlement(instruction).await {
-                Ok(mut result) => {
-                    result.retry_count = retry_count - 1;
-                    
-                    if result.status == SettlementStatus::Completed {
-                        Ok(result)
-                    } else {
-                        Err(backoff::Error::Transient {
-                            err: SettlementError::TransactionFailed(
-                                result.error_message.unwrap_or_default()
-                            ),
-                            retry_after: None,
-                        })
-                    }
+        for (lease, (result, error)) in batch.into_iter().zip(outcomes) {
+            Self::finish_lease(lease, result, error, processing_time, context).await;
+        }
+    }
+
+    /// Claim a leased instruction for processing.
+    ///
+    /// Returns `false` when the instruction should not be submitted; it has
+    /// then already been acked or released.
+    async fn start_lease(lease: &QueueLease, context: &WorkerContext) -> bool {
+        let database = &context.database;
+        let instruction = &lease.instruction;
+        let worker_id = &lease.worker_id;
+
+        if lease.attempts > 1 {
+            warn!(
+                "Instruction {} leased again (attempt {}), a previous lease expired",
+                instruction.id, lease.attempts
+            );
+        }
+
+        // A previous lease holder may have settled it before crashing
+        match database.get_settlement(instruction.id).await {
+            Ok(Some((_, result))) if database::TERMINAL_STATUSES.contains(&result.status) => {
+                debug!(
+                    "Instruction already {}, dropping from queue: {}",
+                    result.status, instruction.id
+                );
+                if let Err(e) = database.ack_instruction(instruction.id, worker_id).await {
+                    error!("Failed to ack instruction {}: {}", instruction.id, e);
                 }
-                Err(e) => {
-                    warn!("Settlement attempt {} failed: {}", retry_count, e);
-                    
-                    // Determine if error is retryable
-                    let is_retryable = match &e {
-                        SettlementError::NetworkError(_) => true,
-                        SettlementError::Timeout(_) => true,
-                        SettlementError::TransactionFailed(_) => true,
-                        SettlementError::InsufficientBalance { .. } => false,
-                        SettlementError::AlreadyProcessed(_) => false,
-                        SettlementError::InvalidInstruction(_) => false,
-                        _ => true,
-                    };
+                return false;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!("Failed to load settlement {}: {}", instruction.id, e);
+                let delay = context.retry.policy("database").delay(lease.attempts);
+                if let Err(e) = database.release_instruction(instruction.id, worker_id, delay).await {
+                    error!("Failed to release instruction {}: {}", instruction.id, e);
+                }
+                return false;
+            }
+        }
 
-                    if is_retryable && retry_count < config.retry_attempts {
-                        Err(backoff::Error::Transient {
-                            err: e,
-                            retry_after: Some(Duration::from_secs(config.retry_delay_seconds)),
-                        })
-                    } else {
-                        Err(backoff::Error::Permanent(e))
+        let reason = format!("Leased (attempt {})", lease.attempts);
+        match database
+            .transition_status(instruction.id, SettlementStatus::Processing, worker_id, Some(&reason))
+            .await
+        {
+            Ok(()) => true,
+            Err(e @ SettlementError::InvalidTransition { .. }) => {
+                // Nothing can legally process it from here, so holding the lease only delays others
+                error!("Dropping instruction {} from queue: {}", instruction.id, e);
+                if let Err(e) = database.ack_instruction(instruction.id, worker_id).await {
+                    error!("Failed to ack instruction {}: {}", instruction.id, e);
+                }
+                false
+            }
+            Err(e) => {
+                error!("Failed to mark {} as processing: {}", instruction.id, e);
+                let delay = context.retry.policy("database").delay(lease.attempts);
+                if let Err(e) = database.release_instruction(instruction.id, worker_id, delay).await {
+                    error!("Failed to release instruction {}: {}", instruction.id, e);
+                }
+                false
+            }
+        }
+    }
+
+    /// Record the outcome of one leased instruction, then ack it once the result is stored.
+    ///
+    /// Retryable failures go back on the queue after the delay given by the
+    /// retry policy for their error class; failures that are not retryable or
+    /// have used up the policy's attempts are dead-lettered. Errors that would
+    /// fail every settlement pause the queue and hand the instruction back
+    /// instead of counting against it, as does not knowing whether the
+    /// settlement already happened on chain.
+    async fn finish_lease(
+        lease: QueueLease,
+        mut result: SettlementResult,
+        error: Option<SettlementError>,
+        processing_time: Duration,
+        context: &WorkerContext,
+    ) {
+        let database = &context.database;
+        let instruction = lease.instruction;
+        let worker_id = lease.worker_id;
+
+        result.retry_count = lease.attempts.saturating_sub(1);
+
+        // Update processing times
+        {
+            let mut times = context.processing_times.write().await;
+            times.push(processing_time);
+            // Keep only last 1000 processing times
+            if times.len() > 1000 {
+                times.remove(0);
+            }
+        }
+
+        let hold = match error.as_ref().map(|e| (e, e.retry_class())) {
+            Some((e, RetryClass::PauseQueue)) => {
+                let pause = match e {
+                    SettlementError::CircuitOpen { retry_after, .. } => *retry_after,
+                    _ => Duration::from_secs(context.config.queue_pause_seconds),
+                };
+                error!("Pausing settlement queue for {:?}: {}", pause, e);
+                *context.paused_until.write().await = Some(Instant::now() + pause);
+                Some((e, pause))
+            }
+            // Submitting without knowing could pay twice, so ask again later
+            Some((e, RetryClass::Hold)) => {
+                let delay = context.retry.policy(e.class()).delay(lease.attempts);
+                warn!("Holding settlement {} for {:?}: {}", instruction.id, delay, e);
+                context.metrics.write().await.unknown_settlement_checks += 1;
+                Some((e, delay))
+            }
+            _ => None,
+        };
+
+        if let Some((e, delay)) = hold {
+            let reason = e.to_string();
+            if let Err(e) = database
+                .transition_status(
+                    instruction.id,
+                    SettlementStatus::Retrying,
+                    &worker_id,
+                    Some(&reason),
+                )
+                .await
+            {
+                error!("Failed to mark {} for retry: {}", instruction.id, e);
+            }
+            if let Err(e) = database.release_instruction(instruction.id, &worker_id, delay).await {
+                error!("Failed to release instruction {}: {}", instruction.id, e);
+            }
+            return;
+        }
+
+        // Keep the lease if the result was not stored; it is retried once the lease expires
+        if let Err(e) = database.store_result(&result, &worker_id).await {
+            error!("Failed to store result: {}", e);
+            return;
+        }
+
+        let error_class = error.as_ref().map(SettlementError::class);
+        let attempt = match database.record_attempt(&result, error_class).await {
+            Ok(attempt) => attempt,
+            Err(e) => {
+                error!("Failed to record attempt for {}: {}", instruction.id, e);
+                0
+            }
+        };
+
+        // Update metrics
+        Self::update_metrics_for_result(&result, &context.metrics).await;
+
+        // Log result
+        match result.status {
+            SettlementStatus::Completed => {
+                info!(
+                    "Settlement completed successfully: {} in {:?}",
+                    instruction.id, processing_time
+                );
+            }
+            SettlementStatus::Failed => {
+                let last_error = result.error_message.unwrap_or_default();
+                error!("Settlement failed: {} - {}", instruction.id, last_error);
+
+                let error_class = error_class.unwrap_or("unknown");
+                let policy = context.retry.policy(error_class);
+                let retryable = error.as_ref().is_none_or(SettlementError::is_retryable);
+                if retryable && policy.allows_retry(attempt) {
+                    let delay = policy.delay(attempt);
+                    let reason = format!(
+                        "Retrying in {:?} (attempt {} of {})",
+                        delay, attempt, policy.max_attempts
+                    );
+                    match database
+                        .transition_status(
+                            instruction.id,
+                            SettlementStatus::Retrying,
+                            &worker_id,
+                            Some(&reason),
+                        )
+                        .await
+                    {
+                        Ok(()) => {
+                            if let Err(e) =
+                                database.release_instruction(instruction.id, &worker_id, delay).await
+                            {
+                                error!("Failed to release instruction {}: {}", instruction.id, e);
+                            }
+                            return;
+                        }
+                        // Leave it failed for the retry processor to pick up
+                        Err(e) => error!("Failed to mark {} for retry: {}", instruction.id, e),
                     }
+                } else {
+                    // Dead-lettering also removes the instruction from the work queue
+                    Self::dead_letter(
+                        database,
+                        instruction.id,
+                        error_class,
+                        &last_error,
+                        &worker_id,
+                    )
+                    .await;
+                    return;
                 }
             }
-        }).await;
+            _ => {}
+        }
+
+        match database.ack_instruction(instruction.id, &worker_id).await {
+            Ok(true) => {}
+            Ok(false) => warn!("Lease on {} expired before it was acked", instruction.id),
+            Err(e) => error!("Failed to ack instruction {}: {}", instruction.id, e),
+        }
+    }
+
+    /// Park a settlement for operator review
+    async fn dead_letter(
+        database: &Arc<dyn SettlementStore>,
+        instruction_id: Uuid,
+        error_class: &str,
+        last_error: &str,
+        actor: &str,
+    ) {
+        match database
+            .dead_letter_instruction(instruction_id, error_class, last_error, actor)
+            .await
+        {
+            Ok(_) => warn!("Settlement dead-lettered: {} ({})", instruction_id, error_class),
+            Err(e) => error!("Failed to dead-letter settlement {}: {}", instruction_id, e),
+        }
+    }
+
+    /// Submit an instruction to the destination chain once.
+    ///
+    /// Returns the result along with the error that ended a failed settlement;
+    /// retrying is left to the queue so it follows the per-class retry policy.
+    /// An error showing the settlement already happened on chain yields a completed result.
+    async fn submit_instruction(
+        instruction: &SettlementInstruction,
+        destination_chain: Arc<dyn DestinationChain>,
+        config: &ProcessingConfig,
+        gas_overrides: &GasOverrides,
+    ) -> (SettlementResult, Option<SettlementError>) {
+        debug!("Submitting settlement: {}", instruction.id);
+
+        let timeout = Duration::from_secs(config.settlement_timeout_seconds);
+        let outcome = match tokio::time::timeout(
+            timeout,
+            destination_chain.submit_settlement_with_gas(instruction, gas_overrides),
+        )
+        .await
+        {
+            Ok(outcome) => outcome,
+            Err(_) => Err(SettlementError::Timeout(format!("no result after {:?}", timeout))),
+        };
+
+        Self::settlement_outcome(instruction, outcome)
+    }
+
+    /// Submit instructions together in one destination transaction.
+    ///
+    /// Returns one result per instruction, in order. When the batch fails as a
+    /// whole, every instruction in it fails with the same error.
+    async fn submit_batch(
+        instructions: &[SettlementInstruction],
+        destination_chain: Arc<dyn DestinationChain>,
+        config: &ProcessingConfig,
+    ) -> Vec<(SettlementResult, Option<SettlementError>)> {
+        debug!("Submitting batch of {} settlements", instructions.len());
+
+        let timeout = Duration::from_secs(config.settlement_timeout_seconds);
+        let outcome = match tokio::time::timeout(
+            timeout,
+            destination_chain.submit_settlement_batch(instructions),
+        )
+        .await
+        {
+            Ok(outcome) => outcome,
+            Err(_) => Err(SettlementError::Timeout(format!("no result after {:?}", timeout))),
+        };
+
+        match outcome {
+            Ok(items) => instructions
+                .iter()
+                .zip(items)
+                .map(|(instruction, item)| Self::settlement_outcome(instruction, item))
+                .collect(),
+            Err(e) => {
+                warn!("Batch of {} settlements failed: {}", instructions.len(), e);
+                instructions
+                    .iter()
+                    .map(|instruction| Self::settlement_outcome(instruction, Err(e.clone())))
+                    .collect()
+            }
+        }
+    }
 
-        match result {
-            Ok(result) => result,
-            Err(e) => SettlementResult::failure(instruction.id, e.to_string(), retry_count - 1),
+    /// Turn a destination chain outcome into a result and the error that ended a failed settlement
+    fn settlement_outcome(
+        instruction: &SettlementInstruction,
+        outcome: Result<SettlementResult, SettlementError>,
+    ) -> (SettlementResult, Option<SettlementError>) {
+        match outcome {
+            Ok(result) if result.status == SettlementStatus::Completed => (result, None),
+            Ok(result) => {
+                let e = SettlementError::TransactionFailed(result.error_message.unwrap_or_default());
+                (SettlementResult::failure(instruction.id, e.to_string(), 0), Some(e))
+            }
+            Err(e) if e.retry_class() == RetryClass::Resolved => {
+                info!("Settlement {} was already settled on chain: {}", instruction.id, e);
+                (SettlementResult::already_settled(instruction.id, 0), None)
+            }
+            Err(e) => {
+                warn!("Settlement {} failed: {}", instruction.id, e);
+                (SettlementResult::failure(instruction.id, e.to_string(), 0), Some(e))
+            }
         }
     }
 
@@ -258,7 +715,7 @@ impl SettlementProcessor {
         info!("Found {} pending instructions", pending_instructions.len());
 
         for instruction in pending_instructions {
-            if let Err(e) = self.instruction_queue.send(instruction) {
+            if let Err(e) = self.database.enqueue_instruction(&instruction).await {
                 error!("Failed to queue pending instruction: {}", e);
             }
         }
@@ -269,9 +726,11 @@ impl SettlementProcessor {
     /// Start metrics updater task
     async fn start_metrics_updater(&self) {
         let database = Arc::clone(&self.database);
+        let source_chain = Arc::clone(&self.source_chain);
         let destination_chain = Arc::clone(&self.destination_chain);
         let metrics = Arc::clone(&self.metrics);
         let processing_times = Arc::clone(&self.processing_times);
+        let breaker = Arc::clone(&self.breaker);
         let start_time = self.start_time;
 
         tokio::spawn(async move {
@@ -282,9 +741,11 @@ impl SettlementProcessor {
 
                 if let Err(e) = Self::update_metrics(
                     &database,
+                    &source_chain,
                     &destination_chain,
                     &metrics,
                     &processing_times,
+                    &breaker,
                     start_time,
                 ).await {
                     error!("Failed to update metrics: {}", e);
@@ -293,13 +754,17 @@ impl SettlementProcessor {
         });
     }
 
-    /// Start retry processor for failed settlements
+    /// Start retry processor for failed settlements.
+    ///
+    /// Picks up failed settlements the workers could not hand back to the queue.
+    /// Each is requeued once the backoff for its last error class has passed, or
+    /// dead-lettered if it has already used that class's attempts.
     async fn start_retry_processor(&self) {
         let database = Arc::clone(&self.database);
-        let instruction_queue = self.instruction_queue.clone();
+        let retry = self.config.retry.clone();
 
         tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(300)); // Check every 5 minutes
+            let mut interval = interval(Duration::from_millis(retry.sweep_interval_ms));
 
             loop {
                 interval.tick().await;
@@ -307,12 +772,59 @@ impl SettlementProcessor {
                 match database.get_instructions_by_status(SettlementStatus::Failed, Some(10)).await {
                     Ok(failed_settlements) => {
                         for (instruction, result) in failed_settlements {
-                            // Retry if not too many attempts and error is retryable
-                            if result.retry_count < 3 {
-                                info!("Retrying failed settlement: {}", instruction.id);
-                                if let Err(e) = instruction_queue.send(instruction) {
-                                    error!("Failed to queue retry instruction: {}", e);
+                            let attempts = match database.get_attempts(instruction.id).await {
+                                Ok(attempts) => attempts,
+                                Err(e) => {
+                                    error!("Failed to load attempts for {}: {}", instruction.id, e);
+                                    continue;
                                 }
+                            };
+
+                            let attempt_count = attempts.len() as u32;
+                            let error_class = attempts
+                                .last()
+                                .and_then(|attempt| attempt.error_class.as_deref())
+                                .unwrap_or("unknown");
+                            let policy = retry.policy(error_class);
+
+                            if !policy.allows_retry(attempt_count) {
+                                Self::dead_letter(
+                                    &database,
+                                    instruction.id,
+                                    error_class,
+                                    &result.error_message.unwrap_or_default(),
+                                    RETRY_ACTOR,
+                                )
+                                .await;
+                                continue;
+                            }
+
+                            // Still backing off from the last attempt
+                            let backoff = chrono::Duration::from_std(policy.ceiling(attempt_count))
+                                .unwrap_or_default();
+                            let due = attempts
+                                .last()
+                                .is_none_or(|attempt| attempt.recorded_at + backoff <= Utc::now());
+                            if !due {
+                                continue;
+                            }
+
+                            info!("Retrying failed settlement: {}", instruction.id);
+                            let reason = format!("Retry after {} attempt(s)", attempts.len());
+                            if let Err(e) = database
+                                .transition_status(
+                                    instruction.id,
+                                    SettlementStatus::Retrying,
+                                    RETRY_ACTOR,
+                                    Some(&reason),
+                                )
+                                .await
+                            {
+                                error!("Failed to mark {} for retry: {}", instruction.id, e);
+                                continue;
+                            }
+                            if let Err(e) = database.enqueue_instruction(&instruction).await {
+                                error!("Failed to queue retry instruction: {}", e);
                             }
                         }
                     }
@@ -326,14 +838,22 @@ impl SettlementProcessor {
 
     /// Update metrics
     async fn update_metrics(
-        database: &Database,
+        database: &Arc<dyn SettlementStore>,
+        source_chain: &Arc<dyn SourceChain>,
         destination_chain: &Arc<dyn DestinationChain>,
         metrics: &Arc<RwLock<RelayerMetrics>>,
         processing_times: &Arc<RwLock<Vec<Duration>>>,
+        breaker: &CircuitBreaker,
         start_time: Instant,
     ) -> Result<(), SettlementError> {
         let stats = database.get_statistics().await?;
-        let vault_balance = destination_chain.get_vault_balance().await.unwrap_or(0);
+        let vault_balance = match destination_chain.get_vault_balance("USDC").await {
+            Ok(balance) => Some(balance),
+            Err(e) => {
+                warn!("Failed to read vault balance, keeping the last known value: {}", e);
+                None
+            }
+        };
 
         let avg_processing_time = {
             let times = processing_times.read().await;
@@ -346,6 +866,8 @@ impl SettlementProcessor {
         };
 
         let mut metrics_guard = metrics.write().await;
+        let vault_balance_usdc = vault_balance
+            .map_or(metrics_guard.vault_balance_usdc, |balance| balance as f64 / 1_000_000.0);
         *metrics_guard = RelayerMetrics {
             total_settlements_processed: stats.total_instructions,
             successful_settlements: stats.completed_settlements,
@@ -358,8 +880,11 @@ impl SettlementProcessor {
                 None
             },
             uptime_seconds: start_time.elapsed().as_secs(),
-            vault_balance_usdc: vault_balance as f64 / 1_000_000.0,
+            vault_balance_usdc,
             total_volume_usdc: stats.total_volume_usdc(),
+            destination_circuit: breaker.status(),
+            source_fetch: source_chain.fetch_stats(),
+            unknown_settlement_checks: metrics_guard.unknown_settlement_checks,
         };
 
         Ok(())
@@ -396,20 +921,75 @@ impl SettlementProcessor {
         self.database.get_statistics().await
     }
 
+    /// Dead-lettered settlements awaiting operator review, oldest first
+    pub async fn list_dead_letters(&self, limit: Option<u32>) -> Result<Vec<DeadLetter>, SettlementError> {
+        self.database.list_dead_letters(limit).await
+    }
+
+    /// A dead-lettered settlement with its attempt history
+    pub async fn get_dead_letter(&self, instruction_id: Uuid) -> Result<Option<DeadLetter>, SettlementError> {
+        self.database.get_dead_letter(instruction_id).await
+    }
+
+    /// Send a dead-lettered settlement back to the work queue, optionally with new gas settings
+    pub async fn requeue_dead_letter(
+        &self,
+        instruction_id: Uuid,
+        gas_overrides: GasOverrides,
+        actor: &str,
+    ) -> Result<bool, SettlementError> {
+        let requeued = self
+            .database
+            .requeue_dead_letter(instruction_id, gas_overrides, actor)
+            .await?;
+        if requeued {
+            info!("Requeued dead-lettered settlement: {} ({:?})", instruction_id, gas_overrides);
+        }
+        Ok(requeued)
+    }
+
+    /// Permanently drop a dead-lettered settlement
+    pub async fn discard_dead_letter(
+        &self,
+        instruction_id: Uuid,
+        actor: &str,
+    ) -> Result<bool, SettlementError> {
+        let discarded = self.database.discard_dead_letter(instruction_id, actor).await?;
+        if discarded {
+            warn!("Discarded dead-lettered settlement: {}", instruction_id);
+        }
+        Ok(discarded)
+    }
+
+    /// A settlement with every status change it has gone through, oldest first
+    pub async fn get_settlement_timeline(
+        &self,
+        instruction_id: Uuid,
+    ) -> Result<Option<(SettlementInstruction, SettlementResult, Vec<StatusTransition>)>, SettlementError> {
+        let Some((instruction, result)) = self.database.get_settlement(instruction_id).await? else {
+            return Ok(None);
+        };
+        let timeline = self.database.get_timeline(instruction_id).await?;
+        Ok(Some((instruction, result, timeline)))
+    }
+
     /// Process a single instruction manually (for testing)
     pub async fn process_instruction(&self, instruction: SettlementInstruction) -> Result<SettlementResult, SettlementError> {
         // Store instruction
         self.database.store_instruction(&instruction).await?;
+        self.database
+            .transition_status(instruction.id, SettlementStatus::Processing, MANUAL_ACTOR, None)
+            .await?;
 
-        // Process with retry
-        let result = Self::process_instruction_with_retry(
+        let (result, _) = Self::submit_instruction(
             &instruction,
             Arc::clone(&self.destination_chain),
             &self.config.processing,
+            &GasOverrides::default(),
         ).await;
 
         // Store result
-        self.database.store_result(&result).await?;
+        self.database.store_result(&result, MANUAL_ACTOR).await?;
 
         // Update metrics
         Self::update_metrics_for_result(&result, &self.metrics).await;
@@ -435,6 +1015,10 @@ impl SettlementProcessor {
         let dest_health = self.destination_chain.check_health().await.unwrap_or(false);
         health.insert("aptos_chain".to_string(), dest_health);
 
+        // An open breaker means Aptos submissions are currently being refused
+        let circuit_closed = self.breaker.status().state != CircuitState::Open;
+        health.insert("aptos_circuit".to_string(), circuit_closed);
+
         // Check source chain
         let source_health = match self.source_chain.get_latest_slot().await {
             Ok(_) => true,
@@ -445,13 +1029,26 @@ impl SettlementProcessor {
         };
         health.insert("solana_chain".to_string(), source_health);
 
+        // Workers are idle while the queue is paused
+        let queue_running = self
+            .paused_until
+            .read()
+            .await
+            .is_none_or(|until| until <= Instant::now());
+        health.insert("settlement_queue".to_string(), queue_running);
+
         health
     }
 
     /// Graceful shutdown
     pub async fn shutdown(&self) -> Result<(), SettlementError> {
         info!("Shutting down settlement processor");
-        
+
+        // Stop listening first so nothing new is queued; dropping the stream stops the listener
+        if let Some(ingest) = self.source_ingest.write().await.take() {
+            ingest.abort();
+        }
+
         // Wait for ongoing settlements to complete
         let permits_needed = self.config.processing.max_concurrent_settlements;
         let _permits = self.processing_semaphore.acquire_many(permits_needed as u32).await;
@@ -465,19 +1062,26 @@ impl SettlementProcessor {
 mod tests {
     use super::*;
     use crate::types::{
-        AptosConfig, ChainId, DatabaseConfig, MonitoringConfig, ProcessingConfig, RelayerConfig,
-        SettlementInstruction, SolanaConfig, TransactionHash, Address,
+        AptosAsset, AptosConfig, DatabaseConfig, FinalityPolicy, MonitoringConfig, ProcessingConfig,
+        RelayerConfig, SolanaConfig,
     };
-    use chrono::Utc;
 
     fn create_test_config() -> RelayerConfig {
         RelayerConfig {
             solana: SolanaConfig {
                 rpc_url: "https://api.devnet.solana.com".to_string(),
+                fallback_rpc_urls: Vec::new(),
+                rpc_quorum: None,
+                rpc_timeout_ms: 10_000,
+                rpc_requests_per_second: 10,
+                max_concurrent_fetches: 8,
+                ws_url: None,
                 program_id: "11111111111111111111111111111112".to_string(),
                 commitment: "confirmed".to_string(),
                 poll_interval_ms: 1000,
                 max_retries: 3,
+                subscribe_logs: true,
+                finality: FinalityPolicy::Finalized,
             },
             aptos: AptosConfig {
                 rpc_url: "https://fullnode.testnet.aptoslabs.com/v1".to_string(),
@@ -486,23 +1090,37 @@ mod tests {
                 private_key: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
                 max_gas_amount: 200000,
                 gas_unit_price: 100,
+                gas_margin_percent: 20,
+                min_gas_unit_price: 100,
+                max_gas_unit_price: 10000,
                 transaction_timeout_secs: 30,
+                transaction_expiry_secs: 60,
+                max_resubmissions: 2,
+                gas_price_bump_percent: 20,
+                account_address: None,
+                key_file: None,
+                key_profile: None,
+                assets: [("USDC".to_string(), AptosAsset::VaultCoin)].into(),
             },
             processing: ProcessingConfig {
                 max_concurrent_settlements: 5,
                 batch_size: 10,
-                retry_attempts: 3,
-                retry_delay_seconds: 5,
+                batch_max_wait_ms: 500,
                 settlement_timeout_seconds: 60,
+                queue_capacity: 100,
+                lease_timeout_seconds: 120,
+                queue_pause_seconds: 300,
             },
+            retry: RetryConfig::default(),
             monitoring: MonitoringConfig {
                 metrics_port: 9090,
                 health_check_port: 8080,
                 log_level: "info".to_string(),
                 enable_metrics: true,
+                admin_token: None,
             },
             database: DatabaseConfig {
-                url: ":memory:".to_string(),
+                url: "memory:".to_string(),
                 max_connections: 5,
                 connection_timeout_secs: 30,
             },
@@ -0,0 +1,11 @@
+//! Locking helpers shared across modules
+
+use std::sync::{Mutex, MutexGuard};
+
+/// Lock `mutex` even if a thread panicked while holding it.
+///
+/// Only for state every holder leaves consistent before anything can panic,
+/// such as counters and health snapshots updated in place.
+pub(crate) fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
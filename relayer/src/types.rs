@@ -1,6 +1,11 @@
+use crate::{
+    chains::SourceFetchStats,
+    retry::{CircuitBreakerStatus, RetryConfig},
+};
+use base64::{engine::general_purpose, Engine as _};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
 use uuid::Uuid;
 
 /// Core protocol types and definitions for Cyrus cross-chain settlement
@@ -32,12 +37,19 @@ impl fmt::Display for Address {
     }
 }
 
+/// Namespace for settlement IDs derived with UUIDv5
+const SETTLEMENT_ID_NAMESPACE: Uuid = Uuid::from_u128(0x5c1b_7e0e_3f4a_4d6b_9a31_c2f8_e7d4_a901);
+
 /// Settlement instruction from source chain
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SettlementInstruction {
+    /// Derived from source chain, source transaction and event index; see [`Self::derive_id`]
     pub id: Uuid,
     pub source_chain: ChainId,
     pub source_tx_hash: TransactionHash,
+    /// Position of the settlement event within its source transaction
+    #[serde(default)]
+    pub event_index: u32,
     pub destination_chain: ChainId,
     pub sender: Address,
     pub receiver: Address,
@@ -50,6 +62,7 @@ pub struct SettlementInstruction {
 }
 
 impl SettlementInstruction {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         source_chain: ChainId,
         source_tx_hash: TransactionHash,
@@ -63,9 +76,10 @@ impl SettlementInstruction {
         payload: Option<Vec<u8>>,
     ) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: Self::derive_id(&source_chain, &source_tx_hash, 0),
             source_chain,
             source_tx_hash,
+            event_index: 0,
             destination_chain,
             sender,
             receiver,
@@ -78,6 +92,35 @@ impl SettlementInstruction {
         }
     }
 
+    /// Deterministic settlement ID, so the same source event always maps to the same instruction
+    pub fn derive_id(
+        source_chain: &ChainId,
+        source_tx_hash: &TransactionHash,
+        event_index: u32,
+    ) -> Uuid {
+        let name = format!("{}:{}:{}", source_chain, source_tx_hash, event_index);
+        Uuid::new_v5(&SETTLEMENT_ID_NAMESPACE, name.as_bytes())
+    }
+
+    /// Set the event's position within its transaction, re-deriving the ID
+    pub fn with_event_index(mut self, event_index: u32) -> Self {
+        self.event_index = event_index;
+        self.id = Self::derive_id(&self.source_chain, &self.source_tx_hash, event_index);
+        self
+    }
+
+    /// Key the Aptos vault records in `processed_instructions` for this settlement.
+    ///
+    /// The first event of a transaction keeps the bare transaction hash, so
+    /// settlements recorded before events were indexed are still recognised.
+    pub fn settlement_key(&self) -> String {
+        if self.event_index == 0 {
+            self.source_tx_hash.0.clone()
+        } else {
+            format!("{}:{}", self.source_tx_hash, self.event_index)
+        }
+    }
+
     pub fn amount_in_usdc(&self) -> f64 {
         self.amount as f64 / 1_000_000.0
     }
@@ -140,6 +183,19 @@ impl SettlementResult {
         }
     }
 
+    /// The settlement was already recorded on the destination chain by an earlier submission
+    pub fn already_settled(instruction_id: Uuid, retry_count: u32) -> Self {
+        Self {
+            instruction_id,
+            status: SettlementStatus::Completed,
+            destination_tx_hash: None,
+            gas_used: None,
+            error_message: None,
+            processed_at: Utc::now(),
+            retry_count,
+        }
+    }
+
     pub fn pending(instruction_id: Uuid) -> Self {
         Self {
             instruction_id,
@@ -160,6 +216,86 @@ pub enum SettlementStatus {
     Completed,
     Failed,
     Retrying,
+    /// Gave up after repeated or permanent failures; waiting for an operator
+    DeadLettered,
+    /// Dropped by an operator from the dead-letter queue
+    Discarded,
+}
+
+impl SettlementStatus {
+    /// Whether a settlement may move from this status to `next`.
+    ///
+    /// `Processing -> Processing` is allowed so a worker can pick up a
+    /// settlement whose previous lease expired mid-flight.
+    pub fn can_transition_to(&self, next: &SettlementStatus) -> bool {
+        use SettlementStatus::*;
+
+        matches!(
+            (self, next),
+            (Pending, Processing)
+                | (Processing, Processing | Completed | Failed | Retrying)
+                | (Retrying, Processing)
+                | (Failed, Retrying | DeadLettered)
+                | (DeadLettered, Pending | Discarded)
+        )
+    }
+
+    /// Validate a move to `next`, returning it if legal
+    pub fn transition(&self, next: SettlementStatus) -> Result<SettlementStatus, SettlementError> {
+        if self.can_transition_to(&next) {
+            Ok(next)
+        } else {
+            Err(SettlementError::InvalidTransition {
+                from: self.clone(),
+                to: next,
+            })
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SettlementStatus::Pending => "pending",
+            SettlementStatus::Processing => "processing",
+            SettlementStatus::Completed => "completed",
+            SettlementStatus::Failed => "failed",
+            SettlementStatus::Retrying => "retrying",
+            SettlementStatus::DeadLettered => "dead_lettered",
+            SettlementStatus::Discarded => "discarded",
+        }
+    }
+}
+
+impl fmt::Display for SettlementStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for SettlementStatus {
+    type Err = SettlementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(SettlementStatus::Pending),
+            "processing" => Ok(SettlementStatus::Processing),
+            "completed" => Ok(SettlementStatus::Completed),
+            "failed" => Ok(SettlementStatus::Failed),
+            "retrying" => Ok(SettlementStatus::Retrying),
+            "dead_lettered" => Ok(SettlementStatus::DeadLettered),
+            "discarded" => Ok(SettlementStatus::Discarded),
+            other => Err(SettlementError::SerializationError(format!(
+                "Unknown settlement status: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Per-settlement gas settings that replace the configured Aptos defaults
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GasOverrides {
+    pub max_gas_amount: Option<u64>,
+    pub gas_unit_price: Option<u64>,
 }
 
 /// Solana event data
@@ -173,6 +309,9 @@ pub struct SolanaSettlementEvent {
     pub timestamp: u64,
     pub signature: String,
     pub block_time: Option<i64>,
+    /// Position among the settlement events emitted by the same transaction
+    #[serde(default)]
+    pub event_index: u32,
 }
 
 impl From<SolanaSettlementEvent> for SettlementInstruction {
@@ -195,15 +334,111 @@ impl From<SolanaSettlementEvent> for SettlementInstruction {
             timestamp,
             None,
         )
+        .with_event_index(event.event_index)
+    }
+}
+
+/// `SettlementRequested` event emitted with Anchor's `emit!` by the Cyrus Solana program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementRequested {
+    pub source_chain: String,
+    pub aptos_recipient: String,
+    pub amount: u64,
+    pub nonce: u64,
+    pub slot: u64,
+    pub timestamp: u64,
+}
+
+impl SettlementRequested {
+    /// Anchor event discriminator, the first 8 bytes of `sha256("event:SettlementRequested")`
+    pub const DISCRIMINATOR: [u8; 8] = [228, 95, 93, 44, 96, 115, 75, 225];
+
+    /// Log prefix Anchor uses for base64-encoded events
+    pub const LOG_PREFIX: &'static str = "Program data: ";
+
+    /// Decode a `Program data:` log line.
+    ///
+    /// Returns `Ok(None)` for other log lines and for other events, and an
+    /// error if the line carries this event but cannot be decoded.
+    pub fn from_log(log: &str) -> Result<Option<Self>, SettlementError> {
+        let Some(encoded) = log.strip_prefix(Self::LOG_PREFIX) else {
+            return Ok(None);
+        };
+        let Ok(data) = general_purpose::STANDARD.decode(encoded.trim()) else {
+            return Ok(None);
+        };
+        Self::decode(&data)
+    }
+
+    /// Decode Borsh event data, including the leading discriminator
+    pub fn decode(data: &[u8]) -> Result<Option<Self>, SettlementError> {
+        let Some(mut fields) = data.strip_prefix(&Self::DISCRIMINATOR) else {
+            return Ok(None);
+        };
+
+        let event = Self {
+            source_chain: borsh_string(&mut fields)?,
+            aptos_recipient: borsh_string(&mut fields)?,
+            amount: borsh_u64(&mut fields)?,
+            nonce: borsh_u64(&mut fields)?,
+            slot: borsh_u64(&mut fields)?,
+            timestamp: borsh_u64(&mut fields)?,
+        };
+        if !fields.is_empty() {
+            return Err(SettlementError::SerializationError(format!(
+                "{} trailing bytes after SettlementRequested",
+                fields.len()
+            )));
+        }
+        Ok(Some(event))
+    }
+
+    /// Attach the transaction the event was found in
+    pub fn into_event(self, signature: &str, block_time: Option<i64>, event_index: u32) -> SolanaSettlementEvent {
+        SolanaSettlementEvent {
+            source_chain: self.source_chain,
+            aptos_recipient: self.aptos_recipient,
+            amount: self.amount,
+            nonce: self.nonce,
+            slot: self.slot,
+            timestamp: self.timestamp,
+            signature: signature.to_string(),
+            block_time,
+            event_index,
+        }
     }
 }
 
+fn borsh_bytes<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], SettlementError> {
+    if data.len() < len {
+        return Err(SettlementError::SerializationError(
+            "SettlementRequested data is truncated".to_string(),
+        ));
+    }
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
+
+fn borsh_u64(data: &mut &[u8]) -> Result<u64, SettlementError> {
+    let bytes = borsh_bytes(data, 8)?;
+    Ok(u64::from_le_bytes(bytes.try_into().expect("8 bytes")))
+}
+
+fn borsh_string(data: &mut &[u8]) -> Result<String, SettlementError> {
+    let len = borsh_bytes(data, 4)?;
+    let len = u32::from_le_bytes(len.try_into().expect("4 bytes")) as usize;
+    String::from_utf8(borsh_bytes(data, len)?.to_vec())
+        .map_err(|e| SettlementError::SerializationError(format!("Invalid string in SettlementRequested: {}", e)))
+}
+
 /// Relayer configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RelayerConfig {
     pub solana: SolanaConfig,
     pub aptos: AptosConfig,
     pub processing: ProcessingConfig,
+    pub retry: RetryConfig,
     pub monitoring: MonitoringConfig,
     pub database: DatabaseConfig,
 }
@@ -211,10 +446,170 @@ pub struct RelayerConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SolanaConfig {
     pub rpc_url: String,
+    /// More RPC endpoints to fail over to, scored alongside `rpc_url`
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    /// Endpoints that must agree on a transaction and its logs before its
+    /// events are settled; unset trusts whichever endpoint answers
+    #[serde(default)]
+    pub rpc_quorum: Option<usize>,
+    /// How long to wait on one endpoint before failing over
+    pub rpc_timeout_ms: u64,
+    /// Requests each endpoint may be sent per second; 0 sends them unthrottled
+    pub rpc_requests_per_second: u32,
+    /// Transactions fetched at once while catching up
+    pub max_concurrent_fetches: usize,
+    /// PubSub endpoint for `logsSubscribe`; derived from `rpc_url` when unset
+    #[serde(default)]
+    pub ws_url: Option<String>,
     pub program_id: String,
     pub commitment: String,
     pub poll_interval_ms: u64,
     pub max_retries: u32,
+    /// Stream program logs over the WebSocket, polling only to reconcile gaps
+    pub subscribe_logs: bool,
+    /// When a source event is final enough to pay out on Aptos
+    pub finality: FinalityPolicy,
+}
+
+impl SolanaConfig {
+    /// Every RPC endpoint, `rpc_url` first
+    pub fn rpc_urls(&self) -> Vec<String> {
+        std::iter::once(&self.rpc_url)
+            .chain(&self.fallback_rpc_urls)
+            .cloned()
+            .collect()
+    }
+
+    /// WebSocket endpoint for log subscriptions.
+    ///
+    /// Without `ws_url` this is `rpc_url` with a `ws`/`wss` scheme, moving a
+    /// local validator's RPC port 8899 to its PubSub port 8900.
+    pub fn websocket_url(&self) -> String {
+        if let Some(ws_url) = &self.ws_url {
+            return ws_url.clone();
+        }
+
+        let url = if let Some(rest) = self.rpc_url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = self.rpc_url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            self.rpc_url.clone()
+        };
+        url.replacen(":8899", ":8900", 1)
+    }
+}
+
+/// Part of a source program's history to re-scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryRange {
+    /// Transactions in slots `from..=to`, or up to the newest when `to` is unset
+    Slots { from: u64, to: Option<u64> },
+    /// Transactions strictly between two signatures, or up to the newest when
+    /// `before` is unset
+    Signatures { after: String, before: Option<String> },
+}
+
+/// How final a Solana transaction must be before its events are settled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum FinalityPolicy {
+    /// Wait until the transaction is rooted
+    Finalized,
+    /// Wait until the confirmed tip is `slots` past the transaction's slot
+    Confirmed { slots: u64 },
+}
+
+/// Status of a held event's transaction as reported by the source chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceTxStatus {
+    /// Not known to the cluster
+    Missing,
+    /// Landed but execution failed
+    Failed,
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+/// Latest confirmed and rooted slots of the source chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceChainTip {
+    pub confirmed_slot: u64,
+    pub finalized_slot: u64,
+}
+
+/// What to do with an event waiting for finality
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FinalityVerdict {
+    Hold,
+    Release,
+    /// The transaction is gone or failed; the event must never be settled
+    Retract(String),
+}
+
+impl FinalityPolicy {
+    /// Decide on an event seen at `slot` whose transaction now has `status`
+    pub fn evaluate(&self, slot: u64, status: SourceTxStatus, tip: SourceChainTip) -> FinalityVerdict {
+        match status {
+            SourceTxStatus::Failed => FinalityVerdict::Retract("Source transaction failed".to_string()),
+            // Once the event's slot is rooted, a transaction the cluster no longer
+            // knows (or never confirmed) was on an abandoned fork
+            SourceTxStatus::Missing | SourceTxStatus::Processed if tip.finalized_slot >= slot => {
+                FinalityVerdict::Retract(format!(
+                    "Source transaction not on chain after slot {} was finalized",
+                    slot
+                ))
+            }
+            SourceTxStatus::Missing | SourceTxStatus::Processed => FinalityVerdict::Hold,
+            SourceTxStatus::Finalized => FinalityVerdict::Release,
+            SourceTxStatus::Confirmed => match self {
+                FinalityPolicy::Finalized => FinalityVerdict::Hold,
+                FinalityPolicy::Confirmed { slots } if tip.confirmed_slot >= slot.saturating_add(*slots) => {
+                    FinalityVerdict::Release
+                }
+                FinalityPolicy::Confirmed { .. } => FinalityVerdict::Hold,
+            },
+        }
+    }
+}
+
+/// Where a source event stands in the pending-finality buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinalityState {
+    Pending,
+    /// Final enough and handed to the settlement queue
+    Confirmed,
+    /// Dropped because its transaction vanished or failed
+    Retracted,
+}
+
+impl FinalityState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FinalityState::Pending => "pending",
+            FinalityState::Confirmed => "confirmed",
+            FinalityState::Retracted => "retracted",
+        }
+    }
+}
+
+impl FromStr for FinalityState {
+    type Err = SettlementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(FinalityState::Pending),
+            "confirmed" => Ok(FinalityState::Confirmed),
+            "retracted" => Ok(FinalityState::Retracted),
+            other => Err(SettlementError::SerializationError(format!(
+                "Unknown finality state: {}",
+                other
+            ))),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -222,19 +617,80 @@ pub struct AptosConfig {
     pub rpc_url: String,
     pub contract_address: String,
     pub vault_owner: String,
+    /// Hex-encoded Ed25519 key; not needed when `key_file` is set
+    #[serde(default)]
     pub private_key: String,
+    /// Account address, for accounts whose key has been rotated
+    pub account_address: Option<String>,
+    /// Aptos CLI `config.yaml` or encrypted keyfile to load the key from
+    pub key_file: Option<String>,
+    /// Profile to read from an Aptos CLI config, `default` if unset
+    pub key_profile: Option<String>,
+    /// Upper bound on the gas limit derived from simulating a settlement
     pub max_gas_amount: u64,
+    /// Gas price used when the node cannot provide an estimate
     pub gas_unit_price: u64,
+    /// Headroom added to simulated gas usage, in percent
+    pub gas_margin_percent: u64,
+    /// Bounds applied to the node's gas price estimate
+    pub min_gas_unit_price: u64,
+    pub max_gas_unit_price: u64,
+    /// How long to wait for a confirmation before treating a transaction as stuck
     pub transaction_timeout_secs: u64,
+    /// Seconds a submitted transaction stays valid before the chain drops it
+    pub transaction_expiry_secs: u64,
+    /// Replacements sent for a stuck or expired transaction before giving up
+    pub max_resubmissions: u32,
+    /// Gas price increase applied to each replacement, in percent
+    pub gas_price_bump_percent: u64,
+    /// How the vault pays out each token, keyed by token symbol
+    #[serde(default)]
+    pub assets: HashMap<String, AptosAsset>,
+}
+
+impl AptosConfig {
+    /// Registry entry for `token_symbol`; symbols are matched case-insensitively
+    pub fn asset(&self, token_symbol: &str) -> Result<&AptosAsset, SettlementError> {
+        self.assets
+            .iter()
+            .find(|(symbol, _)| symbol.eq_ignore_ascii_case(token_symbol))
+            .map(|(_, asset)| asset)
+            .ok_or_else(|| {
+                SettlementError::InvalidInstruction(format!(
+                    "No Aptos asset registered for {}",
+                    token_symbol
+                ))
+            })
+    }
+}
+
+/// How a token is held by the Aptos vault, which selects the settle entry function
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "standard", rename_all = "snake_case")]
+pub enum AptosAsset {
+    /// The vault's original `Coin<USDC>` balance, paid out by `settle`
+    VaultCoin,
+    /// A coin type such as `0x1::aptos_coin::AptosCoin`, paid out by `settle_coin<T>`
+    Coin { coin_type: String },
+    /// A Fungible Asset identified by its metadata object, paid out by `settle_fa`
+    FungibleAsset { metadata_address: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessingConfig {
     pub max_concurrent_settlements: usize,
     pub batch_size: usize,
-    pub retry_attempts: u32,
-    pub retry_delay_seconds: u64,
+    /// How long a worker waits for more ready instructions before submitting a partial batch
+    pub batch_max_wait_ms: u64,
     pub settlement_timeout_seconds: u64,
+    /// Most instructions the durable queue may hold; ingest from the source
+    /// chain waits, rather than enqueueing more, while it is full
+    pub queue_capacity: usize,
+    /// How long a worker may hold a queued instruction before another worker
+    /// can take it; must cover `batch_max_wait_ms` and `settlement_timeout_seconds`
+    pub lease_timeout_seconds: u64,
+    /// How long workers stop leasing after an error that blocks every settlement
+    pub queue_pause_seconds: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -243,6 +699,10 @@ pub struct MonitoringConfig {
     pub health_check_port: u16,
     pub log_level: String,
     pub enable_metrics: bool,
+    /// Bearer token the dead-letter requeue and discard endpoints require;
+    /// they refuse every request while it is unset
+    #[serde(default)]
+    pub admin_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -253,7 +713,7 @@ pub struct DatabaseConfig {
 }
 
 /// Error types
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum SettlementError {
     #[error("Invalid settlement instruction: {0}")]
     InvalidInstruction(String),
@@ -261,8 +721,12 @@ pub enum SettlementError {
     #[error("Already processed: {0}")]
     AlreadyProcessed(String),
     
-    #[error("Insufficient balance: required {required}, available {available}")]
-    InsufficientBalance { required: u64, available: u64 },
+    /// `available` is `None` when the vault balance could not be read
+    #[error(
+        "Insufficient balance: required {required}, available {}",
+        .available.map_or_else(|| "unknown".to_string(), |available| available.to_string())
+    )]
+    InsufficientBalance { required: u64, available: Option<u64> },
     
     #[error("Chain error: {0}")]
     ChainError(String),
@@ -284,11 +748,215 @@ pub enum SettlementError {
     
     #[error("Timeout error: {0}")]
     Timeout(String),
+
+    #[error("Invalid status transition: {from} -> {to}")]
+    InvalidTransition {
+        from: SettlementStatus,
+        to: SettlementStatus,
+    },
+
+    #[error("Already settled on chain: {0}")]
+    AlreadySettled(String),
+
+    #[error("Relayer not authorized: {0}")]
+    UnauthorizedRelayer(String),
+
+    #[error("Vault not initialized: {0}")]
+    VaultNotInitialized(String),
+
+    #[error("Contract aborted with code {code:#x}: {message}")]
+    ContractAbort { code: u64, message: String },
+
+    #[error("Circuit open for {chain}, next probe in {retry_after:?}")]
+    CircuitOpen { chain: String, retry_after: Duration },
+
+    #[error("Settlement status unknown: {0}")]
+    StatusUnknown(String),
     
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
 
+/// Whether a settlement has been recorded on the destination chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettlementCheck {
+    Settled,
+    NotSettled,
+    /// The chain could not be asked or gave an unreadable answer
+    Unknown(String),
+}
+
+impl SettlementCheck {
+    /// `Ok(true)` if settled, `Ok(false)` if not, and [`SettlementError::StatusUnknown`] otherwise
+    pub fn settled(self) -> Result<bool, SettlementError> {
+        match self {
+            SettlementCheck::Settled => Ok(true),
+            SettlementCheck::NotSettled => Ok(false),
+            SettlementCheck::Unknown(reason) => Err(SettlementError::StatusUnknown(reason)),
+        }
+    }
+}
+
+/// How the processor reacts to an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryClass {
+    /// May succeed if the same settlement is submitted again
+    Transient,
+    /// Will fail the same way every time; the settlement needs an operator
+    Permanent,
+    /// No settlement can succeed until the relayer or vault is fixed, so stop leasing work
+    PauseQueue,
+    /// The settlement already happened; record it as completed
+    Resolved,
+    /// Whether the settlement already happened could not be determined; hold it
+    /// and ask again later without counting an attempt
+    Hold,
+}
+
+impl SettlementError {
+    /// Stable error code, recorded with failed attempts and dead letters
+    pub fn class(&self) -> &'static str {
+        match self {
+            SettlementError::InvalidInstruction(_) => "invalid_instruction",
+            SettlementError::AlreadyProcessed(_) => "already_processed",
+            SettlementError::InsufficientBalance { .. } => "insufficient_balance",
+            SettlementError::ChainError(_) => "chain",
+            SettlementError::NetworkError(_) => "network",
+            SettlementError::ConfigError(_) => "config",
+            SettlementError::DatabaseError(_) => "database",
+            SettlementError::SerializationError(_) => "serialization",
+            SettlementError::TransactionFailed(_) => "transaction_failed",
+            SettlementError::Timeout(_) => "timeout",
+            SettlementError::InvalidTransition { .. } => "invalid_transition",
+            SettlementError::AlreadySettled(_) => "already_settled",
+            SettlementError::UnauthorizedRelayer(_) => "unauthorized_relayer",
+            SettlementError::VaultNotInitialized(_) => "vault_not_initialized",
+            SettlementError::ContractAbort { .. } => "contract_abort",
+            SettlementError::CircuitOpen { .. } => "circuit_open",
+            SettlementError::StatusUnknown(_) => "status_unknown",
+            SettlementError::Unknown(_) => "unknown",
+        }
+    }
+
+    /// How the processor should react to this error
+    pub fn retry_class(&self) -> RetryClass {
+        match self {
+            SettlementError::AlreadyProcessed(_) | SettlementError::AlreadySettled(_) => {
+                RetryClass::Resolved
+            }
+            SettlementError::InsufficientBalance { .. }
+            | SettlementError::UnauthorizedRelayer(_)
+            | SettlementError::VaultNotInitialized(_)
+            | SettlementError::CircuitOpen { .. } => RetryClass::PauseQueue,
+            SettlementError::InvalidInstruction(_)
+            | SettlementError::InvalidTransition { .. }
+            | SettlementError::ContractAbort { .. } => RetryClass::Permanent,
+            SettlementError::StatusUnknown(_) => RetryClass::Hold,
+            _ => RetryClass::Transient,
+        }
+    }
+
+    /// Whether submitting the same settlement again could succeed
+    pub fn is_retryable(&self) -> bool {
+        self.retry_class() == RetryClass::Transient
+    }
+
+    /// Whether the destination chain failed to answer, as opposed to rejecting the settlement
+    pub fn is_chain_failure(&self) -> bool {
+        matches!(self.retry_class(), RetryClass::Transient | RetryClass::Hold)
+    }
+}
+
+/// A Move abort reported in an Aptos transaction's `vm_status`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveAbort {
+    /// Address of the module that aborted, as printed by the node
+    pub module_address: String,
+    pub module_name: String,
+    /// Full abort code, including the `std::error` category
+    pub code: u64,
+}
+
+impl MoveAbort {
+    /// Parse statuses such as
+    /// `Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins`
+    /// or `Move abort in 0xcafe::settlement: 0x80004`.
+    ///
+    /// Returns `None` for anything that is not a Move abort with a readable code.
+    pub fn parse(vm_status: &str) -> Option<Self> {
+        let (_, rest) = vm_status.split_once("Move abort in ")?;
+        let (location, detail) = rest.split_once(": ")?;
+        let (module_address, module_name) = location.trim().rsplit_once("::")?;
+
+        // With an error map the code follows the constant name in parentheses
+        let detail = detail.trim_start();
+        let code = match detail.split_once('(') {
+            Some((_, code)) if code.starts_with("0x") => code,
+            _ => detail,
+        };
+        let hex: String = code
+            .strip_prefix("0x")?
+            .chars()
+            .take_while(char::is_ascii_hexdigit)
+            .collect();
+
+        Some(Self {
+            module_address: module_address.to_string(),
+            module_name: module_name.to_string(),
+            code: u64::from_str_radix(&hex, 16).ok()?,
+        })
+    }
+
+    /// Reason part of the code, i.e. the module's `E_*` constant
+    pub fn reason(&self) -> u64 {
+        self.code & 0xffff
+    }
+}
+
+/// Abort reasons raised by `cyrus_protocol::settlement`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettlementAbort {
+    NotOwner,
+    InsufficientBalance,
+    InvalidInstruction,
+    AlreadySettled,
+    VaultNotInitialized,
+    UnauthorizedRelayer,
+    InvalidAmount,
+    VaultAlreadyExists,
+    AssetNotRegistered,
+}
+
+impl SettlementAbort {
+    /// Name of the Move module that raises these aborts
+    pub const MODULE_NAME: &'static str = "settlement";
+
+    /// Map a reason code to the contract's `E_*` constant
+    pub fn from_reason(reason: u64) -> Option<Self> {
+        match reason {
+            1 => Some(SettlementAbort::NotOwner),
+            2 => Some(SettlementAbort::InsufficientBalance),
+            3 => Some(SettlementAbort::InvalidInstruction),
+            4 => Some(SettlementAbort::AlreadySettled),
+            5 => Some(SettlementAbort::VaultNotInitialized),
+            6 => Some(SettlementAbort::UnauthorizedRelayer),
+            7 => Some(SettlementAbort::InvalidAmount),
+            8 => Some(SettlementAbort::VaultAlreadyExists),
+            9 => Some(SettlementAbort::AssetNotRegistered),
+            _ => None,
+        }
+    }
+
+    /// Decode a settlement module abort, ignoring aborts raised by other modules
+    pub fn from_abort(abort: &MoveAbort) -> Option<Self> {
+        if abort.module_name != Self::MODULE_NAME {
+            return None;
+        }
+        Self::from_reason(abort.reason())
+    }
+}
+
 impl From<serde_json::Error> for SettlementError {
     fn from(err: serde_json::Error) -> Self {
         SettlementError::SerializationError(err.to_string())
@@ -319,6 +987,11 @@ pub struct RelayerMetrics {
     pub uptime_seconds: u64,
     pub vault_balance_usdc: f64,
     pub total_volume_usdc: f64,
+    pub destination_circuit: CircuitBreakerStatus,
+    /// Solana transaction fetch throughput and RPC throttling
+    pub source_fetch: SourceFetchStats,
+    /// Settlements held back because their on-chain status could not be read
+    pub unknown_settlement_checks: u64,
 }
 
 /// Health check status
@@ -421,6 +1094,222 @@ mod tests {
         assert!(failure_result.error_message.is_some());
     }
 
+    #[test]
+    fn test_settlement_status_round_trip() {
+        for status in [
+            SettlementStatus::Pending,
+            SettlementStatus::Processing,
+            SettlementStatus::Completed,
+            SettlementStatus::Failed,
+            SettlementStatus::Retrying,
+            SettlementStatus::DeadLettered,
+            SettlementStatus::Discarded,
+        ] {
+            assert_eq!(status.as_str().parse::<SettlementStatus>().unwrap(), status);
+        }
+        assert!("settled".parse::<SettlementStatus>().is_err());
+    }
+
+    #[test]
+    fn test_move_abort_decoding() {
+        let abort = MoveAbort::parse(
+            "Move abort in 0xcafe::settlement: E_ALREADY_SETTLED(0x80004): ",
+        )
+        .unwrap();
+        assert_eq!(abort.module_address, "0xcafe");
+        assert_eq!(abort.code, 0x80004);
+        assert_eq!(SettlementAbort::from_abort(&abort), Some(SettlementAbort::AlreadySettled));
+
+        // Without an error map the node only prints the code
+        let abort = MoveAbort::parse("Move abort in 0xcafe::settlement: 0x30002").unwrap();
+        assert_eq!(
+            SettlementAbort::from_abort(&abort),
+            Some(SettlementAbort::InsufficientBalance)
+        );
+
+        // Same reason number from another module is not ours
+        let abort = MoveAbort::parse(
+            "Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins",
+        )
+        .unwrap();
+        assert_eq!(abort.reason(), 6);
+        assert_eq!(SettlementAbort::from_abort(&abort), None);
+
+        assert_eq!(MoveAbort::parse("Out of gas"), None);
+        assert_eq!(MoveAbort::parse("Executed successfully"), None);
+    }
+
+    #[test]
+    fn test_solana_websocket_url() {
+        let mut config = SolanaConfig {
+            rpc_url: "https://api.devnet.solana.com".to_string(),
+            fallback_rpc_urls: Vec::new(),
+            rpc_quorum: None,
+            rpc_timeout_ms: 10_000,
+            rpc_requests_per_second: 10,
+            max_concurrent_fetches: 8,
+            ws_url: None,
+            program_id: "11111111111111111111111111111112".to_string(),
+            commitment: "confirmed".to_string(),
+            poll_interval_ms: 1000,
+            max_retries: 3,
+            subscribe_logs: true,
+            finality: FinalityPolicy::Finalized,
+        };
+        assert_eq!(config.websocket_url(), "wss://api.devnet.solana.com");
+
+        config.rpc_url = "http://127.0.0.1:8899".to_string();
+        assert_eq!(config.websocket_url(), "ws://127.0.0.1:8900");
+
+        config.ws_url = Some("wss://stream.example.com/ws".to_string());
+        assert_eq!(config.websocket_url(), "wss://stream.example.com/ws");
+    }
+
+    #[test]
+    fn test_finality_policy() {
+        let tip = SourceChainTip {
+            confirmed_slot: 140,
+            finalized_slot: 100,
+        };
+        let finalized = FinalityPolicy::Finalized;
+        let confirmed = FinalityPolicy::Confirmed { slots: 32 };
+
+        assert_eq!(finalized.evaluate(105, SourceTxStatus::Confirmed, tip), FinalityVerdict::Hold);
+        assert_eq!(finalized.evaluate(95, SourceTxStatus::Finalized, tip), FinalityVerdict::Release);
+
+        // Confirmed plus N slots releases once the confirmed tip is far enough ahead
+        assert_eq!(confirmed.evaluate(108, SourceTxStatus::Confirmed, tip), FinalityVerdict::Release);
+        assert_eq!(confirmed.evaluate(109, SourceTxStatus::Confirmed, tip), FinalityVerdict::Hold);
+
+        // Unknown transactions wait until their slot is rooted, then are retracted
+        assert_eq!(confirmed.evaluate(120, SourceTxStatus::Missing, tip), FinalityVerdict::Hold);
+        assert!(matches!(
+            finalized.evaluate(90, SourceTxStatus::Missing, tip),
+            FinalityVerdict::Retract(_)
+        ));
+        assert!(matches!(
+            confirmed.evaluate(90, SourceTxStatus::Processed, tip),
+            FinalityVerdict::Retract(_)
+        ));
+        assert!(matches!(
+            confirmed.evaluate(120, SourceTxStatus::Failed, tip),
+            FinalityVerdict::Retract(_)
+        ));
+
+        let parsed: FinalityPolicy = serde_json::from_str(r#"{"mode":"confirmed","slots":32}"#).unwrap();
+        assert_eq!(parsed, confirmed);
+        assert_eq!("retracted".parse::<FinalityState>().unwrap(), FinalityState::Retracted);
+    }
+
+    #[test]
+    fn test_asset_registry() {
+        let assets: HashMap<String, AptosAsset> = serde_json::from_value(serde_json::json!({
+            "USDC": {
+                "standard": "fungible_asset",
+                "metadata_address": "0xbae207659db88bea0cbead6da0ed00aac12edcdda169e591cd41c94180b46f3b"
+            },
+            "usdt": { "standard": "coin", "coin_type": "0xcafe::usdt::USDT" },
+            "LEGACY": { "standard": "vault_coin" }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            assets["usdt"],
+            AptosAsset::Coin { coin_type: "0xcafe::usdt::USDT".to_string() }
+        );
+        assert_eq!(assets["LEGACY"], AptosAsset::VaultCoin);
+
+        let config = AptosConfig {
+            rpc_url: String::new(),
+            contract_address: "0x1".to_string(),
+            vault_owner: "0x1".to_string(),
+            private_key: String::new(),
+            account_address: None,
+            key_file: None,
+            key_profile: None,
+            max_gas_amount: 200000,
+            gas_unit_price: 100,
+            gas_margin_percent: 20,
+            min_gas_unit_price: 100,
+            max_gas_unit_price: 10000,
+            transaction_timeout_secs: 30,
+            transaction_expiry_secs: 60,
+            max_resubmissions: 2,
+            gas_price_bump_percent: 20,
+            assets,
+        };
+        assert!(matches!(config.asset("usdc"), Ok(AptosAsset::FungibleAsset { .. })));
+        assert!(matches!(config.asset("USDT"), Ok(AptosAsset::Coin { .. })));
+        assert!(matches!(
+            config.asset("SOL"),
+            Err(SettlementError::InvalidInstruction(_))
+        ));
+    }
+
+    #[test]
+    fn test_insufficient_balance_message() {
+        let known = SettlementError::InsufficientBalance { required: 10, available: Some(4) };
+        assert_eq!(known.to_string(), "Insufficient balance: required 10, available 4");
+        // An unreadable balance is not reported as empty
+        let unknown = SettlementError::InsufficientBalance { required: 10, available: None };
+        assert_eq!(unknown.to_string(), "Insufficient balance: required 10, available unknown");
+    }
+
+    #[test]
+    fn test_error_retry_classes() {
+        assert_eq!(
+            SettlementError::AlreadySettled("tx_1".to_string()).retry_class(),
+            RetryClass::Resolved
+        );
+        assert_eq!(
+            SettlementError::InsufficientBalance { required: 10, available: Some(0) }.retry_class(),
+            RetryClass::PauseQueue
+        );
+        assert_eq!(
+            SettlementError::ContractAbort { code: 0x10007, message: String::new() }.retry_class(),
+            RetryClass::Permanent
+        );
+        assert!(SettlementError::Timeout("slow".to_string()).is_retryable());
+
+        // Not knowing whether it settled is neither a retry nor a failure of the settlement
+        let unknown = SettlementError::StatusUnknown("view call failed".to_string());
+        assert_eq!(unknown.retry_class(), RetryClass::Hold);
+        assert!(!unknown.is_retryable());
+        assert!(unknown.is_chain_failure());
+        assert_eq!(
+            SettlementCheck::Unknown("view call failed".to_string()).settled().unwrap_err().class(),
+            "status_unknown"
+        );
+        assert!(SettlementCheck::Settled.settled().unwrap());
+        assert!(!SettlementError::UnauthorizedRelayer("0x1".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        use SettlementStatus::*;
+
+        assert!(Pending.can_transition_to(&Processing));
+        assert!(Processing.can_transition_to(&Processing));
+        assert!(Processing.can_transition_to(&Completed));
+        assert!(Failed.can_transition_to(&Retrying));
+        assert!(Failed.can_transition_to(&DeadLettered));
+        assert!(DeadLettered.can_transition_to(&Pending));
+
+        assert!(!Completed.can_transition_to(&Pending));
+        assert!(!Pending.can_transition_to(&Completed));
+        assert!(!Discarded.can_transition_to(&Pending));
+        assert!(!DeadLettered.can_transition_to(&Processing));
+
+        match Completed.transition(Pending) {
+            Err(SettlementError::InvalidTransition { from, to }) => {
+                assert_eq!(from, Completed);
+                assert_eq!(to, Pending);
+            }
+            other => panic!("Expected invalid transition, got {:?}", other),
+        }
+        assert_eq!(Retrying.transition(Processing).unwrap(), Processing);
+    }
+
     #[test]
     fn test_solana_event_conversion() {
         let event = SolanaSettlementEvent {
@@ -432,6 +1321,7 @@ mod tests {
             timestamp: 1640995200,
             signature: "test_signature".to_string(),
             block_time: Some(1640995200),
+            event_index: 0,
         };
 
         let instruction: SettlementInstruction = event.into();
@@ -439,4 +1329,88 @@ mod tests {
         assert_eq!(instruction.nonce, 42);
         assert_eq!(instruction.receiver.0, "0x123");
     }
+
+    fn encode_settlement_requested(event: &SettlementRequested) -> Vec<u8> {
+        let mut data = SettlementRequested::DISCRIMINATOR.to_vec();
+        for field in [&event.source_chain, &event.aptos_recipient] {
+            data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            data.extend_from_slice(field.as_bytes());
+        }
+        for field in [event.amount, event.nonce, event.slot, event.timestamp] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_settlement_requested_decoding() {
+        let digest = solana_sdk::hash::hash(b"event:SettlementRequested");
+        assert_eq!(digest.to_bytes()[..8], SettlementRequested::DISCRIMINATOR);
+
+        let event = SettlementRequested {
+            source_chain: "solana".to_string(),
+            aptos_recipient: "0x123".to_string(),
+            amount: 1_000_000,
+            nonce: 42,
+            slot: 12345,
+            timestamp: 1640995200,
+        };
+        let data = encode_settlement_requested(&event);
+        let log = format!("{}{}", SettlementRequested::LOG_PREFIX, general_purpose::STANDARD.encode(&data));
+        assert_eq!(SettlementRequested::from_log(&log).unwrap(), Some(event.clone()));
+
+        let decoded = event.into_event("sig", Some(1640995200), 2);
+        assert_eq!(decoded.signature, "sig");
+        assert_eq!(decoded.event_index, 2);
+        assert_eq!(decoded.nonce, 42);
+
+        // Other logs and other events are skipped
+        assert_eq!(SettlementRequested::from_log("Program log: Instruction: Settle").unwrap(), None);
+        let mut other = data.clone();
+        other[0] ^= 0xff;
+        assert_eq!(SettlementRequested::decode(&other).unwrap(), None);
+
+        // Malformed event data is an error
+        assert!(SettlementRequested::decode(&data[..data.len() - 1]).is_err());
+        let mut trailing = data;
+        trailing.push(0);
+        assert!(SettlementRequested::decode(&trailing).is_err());
+    }
+
+    #[test]
+    fn test_settlement_id_is_deterministic() {
+        let create = |tx_hash: &str| {
+            SettlementInstruction::new(
+                ChainId("solana".to_string()),
+                TransactionHash(tx_hash.to_string()),
+                ChainId("aptos".to_string()),
+                Address("sender".to_string()),
+                Address("0x123".to_string()),
+                "USDC".to_string(),
+                1000000,
+                1,
+                Utc::now(),
+                None,
+            )
+        };
+
+        // The same event seen twice collapses to one ID
+        assert_eq!(create("tx_1").id, create("tx_1").id);
+        assert_ne!(create("tx_1").id, create("tx_2").id);
+
+        let second_event = create("tx_1").with_event_index(1);
+        assert_eq!(second_event.event_index, 1);
+        assert_ne!(second_event.id, create("tx_1").id);
+
+        assert_eq!(create("tx_1").settlement_key(), "tx_1");
+        assert_eq!(second_event.settlement_key(), "tx_1:1");
+        assert_eq!(
+            second_event.id,
+            SettlementInstruction::derive_id(
+                &ChainId("solana".to_string()),
+                &TransactionHash("tx_1".to_string()),
+                1
+            )
+        );
+    }
 }
\ No newline at end of file